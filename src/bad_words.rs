@@ -0,0 +1,331 @@
+//! Checking and masking text against Pixiv's `bad_words()` list, locally. Requires the `bad-words` feature.
+//!
+//! Words and text are both normalised (see `text::normalize`) before matching, so `ｶﾞ`, `ガ`,
+//! `ＡＢＣ` and `abc` are caught alike. Masking replaces the original characters, whatever width they were.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # use pixiv::bad_words::BadWords;
+//! # fn main() {
+//!     let bad_words = BadWords::new(vec!["spoiler", "ネタバレ"]);
+//!     assert!(bad_words.is_match("No ＳＰＯＩＬＥＲＳ please"));
+//!     assert_eq!(bad_words.mask("ﾈﾀﾊﾞﾚ注意"), "*****注意");
+//! # }
+//! ```
+
+use std::ops::Range;
+#[cfg(feature = "reqwest-client")]
+use std::sync::Arc;
+#[cfg(feature = "reqwest-client")]
+use std::time::{Duration, Instant};
+
+use aho_corasick::{AhoCorasick, MatchKind};
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+use models::{BadWord, Comment, PixivResponse, Work};
+use text::{self, Normalized};
+#[cfg(feature = "reqwest-client")]
+use PixivRequestBuilder;
+
+/// The character `BadWords::mask` replaces matches with.
+pub const MASK: char = '*';
+
+/// A set of bad words, matched against normalised text.
+#[derive(Debug, Clone)]
+pub struct BadWords {
+    words: Vec<String>,
+    matcher: Option<AhoCorasick>,
+}
+
+/// A bad word found in some text. `start` and `end` are byte offsets into the text as given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadWordMatch {
+    pub word: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A field of a `Work` which contained a bad word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkField {
+    Title,
+    Caption,
+    /// The tag at this index of `Work::tags`.
+    Tag(usize),
+}
+
+impl BadWords {
+    /// Creates a set from the given words. Duplicates after normalisation, and empty words, are dropped.
+    pub fn new<I, S>(words: I) -> BadWords
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut normalized: Vec<String> = Vec::new();
+        for word in words {
+            let word = text::normalize(word.as_ref().trim());
+            if !word.is_empty() && !normalized.contains(&word) {
+                normalized.push(word);
+            }
+        }
+        let matcher = if normalized.is_empty() {
+            None
+        } else {
+            let matcher = AhoCorasick::builder()
+                .match_kind(MatchKind::LeftmostLongest)
+                .build(&normalized)
+                .expect("Bad words should always build a matcher.");
+            Some(matcher)
+        };
+        BadWords { words: normalized, matcher }
+    }
+
+    /// Creates a set from a `bad_words()` response.
+    pub fn from_response(response: &PixivResponse<BadWord>) -> BadWords {
+        BadWords::new(response.response.iter().map(|bad_word| &bad_word.word))
+    }
+
+    /// Fetches the current list with `bad_words()`.
+    #[cfg(feature = "reqwest-client")]
+    pub fn fetch(pixiv: &Pixiv) -> Result<BadWords, FetchError> {
+        let response: PixivResponse<BadWord> = pixiv.fetch(PixivRequestBuilder::bad_words())?;
+        Ok(BadWords::from_response(&response))
+    }
+
+    /// Get the words, normalised.
+    #[inline]
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Get the number of words.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether there are no words, in which case nothing matches.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Finds the bad words in `text`, leftmost first, preferring the longest word at each position.
+    pub fn find(&self, text: &str) -> Vec<BadWordMatch> {
+        let matcher = match self.matcher {
+            Some(ref matcher) => matcher,
+            None => return Vec::new(),
+        };
+        let normalized = Normalized::new(text);
+        matcher
+            .find_iter(&normalized.text)
+            .map(|m| {
+                let range = normalized.original_range(m.start()..m.end());
+                BadWordMatch {
+                    word: self.words[m.pattern().as_usize()].clone(),
+                    start: range.start,
+                    end: range.end,
+                }
+            })
+            .collect()
+    }
+
+    /// Whether `text` contains any bad word.
+    pub fn is_match(&self, text: &str) -> bool {
+        match self.matcher {
+            // The same text `find` searches, since normalising segment by segment can differ from
+            // normalising the whole.
+            Some(ref matcher) => matcher.is_match(&Normalized::new(text).text),
+            None => false,
+        }
+    }
+
+    /// Replaces every character of each bad word in `text` with `MASK`.
+    pub fn mask(&self, text: &str) -> String {
+        self.mask_with(text, MASK)
+    }
+
+    /// Replaces every character of each bad word in `text` with `mask`.
+    pub fn mask_with(&self, text: &str, mask: char) -> String {
+        let mut masked = String::with_capacity(text.len());
+        let mut last = 0;
+        for range in merge(self.find(text).into_iter().map(|m| m.start..m.end)) {
+            masked.push_str(&text[last..range.start]);
+            masked.extend(text[range.clone()].chars().map(|_| mask));
+            last = range.end;
+        }
+        masked.push_str(&text[last..]);
+        masked
+    }
+
+    /// Checks the title, caption and tags (original and translated) of a work, returning each field with a bad word and the first word found.
+    pub fn check_work(&self, work: &Work) -> Vec<(WorkField, String)> {
+        let mut found = Vec::new();
+        let first = |text: &str| self.find(text).into_iter().next().map(|m| m.word);
+        if let Some(word) = first(&work.title) {
+            found.push((WorkField::Title, word));
+        }
+        if let Some(word) = work.caption.as_deref().and_then(first) {
+            found.push((WorkField::Caption, word));
+        }
+        for (i, tag) in work.tags.iter().enumerate() {
+            if let Some(word) = first(&tag.name).or_else(|| tag.translated_name.as_deref().and_then(first)) {
+                found.push((WorkField::Tag(i), word));
+            }
+        }
+        found
+    }
+
+    /// Masks bad words in the title, caption and tags of a work in place. Returns whether anything was masked.
+    pub fn mask_work(&self, work: &mut Work) -> bool {
+        let mut changed = self.mask_in_place(&mut work.title);
+        if let Some(ref mut caption) = work.caption {
+            changed |= self.mask_in_place(caption);
+        }
+        for tag in &mut work.tags {
+            changed |= self.mask_in_place(&mut tag.name);
+            if let Some(ref mut translated) = tag.translated_name {
+                changed |= self.mask_in_place(translated);
+            }
+        }
+        changed
+    }
+
+    /// Masks bad words in a comment, and in the comment it replies to, in place. Returns whether anything was masked.
+    pub fn mask_comment(&self, comment: &mut Comment) -> bool {
+        let mut changed = self.mask_in_place(&mut comment.comment);
+        if let Some(ref mut parent) = comment.parent_comment {
+            changed |= self.mask_comment(parent);
+        }
+        changed
+    }
+
+    fn mask_in_place(&self, text: &mut String) -> bool {
+        if !self.is_match(text) {
+            return false;
+        }
+        *text = self.mask(text);
+        true
+    }
+}
+
+// Merges sorted, possibly overlapping ranges.
+fn merge<I: IntoIterator<Item = Range<usize>>>(ranges: I) -> Vec<Range<usize>> {
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Keeps a `BadWords` fetched from Pixiv, refetching it once it is older than the refresh interval.
+#[cfg(feature = "reqwest-client")]
+#[derive(Debug, Clone)]
+pub struct BadWordsCache {
+    refresh: Duration,
+    cached: Option<(Instant, Arc<BadWords>)>,
+}
+
+#[cfg(feature = "reqwest-client")]
+impl BadWordsCache {
+    /// Creates an empty cache which refetches after `refresh`.
+    pub fn new(refresh: Duration) -> BadWordsCache {
+        BadWordsCache { refresh, cached: None }
+    }
+
+    /// Get the refresh interval.
+    #[inline]
+    pub fn refresh(&self) -> Duration {
+        self.refresh
+    }
+
+    /// Returns the cached list, fetching it first if there is none or it is due a refresh.
+    /// If a refresh fails, the error is returned and the old list is kept.
+    pub fn get(&mut self, pixiv: &Pixiv) -> Result<Arc<BadWords>, FetchError> {
+        if let Some((fetched_at, ref bad_words)) = self.cached {
+            if fetched_at.elapsed() < self.refresh {
+                return Ok(Arc::clone(bad_words));
+            }
+        }
+        let bad_words = Arc::new(BadWords::fetch(pixiv)?);
+        self.cached = Some((Instant::now(), Arc::clone(&bad_words)));
+        Ok(bad_words)
+    }
+
+    /// Drops the cached list, so the next `get` refetches it.
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{self, json};
+
+    #[test]
+    fn test_find() {
+        let bad_words = BadWords::new(vec!["Spoiler", "ｽﾎﾟｲﾗｰ", "spoilers", "", "SPOILER"]);
+        assert_eq!(bad_words.words(), &["spoiler", "スポイラー", "spoilers"]);
+
+        let text = "Ｓｐｏｉｌｅｒｓ and スポイラー";
+        let found = bad_words.find(text);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].word, "spoilers");
+        assert_eq!(&text[found[0].start..found[0].end], "Ｓｐｏｉｌｅｒｓ");
+        assert_eq!(&text[found[1].start..found[1].end], "スポイラー");
+        assert!(!bad_words.is_match("spoil"));
+        assert!(!BadWords::new(Vec::<String>::new()).is_match("anything"));
+    }
+
+    #[test]
+    fn test_mask() {
+        let bad_words = BadWords::new(vec!["ネタバレ", "abc"]);
+        assert_eq!(bad_words.mask("ﾈﾀﾊﾞﾚ and ABC"), "***** and ***");
+        assert_eq!(bad_words.mask_with("xＡＢＣx", '#'), "x###x");
+        assert_eq!(bad_words.mask("nothing"), "nothing");
+
+        let mut work: Work = serde_json::from_value(json!({
+            "id": 1,
+            "title": "ネタバレ",
+            "caption": "fine",
+            "tags": ["ok", "ＡＢＣ"]
+        })).unwrap();
+        assert_eq!(
+            bad_words.check_work(&work),
+            vec![(WorkField::Title, "ネタバレ".to_owned()), (WorkField::Tag(1), "abc".to_owned())]
+        );
+        assert!(bad_words.mask_work(&mut work));
+        assert_eq!(work.title, "****");
+        assert_eq!(work.tags[1].name, "***");
+        assert!(!bad_words.mask_work(&mut work));
+
+        // Conjoining jamo only compose when normalised together, which `find` doesn't do.
+        let jamo = BadWords::new(vec!["\u{ac00}"]);
+        let mut text = "\u{1100}\u{1161}".to_owned();
+        assert_eq!(jamo.is_match(&text), !jamo.find(&text).is_empty());
+        assert!(!jamo.mask_in_place(&mut text));
+
+        let mut comment: Comment = serde_json::from_value(json!({
+            "id": 2,
+            "comment": "(happy) ok",
+            "parent_comment": {"id": 1, "comment": "ｎｅｔａ abc"}
+        })).unwrap();
+        assert!(bad_words.mask_comment(&mut comment));
+        assert_eq!(comment.comment, "(happy) ok");
+        assert_eq!(comment.parent_comment.unwrap().comment, "ｎｅｔａ ***");
+    }
+
+    #[test]
+    fn test_from_response() {
+        let response: PixivResponse<BadWord> = serde_json::from_value(json!({
+            "status": "success",
+            "response": ["abc", {"word": "def"}]
+        })).unwrap();
+        assert_eq!(BadWords::from_response(&response).words(), &["abc", "def"]);
+    }
+}
@@ -0,0 +1,750 @@
+//! Command-line interface to the Pixiv API, built with the `cli` feature.
+//!
+//! Run `pixiv help` for usage. The session is stored between invocations, by default in
+//! `<config dir>/pixiv/session.json`.
+
+#[macro_use]
+extern crate clap;
+extern crate dirs;
+extern crate pixiv;
+extern crate rpassword;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate unicode_width;
+
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use pixiv::client::Pixiv;
+use pixiv::http::StatusCode;
+use pixiv::models::{PixivResponse, Ranking, User, Work};
+use pixiv::params::Params;
+use pixiv::reqwest::Client;
+use pixiv::{
+    ImageSize, PixivRequestBuilder, Publicity, RankingMode, RankingType, SearchMode, SearchOrder,
+    SearchPeriod, SearchSort, WorkType,
+};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+const TITLE_WIDTH: usize = 40;
+
+/// Tokens stored between invocations.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Session {
+    access_token: String,
+    refresh_token: String,
+}
+
+struct Cli {
+    pixiv: Pixiv,
+    session_path: PathBuf,
+    json: bool,
+}
+
+impl Cli {
+    fn new(matches: &ArgMatches) -> CliResult<Cli> {
+        let session_path = match matches.value_of("session") {
+            Some(path) => PathBuf::from(path),
+            None => match env::var_os("PIXIV_SESSION") {
+                Some(path) => PathBuf::from(path),
+                None => dirs::config_dir()
+                    .ok_or("Unable to find a config directory, use --session instead.")?
+                    .join("pixiv")
+                    .join("session.json"),
+            },
+        };
+        Ok(Cli {
+            pixiv: Pixiv::new(&Client::new()),
+            session_path,
+            json: matches.is_present("json"),
+        })
+    }
+
+    fn load_session(&mut self) -> CliResult<()> {
+        let file = File::open(&self.session_path).map_err(|e| {
+            format!(
+                "Unable to read session from {}: {}. Run `pixiv login` first.",
+                self.session_path.display(),
+                e
+            )
+        })?;
+        let session: Session = serde_json::from_reader(file)?;
+        *self.pixiv.access_token_mut() = session.access_token;
+        *self.pixiv.refresh_token_mut() = session.refresh_token;
+        Ok(())
+    }
+
+    fn save_session(&self) -> CliResult<()> {
+        if let Some(dir) = self.session_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let session = Session {
+            access_token: self.pixiv.access_token().clone(),
+            refresh_token: self.pixiv.refresh_token().clone(),
+        };
+        let file = create_private(&self.session_path)?;
+        serde_json::to_writer_pretty(file, &session)?;
+        Ok(())
+    }
+
+    /// Executes the request, refreshing the session once if the access token has expired.
+    fn fetch<P: Params, T: DeserializeOwned>(&mut self, builder: PixivRequestBuilder<P>) -> CliResult<T> {
+        let request = builder.try_build()?;
+        let mut response = self.pixiv.execute(request.clone())?;
+        match response.status() {
+            StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED if !self.pixiv.refresh_token().is_empty() => {
+                self.pixiv.refresh_auth()?;
+                self.save_session()?;
+                response = self.pixiv.execute(request)?;
+            }
+            _ => {}
+        }
+        if !response.status().is_success() {
+            return Err(format!("Request failed ({}): {}", response.status(), response.text()?).into());
+        }
+        Ok(response.json()?)
+    }
+
+    fn print_json<T: Serialize>(&self, value: &T) -> CliResult<()> {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        serde_json::to_writer_pretty(&mut stdout, value)?;
+        writeln!(stdout)?;
+        Ok(())
+    }
+
+    fn print_works(&self, works: &[Work]) -> CliResult<()> {
+        if self.json {
+            return self.print_json(&works);
+        }
+        let rows = works
+            .iter()
+            .map(|work| {
+                let stats = work.stats.clone().unwrap_or_default();
+                vec![
+                    work.id.to_string(),
+                    truncate(&work.title, TITLE_WIDTH),
+                    work.user.as_ref().map(|user| user.name.clone()).unwrap_or_default(),
+                    work.work_type.map(|t| t.to_string()).unwrap_or_default(),
+                    optional(work.page_count),
+                    optional(stats.views_count),
+                    optional(stats.favorited_count.map(|count| count.public + count.private)),
+                    optional(work.created_time),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(
+            &["ID", "TITLE", "USER", "TYPE", "PAGES", "VIEWS", "BOOKMARKS", "CREATED"],
+            &rows,
+        );
+        Ok(())
+    }
+
+    fn print_users(&self, users: &[User]) -> CliResult<()> {
+        if self.json {
+            return self.print_json(&users);
+        }
+        let rows = users
+            .iter()
+            .map(|user| {
+                vec![
+                    user.id.to_string(),
+                    user.account.clone(),
+                    user.name.clone(),
+                    optional(user.is_following),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["ID", "ACCOUNT", "NAME", "FOLLOWING"], &rows);
+        Ok(())
+    }
+
+    fn print_work(&self, work: &Work) -> CliResult<()> {
+        if self.json {
+            return self.print_json(work);
+        }
+        let stats = work.stats.clone().unwrap_or_default();
+        let mut rows = vec![
+            vec!["ID".to_owned(), work.id.to_string()],
+            vec!["Title".to_owned(), work.title.clone()],
+            vec![
+                "User".to_owned(),
+                work.user.as_ref().map(ToString::to_string).unwrap_or_default(),
+            ],
+            vec![
+                "Type".to_owned(),
+                work.work_type.map(|t| t.to_string()).unwrap_or_default(),
+            ],
+            vec!["Pages".to_owned(), optional(work.page_count)],
+            vec!["Tags".to_owned(), work.tags.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")],
+            vec!["Views".to_owned(), optional(stats.views_count)],
+            vec![
+                "Bookmarks".to_owned(),
+                optional(stats.favorited_count.map(|count| count.public + count.private)),
+            ],
+            vec!["Sanity level".to_owned(), work.sanity_level.clone().unwrap_or_default()],
+            vec!["Created".to_owned(), optional(work.created_time)],
+        ];
+        for (size, url) in &work.image_urls {
+            rows.push(vec![size.to_string(), url.to_owned()]);
+        }
+        print_table(&["FIELD", "VALUE"], &rows);
+        Ok(())
+    }
+
+    fn print_user(&self, user: &User) -> CliResult<()> {
+        if self.json {
+            return self.print_json(user);
+        }
+        let stats = user.stats.clone().unwrap_or_default();
+        let rows = vec![
+            vec!["ID".to_owned(), user.id.to_string()],
+            vec!["Account".to_owned(), user.account.clone()],
+            vec!["Name".to_owned(), user.name.clone()],
+            vec!["Following".to_owned(), optional(user.is_following)],
+            vec!["Works".to_owned(), optional(stats.works)],
+            vec!["Favorites".to_owned(), optional(stats.favorites)],
+            vec!["Follows".to_owned(), optional(stats.following)],
+        ];
+        print_table(&["FIELD", "VALUE"], &rows);
+        Ok(())
+    }
+
+    fn print_rankings(&self, rankings: &[Ranking]) -> CliResult<()> {
+        if self.json {
+            return self.print_json(&rankings);
+        }
+        let rows = rankings
+            .iter()
+            .flat_map(|ranking| &ranking.works)
+            .map(|ranked| {
+                vec![
+                    ranked.rank.to_string(),
+                    match ranked.previous_rank {
+                        0 => "new".to_owned(),
+                        rank => rank.to_string(),
+                    },
+                    ranked.work.id.to_string(),
+                    truncate(&ranked.work.title, TITLE_WIDTH),
+                    ranked.work.user.as_ref().map(|user| user.name.clone()).unwrap_or_default(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["RANK", "PREV", "ID", "TITLE", "USER"], &rows);
+        Ok(())
+    }
+}
+
+fn main() {
+    let matches = app().get_matches();
+    if let Err(e) = run(&matches) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn app<'a, 'b>() -> App<'a, 'b> {
+    let page = Arg::with_name("page")
+        .long("page")
+        .takes_value(true)
+        .value_name("N")
+        .help("Page of results to fetch");
+    let per_page = Arg::with_name("per-page")
+        .long("per-page")
+        .takes_value(true)
+        .value_name("N")
+        .help("Amount of results per page");
+    let private = Arg::with_name("private")
+        .long("private")
+        .help("Use private instead of public bookmarks and follows");
+    let date = Arg::with_name("date")
+        .long("date")
+        .takes_value(true)
+        .value_name("YYYY-MM-DD");
+
+    App::new("pixiv")
+        .version(crate_version!())
+        .about("Command-line interface to the Pixiv API.")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .setting(AppSettings::VersionlessSubcommands)
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .global(true)
+                .help("Print responses as JSON instead of tables"),
+        )
+        .arg(
+            Arg::with_name("session")
+                .long("session")
+                .global(true)
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Where the session is stored, also read from PIXIV_SESSION"),
+        )
+        .subcommand(
+            SubCommand::with_name("login")
+                .about("Logs in and stores the session. The password is read from PIXIV_PW, or prompted for")
+                .arg(Arg::with_name("username").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("work")
+                .about("Shows a work")
+                .arg(Arg::with_name("id").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("user")
+                .about("Shows a user")
+                .arg(Arg::with_name("id").required(true))
+                .arg(Arg::with_name("works").long("works").help("List the user's works instead"))
+                .arg(page.clone())
+                .arg(per_page.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("search")
+                .about("Searches for works")
+                .arg(Arg::with_name("query").required(true))
+                .arg(
+                    Arg::with_name("mode")
+                        .long("mode")
+                        .takes_value(true)
+                        .possible_values(&["text", "tag", "exact_tag", "caption"]),
+                )
+                .arg(
+                    Arg::with_name("period")
+                        .long("period")
+                        .takes_value(true)
+                        .possible_values(&["all", "day", "week", "month"]),
+                )
+                .arg(
+                    Arg::with_name("sort")
+                        .long("sort")
+                        .takes_value(true)
+                        .possible_values(&["date", "popular", "popular_male", "popular_female"]),
+                )
+                .arg(
+                    Arg::with_name("order")
+                        .long("order")
+                        .takes_value(true)
+                        .possible_values(&["desc", "asc"]),
+                )
+                .arg(
+                    Arg::with_name("type")
+                        .long("type")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .possible_values(&["illustration", "manga", "ugoira"]),
+                )
+                .arg(date.clone())
+                .arg(page.clone())
+                .arg(per_page.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("ranking")
+                .about("Shows a ranking")
+                .arg(
+                    Arg::with_name("type")
+                        .long("type")
+                        .takes_value(true)
+                        .default_value("all")
+                        .possible_values(&["all", "illust", "manga", "ugoira"]),
+                )
+                .arg(
+                    Arg::with_name("mode")
+                        .long("mode")
+                        .takes_value(true)
+                        .default_value("daily")
+                        .possible_values(RANKING_MODES),
+                )
+                .arg(date)
+                .arg(page.clone())
+                .arg(per_page.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("following")
+                .about("Lists users you follow")
+                .arg(
+                    Arg::with_name("works")
+                        .long("works")
+                        .help("List the newest works of users you follow instead"),
+                )
+                .arg(private.clone())
+                .arg(page.clone())
+                .arg(per_page.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("bookmark")
+                .about("Manages bookmarked works")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists bookmarked works")
+                        .arg(private.clone())
+                        .arg(page)
+                        .arg(per_page),
+                )
+                .subcommand(
+                    SubCommand::with_name("add")
+                        .about("Bookmarks a work")
+                        .arg(Arg::with_name("id").required(true))
+                        .arg(private.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Removes bookmarked works")
+                        .arg(Arg::with_name("id").required(true).multiple(true))
+                        .arg(private),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("download")
+                .about("Downloads every page of works")
+                .arg(Arg::with_name("id").required(true).multiple(true))
+                .arg(
+                    Arg::with_name("size")
+                        .long("size")
+                        .takes_value(true)
+                        .default_value("large")
+                        .possible_values(&["px_128x128", "px_480mw", "small", "medium", "large"]),
+                )
+                .arg(
+                    Arg::with_name("dir")
+                        .long("dir")
+                        .takes_value(true)
+                        .default_value(".")
+                        .help("Directory to save the files in"),
+                ),
+        )
+}
+
+fn run(matches: &ArgMatches) -> CliResult<()> {
+    let mut cli = Cli::new(matches)?;
+    if let ("login", Some(matches)) = matches.subcommand() {
+        return login(&mut cli, matches);
+    }
+    cli.load_session()?;
+    match matches.subcommand() {
+        ("work", Some(matches)) => {
+            let id = value_t!(matches, "id", usize)?;
+            let response: PixivResponse<Work> = cli.fetch(PixivRequestBuilder::work(id))?;
+            match response.response.first() {
+                Some(work) => cli.print_work(work),
+                None => Err(format!("Work {} not found.", id).into()),
+            }
+        }
+        ("user", Some(matches)) => {
+            let id = value_t!(matches, "id", usize)?;
+            if matches.is_present("works") {
+                let builder = paged(PixivRequestBuilder::user_works(id), matches)?;
+                let response: PixivResponse<Work> = cli.fetch(builder)?;
+                return cli.print_works(&response.response);
+            }
+            let response: PixivResponse<User> = cli.fetch(PixivRequestBuilder::user(id))?;
+            match response.response.first() {
+                Some(user) => cli.print_user(user),
+                None => Err(format!("User {} not found.", id).into()),
+            }
+        }
+        ("search", Some(matches)) => {
+            let mut builder = PixivRequestBuilder::search_works(matches.value_of("query").unwrap());
+            if let Some(mode) = matches.value_of("mode") {
+                builder = builder.search_mode(search_mode(mode));
+            }
+            if let Some(period) = matches.value_of("period") {
+                builder = builder.search_period(search_period(period));
+            }
+            if let Some(sort) = matches.value_of("sort") {
+                builder = builder.search_sort(search_sort(sort));
+            }
+            if let Some(order) = matches.value_of("order") {
+                builder = builder.search_order(search_order(order));
+            }
+            if let Some(types) = matches.values_of("type") {
+                builder = builder.search_types(&types.map(work_type).collect::<Vec<_>>());
+            }
+            if let Some(date) = matches.value_of("date") {
+                builder = builder.try_date(date)?;
+            }
+            let response: PixivResponse<Work> = cli.fetch(paged(builder, matches)?)?;
+            cli.print_works(&response.response)
+        }
+        ("ranking", Some(matches)) => {
+            let ranking_type = RankingType::from_str(matches.value_of("type").unwrap()).unwrap();
+            let mut builder = PixivRequestBuilder::ranking(ranking_type)
+                .ranking_mode(RankingMode::from_str(matches.value_of("mode").unwrap()).unwrap());
+            if let Some(date) = matches.value_of("date") {
+                builder = builder.try_date(date)?;
+            }
+            let response: PixivResponse<Ranking> = cli.fetch(paged(builder, matches)?)?;
+            cli.print_rankings(&response.response)
+        }
+        ("following", Some(matches)) => {
+            if matches.is_present("works") {
+                let builder = paged(PixivRequestBuilder::following_works(), matches)?;
+                let response: PixivResponse<Work> = cli.fetch(builder)?;
+                return cli.print_works(&response.response);
+            }
+            let builder = PixivRequestBuilder::following().publicity(publicity(matches));
+            let response: PixivResponse<User> = cli.fetch(paged(builder, matches)?)?;
+            cli.print_users(&response.response)
+        }
+        ("bookmark", Some(matches)) => bookmark(&mut cli, matches),
+        ("download", Some(matches)) => download(&mut cli, matches),
+        _ => unreachable!(),
+    }
+}
+
+fn login(cli: &mut Cli, matches: &ArgMatches) -> CliResult<()> {
+    let username = matches.value_of("username").unwrap();
+    // never taken as an argument, which would leave it in shell history and process listings
+    let password = match env::var("PIXIV_PW") {
+        Ok(password) => password,
+        Err(_) => rpassword::prompt_password("Password: ")?,
+    };
+    cli.pixiv.login(username, &password)?;
+    cli.save_session()?;
+    eprintln!("Logged in, session saved to {}", cli.session_path.display());
+    Ok(())
+}
+
+fn bookmark(cli: &mut Cli, matches: &ArgMatches) -> CliResult<()> {
+    match matches.subcommand() {
+        ("list", Some(matches)) => {
+            let builder = PixivRequestBuilder::favorite_works().publicity(publicity(matches));
+            let response: PixivResponse<FavoriteWork> = cli.fetch(paged(builder, matches)?)?;
+            let works = response
+                .response
+                .into_iter()
+                .map(|favorite| favorite.work)
+                .collect::<Vec<_>>();
+            cli.print_works(&works)
+        }
+        ("add", Some(matches)) => {
+            let id = value_t!(matches, "id", usize)?;
+            let builder = PixivRequestBuilder::favorite_work_add(id).publicity(publicity(matches));
+            let _: serde_json::Value = cli.fetch(builder)?;
+            eprintln!("Bookmarked {}", id);
+            Ok(())
+        }
+        ("remove", Some(matches)) => {
+            let ids = values_t!(matches, "id", usize)?;
+            let builder = PixivRequestBuilder::favorite_works_remove(&ids).publicity(publicity(matches));
+            let _: serde_json::Value = cli.fetch(builder)?;
+            eprintln!("Removed {} bookmark(s)", ids.len());
+            Ok(())
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Entry of `favorite_works()`, which wraps the favorited work.
+#[derive(Deserialize)]
+struct FavoriteWork {
+    work: Work,
+}
+
+fn download(cli: &mut Cli, matches: &ArgMatches) -> CliResult<()> {
+    let ids = values_t!(matches, "id", usize)?;
+    let size = image_size(matches.value_of("size").unwrap());
+    let dir = Path::new(matches.value_of("dir").unwrap());
+    fs::create_dir_all(dir)?;
+
+    for id in ids {
+        let response: PixivResponse<Work> = cli.fetch(PixivRequestBuilder::work(id))?;
+        let work = response
+            .response
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Work {} not found.", id))?;
+
+        let mut urls = work
+            .metadata
+            .iter()
+            .flat_map(|metadata| &metadata.pages)
+            .filter_map(|page| page.image_urls.get(size))
+            .collect::<Vec<_>>();
+        if urls.is_empty() {
+            urls.extend(work.image_url(size));
+        }
+        if urls.is_empty() {
+            return Err(format!("Work {} has no {} image.", id, size).into());
+        }
+
+        for url in urls {
+            let name = url.rsplit('/').next().filter(|name| !name.is_empty());
+            let path = dir.join(name.map(String::from).unwrap_or_else(|| format!("{}", id)));
+            let mut response = cli.pixiv.download(url)?;
+            if !response.status().is_success() {
+                return Err(format!("Downloading {} failed ({})", url, response.status()).into());
+            }
+            let mut file = File::create(&path)?;
+            response.copy_to(&mut file)?;
+            eprintln!("Saved {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn paged<P>(builder: PixivRequestBuilder<P>, matches: &ArgMatches) -> CliResult<PixivRequestBuilder<P>>
+where
+    P: pixiv::params::HasPage + pixiv::params::HasPerPage,
+{
+    let mut builder = builder;
+    if matches.is_present("page") {
+        builder = builder.page(value_t!(matches, "page", usize)?);
+    }
+    if matches.is_present("per-page") {
+        builder = builder.per_page(value_t!(matches, "per-page", usize)?);
+    }
+    Ok(builder)
+}
+
+fn publicity(matches: &ArgMatches) -> Publicity {
+    if matches.is_present("private") {
+        Publicity::Private
+    } else {
+        Publicity::Public
+    }
+}
+
+const RANKING_MODES: &[&str] = &[
+    "daily", "weekly", "monthly", "rookie", "original", "male", "female", "daily_r18",
+    "weekly_r18", "male_r18", "female_r18", "r18g",
+];
+
+fn search_mode(value: &str) -> SearchMode {
+    match value {
+        "text" => SearchMode::Text,
+        "tag" => SearchMode::Tag,
+        "exact_tag" => SearchMode::ExactTag,
+        "caption" => SearchMode::Caption,
+        _ => unreachable!(),
+    }
+}
+
+fn search_period(value: &str) -> SearchPeriod {
+    match value {
+        "all" => SearchPeriod::All,
+        "day" => SearchPeriod::Day,
+        "week" => SearchPeriod::Week,
+        "month" => SearchPeriod::Month,
+        _ => unreachable!(),
+    }
+}
+
+fn search_sort(value: &str) -> SearchSort {
+    match value {
+        "date" => SearchSort::Date,
+        "popular" => SearchSort::Popular,
+        "popular_male" => SearchSort::PopularMale,
+        "popular_female" => SearchSort::PopularFemale,
+        _ => unreachable!(),
+    }
+}
+
+fn search_order(value: &str) -> SearchOrder {
+    match value {
+        "desc" => SearchOrder::Descending,
+        "asc" => SearchOrder::Ascending,
+        _ => unreachable!(),
+    }
+}
+
+fn work_type(value: &str) -> WorkType {
+    match value {
+        "illustration" => WorkType::Illustration,
+        "manga" => WorkType::Manga,
+        "ugoira" => WorkType::Ugoira,
+        _ => unreachable!(),
+    }
+}
+
+fn image_size(value: &str) -> ImageSize {
+    match value {
+        "px_128x128" => ImageSize::Px128x128,
+        "px_480mw" => ImageSize::Px480mw,
+        "small" => ImageSize::Small,
+        "medium" => ImageSize::Medium,
+        "large" => ImageSize::Large,
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(unix)]
+fn create_private(path: &Path) -> io::Result<File> {
+    use std::fs::OpenOptions;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn create_private(path: &Path) -> io::Result<File> {
+    File::create(path)
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn truncate(value: &str, width: usize) -> String {
+    if value.width() <= width {
+        return value.to_owned();
+    }
+    let mut ret = String::new();
+    let mut used = 0;
+    for c in value.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        used += w;
+        ret.push(c);
+    }
+    ret.push('…');
+    ret
+}
+
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths = headers.iter().map(|header| header.width()).collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut print_row = |cells: &mut dyn Iterator<Item = &str>| {
+        let mut line = String::new();
+        for (i, (cell, width)) in cells.zip(&widths).enumerate() {
+            if i > 0 {
+                line.push_str("  ");
+            }
+            line.push_str(cell);
+            line.extend((cell.width()..*width).map(|_| ' '));
+        }
+        let _ = writeln!(stdout, "{}", line.trim_end());
+    };
+    print_row(&mut headers.iter().cloned());
+    for row in rows {
+        print_row(&mut row.iter().map(String::as_str));
+    }
+}
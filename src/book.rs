@@ -0,0 +1,871 @@
+//! Converting novels, or whole novel series, into EPUB 3 (with the `epub` feature), Markdown or plain text
+//! for reading offline.
+//!
+//! A `Book` holds novels in reading order along with their parsed text (see the `novel` module) and any
+//! images they embed. `[newpage]` becomes a page break, `[chapter:]` a heading in the table of contents,
+//! and ruby is kept as `<ruby>` where the format allows it. Embedded images which weren't added to the
+//! book are linked to on pixiv instead.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # extern crate serde_json;
+//! # use pixiv::book::Book;
+//! # use pixiv::models::Novel;
+//! # use pixiv::novel::NovelText;
+//! # fn main() {
+//! #   let novel: Novel = serde_json::from_str(r#"{"id": 1, "title": "Title", "user": {"id": 2, "name": "Author"}}"#).unwrap();
+//!     let text = NovelText::parse("[chapter:One]\n[[rb:吾輩 > わがはい]]は猫である。");
+//!     let book = Book::from_novel(novel, text);
+//!     assert_eq!(
+//!         book.to_markdown(),
+//!         "# Title\n\nby Author\n\n## One\n\n<ruby>吾輩<rt>わがはい</rt></ruby>は猫である。\n"
+//!     );
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "epub")]
+use std::error::Error;
+#[cfg(feature = "epub")]
+use std::fmt;
+#[cfg(feature = "epub")]
+use std::io::{self, Seek, Write};
+#[cfg(feature = "reqwest-client")]
+use std::thread;
+#[cfg(feature = "reqwest-client")]
+use std::time::Duration;
+
+#[cfg(feature = "epub")]
+use chrono::Utc;
+#[cfg(feature = "epub")]
+use zip::result::ZipError;
+#[cfg(feature = "epub")]
+use zip::write::SimpleFileOptions;
+#[cfg(feature = "epub")]
+use zip::{CompressionMethod, ZipWriter};
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+use models::{Novel, NovelSeries};
+#[cfg(feature = "reqwest-client")]
+use models::{NovelDetail, NovelSeriesResponse, NovelTextResponse, PixivResponse, Work};
+use novel::{Element, NovelText, Page};
+use utils::escape_xml;
+#[cfg(feature = "reqwest-client")]
+use {ImageSize, PixivRequestBuilder};
+
+/// An image embedded in a novel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ImageRef {
+    /// `[pixivimage:]`, with the page for manga.
+    Pixiv { illust_id: usize, page: Option<usize> },
+    /// `[uploadedimage:]`.
+    Uploaded(usize),
+}
+
+impl ImageRef {
+    #[cfg(feature = "epub")]
+    fn file_stem(&self) -> String {
+        match *self {
+            ImageRef::Pixiv { illust_id, page: Some(page) } => format!("pixiv-{}-{}", illust_id, page),
+            ImageRef::Pixiv { illust_id, page: None } => format!("pixiv-{}", illust_id),
+            ImageRef::Uploaded(id) => format!("uploaded-{}", id),
+        }
+    }
+}
+
+/// Image data with its media type, e.g. `image/jpeg`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an image.
+    pub fn new<S: Into<String>>(media_type: S, data: Vec<u8>) -> Image {
+        Image { media_type: media_type.into(), data }
+    }
+
+    #[cfg(feature = "epub")]
+    fn extension(&self) -> &'static str {
+        match self.media_type.as_str() {
+            "image/png" => "png",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            "image/svg+xml" => "svg",
+            _ => "jpg",
+        }
+    }
+}
+
+/// A novel and its text, as one part of a `Book`.
+#[derive(Debug, Clone)]
+pub struct BookEntry {
+    pub novel: Novel,
+    pub text: NovelText,
+}
+
+/// One or more novels to be written out as a single book.
+#[derive(Debug, Clone)]
+pub struct Book {
+    title: String,
+    author: Option<String>,
+    description: Option<String>,
+    language: String,
+    // `dc:identifier` of the EPUB.
+    #[cfg_attr(not(feature = "epub"), allow(dead_code))]
+    identifier: String,
+    series: bool,
+    entries: Vec<BookEntry>,
+    cover: Option<Image>,
+    images: BTreeMap<ImageRef, Image>,
+}
+
+impl Book {
+    /// Creates a book of a single novel, titled and credited after it.
+    pub fn from_novel(novel: Novel, text: NovelText) -> Book {
+        Book {
+            title: novel.title.clone(),
+            author: novel.user.as_ref().map(|user| user.name.clone()),
+            description: Some(novel.caption.clone()).filter(|caption| !caption.is_empty()),
+            language: "ja".to_owned(),
+            identifier: format!("urn:pixiv:novel:{}", novel.id),
+            series: false,
+            entries: vec![BookEntry { novel, text }],
+            cover: None,
+            images: BTreeMap::new(),
+        }
+    }
+    /// Creates a book of a series. `novels` should be in series order, as `novel_series()` returns them.
+    pub fn from_series<I: IntoIterator<Item = (Novel, NovelText)>>(series: &NovelSeries, novels: I) -> Book {
+        Book {
+            title: series.title.clone(),
+            author: series.user.as_ref().map(|user| user.name.clone()),
+            description: Some(series.caption.clone()).filter(|caption| !caption.is_empty()),
+            language: "ja".to_owned(),
+            identifier: format!("urn:pixiv:novel-series:{}", series.id),
+            series: true,
+            entries: novels.into_iter().map(|(novel, text)| BookEntry { novel, text }).collect(),
+            cover: None,
+            images: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the title.
+    pub fn title<S: Into<String>>(mut self, value: S) -> Self {
+        self.title = value.into();
+        self
+    }
+    /// Sets the author.
+    pub fn author<S: Into<String>>(mut self, value: S) -> Self {
+        self.author = Some(value.into());
+        self
+    }
+    /// Sets the language tag (default: `ja`).
+    pub fn language<S: Into<String>>(mut self, value: S) -> Self {
+        self.language = value.into();
+        self
+    }
+    /// Sets the cover image.
+    pub fn cover(mut self, image: Image) -> Self {
+        self.cover = Some(image);
+        self
+    }
+    /// Adds the data of an embedded image.
+    pub fn image(mut self, image_ref: ImageRef, image: Image) -> Self {
+        self.images.insert(image_ref, image);
+        self
+    }
+
+    /// Get the novels, in reading order.
+    #[inline]
+    pub fn entries(&self) -> &[BookEntry] {
+        &self.entries
+    }
+    /// Get the images embedded in the text, whether or not their data was added.
+    pub fn image_refs(&self) -> BTreeSet<ImageRef> {
+        self.elements()
+            .filter_map(|element| match *element {
+                Element::PixivImage { illust_id, page } => Some(ImageRef::Pixiv { illust_id, page }),
+                Element::UploadedImage(id) => Some(ImageRef::Uploaded(id)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.entries
+            .iter()
+            .flat_map(|entry| &entry.text.pages)
+            .flat_map(|page| &page.elements)
+    }
+
+    /// Renders the book as Markdown. Line breaks are kept as hard breaks and pages are separated by rules.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", escape_markdown_line(&self.title));
+        if let Some(ref author) = self.author {
+            out.push_str(&format!("by {}\n\n", escape_markdown_line(author)));
+        }
+        if let Some(ref description) = self.description {
+            out.push_str(&format!("{}\n\n", escape_markdown_line(&strip_html(description))));
+        }
+        let level = if self.series { 3 } else { 2 };
+        for entry in &self.entries {
+            if self.series {
+                out.push_str(&format!("## {}\n\n", escape_markdown_line(&entry.novel.title)));
+            }
+            for (i, page) in entry.text.pages.iter().enumerate() {
+                if i > 0 {
+                    out.push_str("---\n\n");
+                }
+                let page = markdown_page(page, level);
+                if !page.is_empty() {
+                    out.push_str(&page);
+                    out.push_str("\n\n");
+                }
+            }
+        }
+        let len = out.trim_end().len();
+        out.truncate(len);
+        out.push('\n');
+        out
+    }
+
+    /// Renders the book as plain text, see `NovelText::plain_text`.
+    pub fn to_plain_text(&self) -> String {
+        let mut out = format!("{}\n", self.title);
+        if let Some(ref author) = self.author {
+            out.push_str(&format!("{}\n", author));
+        }
+        for entry in &self.entries {
+            out.push('\n');
+            if self.series {
+                out.push_str(&format!("{}\n\n", entry.novel.title));
+            }
+            out.push_str(&entry.text.plain_text());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl Book {
+    /// Fetches a novel and its text with `novel()` and `novel_text()`.
+    pub fn fetch_novel(pixiv: &Pixiv, novel_id: usize) -> Result<Book, FetchError> {
+        let detail: NovelDetail = pixiv.fetch(PixivRequestBuilder::novel(novel_id))?;
+        let text: NovelTextResponse = pixiv.fetch(PixivRequestBuilder::novel_text(novel_id))?;
+        Ok(Book::from_novel(detail.novel, text.parse()))
+    }
+
+    /// Fetches every novel of a series in series order, waiting `delay` between requests.
+    pub fn fetch_series(pixiv: &Pixiv, series_id: usize, delay: Duration) -> Result<Book, FetchError> {
+        let response: NovelSeriesResponse = pixiv.fetch(PixivRequestBuilder::novel_series(series_id))?;
+        let series = response.novel_series_detail.clone();
+        let mut novels = response.novels.clone();
+        let mut next = response.next_param("last_order");
+        while let Some(last_order) = next.and_then(|value| value.parse().ok()) {
+            thread::sleep(delay);
+            let response: NovelSeriesResponse =
+                pixiv.fetch(PixivRequestBuilder::novel_series(series_id).last_order(last_order))?;
+            if response.novels.is_empty() {
+                break;
+            }
+            novels.extend(response.novels.iter().cloned());
+            next = response.next_param("last_order");
+        }
+
+        let mut entries = Vec::with_capacity(novels.len());
+        for novel in novels {
+            thread::sleep(delay);
+            let text: NovelTextResponse = pixiv.fetch(PixivRequestBuilder::novel_text(novel.id))?;
+            entries.push((novel, text.parse()));
+        }
+        Ok(Book::from_series(&series, entries))
+    }
+
+    /// Downloads the cover (of the first novel) and the `[pixivimage:]` illustrations which haven't been added yet,
+    /// returning how many images were added. `[uploadedimage:]` urls aren't available from the App API, so those are skipped.
+    pub fn fetch_images(&mut self, pixiv: &Pixiv) -> Result<usize, FetchError> {
+        let mut added = 0;
+        if self.cover.is_none() {
+            let urls = self.entries.first().map(|entry| &entry.novel.image_urls);
+            let url = urls.and_then(|urls| ["large", "medium", "square_medium"].iter().find_map(|size| urls.get(*size)));
+            if let Some(url) = url {
+                self.cover = Some(download(pixiv, url)?);
+                added += 1;
+            }
+        }
+        for image_ref in self.image_refs() {
+            let (illust_id, page) = match image_ref {
+                ImageRef::Pixiv { illust_id, page } if !self.images.contains_key(&image_ref) => (illust_id, page),
+                _ => continue,
+            };
+            let response: PixivResponse<Work> = pixiv.fetch(PixivRequestBuilder::work(illust_id))?;
+            let work = match response.response.into_iter().next() {
+                Some(work) => work,
+                None => continue,
+            };
+            let page_url = page.and_then(|page| {
+                let pages = work.metadata.as_ref().map(|metadata| &metadata.pages[..]).unwrap_or(&[]);
+                pages.get(page.checked_sub(1)?)?.image_urls.get(ImageSize::Large)
+            });
+            if let Some(url) = page_url.or_else(|| work.image_url(ImageSize::Large)) {
+                self.images.insert(image_ref, download(pixiv, url)?);
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+fn download(pixiv: &Pixiv, url: &str) -> Result<Image, FetchError> {
+    let mut response = pixiv.download(url)?;
+    if !response.status().is_success() {
+        return Err(FetchError::Status(response.status()));
+    }
+    let media_type = response
+        .headers()
+        .get(::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| value.starts_with("image/"))
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| if url.ends_with(".png") { "image/png" } else { "image/jpeg" }.to_owned());
+    let mut data = Vec::new();
+    response.copy_to(&mut data)?;
+    Ok(Image::new(media_type, data))
+}
+
+fn artwork_url(illust_id: usize) -> String {
+    format!("https://www.pixiv.net/artworks/{}", illust_id)
+}
+
+fn markdown_page(page: &Page, level: usize) -> String {
+    let mut out = String::new();
+    for element in &page.elements {
+        match *element {
+            Element::Text(ref text) => {
+                for (i, line) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        out.push('\n');
+                    }
+                    push_markdown(&mut out, line);
+                }
+            }
+            Element::Chapter(ref title) => {
+                out.push_str(&format!("\n\n{} {}\n\n", "#".repeat(level), escape_markdown_line(&NovelText::parse(title).plain_text())));
+            }
+            Element::Ruby { ref base, ref reading } => {
+                out.push_str(&format!("<ruby>{}<rt>{}</rt></ruby>", escape_xml(base), escape_xml(reading)));
+            }
+            Element::PixivImage { illust_id, .. } => {
+                out.push_str(&format!("[illustration {}]({})", illust_id, artwork_url(illust_id)));
+            }
+            Element::UploadedImage(_) => {}
+            Element::JumpPage(page) => out.push_str(&format!("*(page {})*", page)),
+            Element::JumpUri { ref text, ref uri } => {
+                out.push('[');
+                push_markdown(&mut out, text);
+                out.push_str(&format!("]({})", uri));
+            }
+        }
+    }
+
+    // Keeps single line breaks as hard breaks, and collapses runs of blank lines.
+    let lines: Vec<&str> = out.split('\n').map(str::trim_end).collect();
+    let mut page = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.is_empty() {
+            if !page.is_empty() && !page.ends_with("\n\n") {
+                page.push('\n');
+            }
+            continue;
+        }
+        page.push_str(line);
+        let next_is_text = lines.get(i + 1).is_some_and(|next| !next.is_empty());
+        page.push_str(if next_is_text && !line.starts_with('#') { "  \n" } else { "\n" });
+    }
+    page.trim_end().to_owned()
+}
+
+// Escapes text for Markdown, including markers which only mean something at the start of a line.
+fn push_markdown(out: &mut String, text: &str) {
+    for (i, c) in text.chars().enumerate() {
+        let line_start = i == 0 && (out.is_empty() || out.ends_with('\n'));
+        match c {
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' => out.push('\\'),
+            '#' | '>' | '-' | '+' if line_start => out.push('\\'),
+            _ => {}
+        }
+        out.push(c);
+    }
+}
+
+fn escape_markdown_line(text: &str) -> String {
+    let mut out = String::new();
+    push_markdown(&mut out, &text.replace('\n', " "));
+    out
+}
+
+// Captions are HTML with `<br />` line breaks.
+fn strip_html(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..].find('>').map_or(rest.len(), |end| start + end + 1);
+        let tag = rest[start..end].to_lowercase();
+        if tag.starts_with("<br") || tag.starts_with("</p") {
+            out.push('\n');
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+        .trim()
+        .to_owned()
+}
+
+/// Error returned by `Book::write_epub`.
+#[cfg(feature = "epub")]
+#[derive(Debug)]
+pub enum EpubError {
+    Io(io::Error),
+    Zip(ZipError),
+}
+
+#[cfg(feature = "epub")]
+impl Error for EpubError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to write an EPUB."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            EpubError::Io(ref e) => Some(e),
+            EpubError::Zip(ref e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "epub")]
+impl fmt::Display for EpubError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EpubError::Io(ref e) => write!(f, "An error occurred while trying to write an EPUB. Reason: {}", e),
+            EpubError::Zip(ref e) => write!(f, "An error occurred while trying to write an EPUB. Reason: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "epub")]
+impl From<io::Error> for EpubError {
+    fn from(e: io::Error) -> Self {
+        EpubError::Io(e)
+    }
+}
+
+#[cfg(feature = "epub")]
+impl From<ZipError> for EpubError {
+    fn from(e: ZipError) -> Self {
+        EpubError::Zip(e)
+    }
+}
+
+#[cfg(feature = "epub")]
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+#[cfg(feature = "epub")]
+const STYLE_CSS: &str = "body { line-height: 1.8; }
+p { margin: 0; }
+figure { margin: 1em 0; text-align: center; }
+img { max-width: 100%; }
+";
+
+// A file of the EPUB besides the package document, in spine order where it's a document.
+#[cfg(feature = "epub")]
+struct Item {
+    id: String,
+    href: String,
+    media_type: String,
+    properties: Option<&'static str>,
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "epub")]
+impl Book {
+    /// Writes the book as an EPUB 3, one document per page, and returns the writer.
+    pub fn write_epub<W: Write + Seek>(&self, writer: W) -> Result<W, EpubError> {
+        let image_hrefs: BTreeMap<ImageRef, String> = self
+            .images
+            .iter()
+            .map(|(image_ref, image)| (*image_ref, format!("images/{}.{}", image_ref.file_stem(), image.extension())))
+            .collect();
+
+        let mut documents = Vec::new();
+        if let Some(ref cover) = self.cover {
+            let body = format!(
+                "<figure class=\"cover\"><img src=\"images/cover.{}\" alt=\"{}\"/></figure>\n",
+                cover.extension(),
+                escape_xml(&self.title)
+            );
+            documents.push(self.document_item("cover", "cover.xhtml", &self.title, &body));
+        }
+        let mut nav = String::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let first_href = format!("text/{:03}-001.xhtml", i + 1);
+            let mut chapters = String::new();
+            for (j, page) in entry.text.pages.iter().enumerate() {
+                let href = format!("text/{:03}-{:03}.xhtml", i + 1, j + 1);
+                let mut body = String::new();
+                if j == 0 {
+                    body.push_str(&format!("<h1>{}</h1>\n", escape_xml(&entry.novel.title)));
+                }
+                body.push_str(&xhtml_page(page, i + 1, j + 1, &image_hrefs, &mut chapters, &href));
+                let id = format!("page-{:03}-{:03}", i + 1, j + 1);
+                documents.push(self.document_item(&id, &href, &entry.novel.title, &body));
+            }
+            let chapters = if chapters.is_empty() { chapters } else { format!("\n<ol>{}</ol>", chapters) };
+            nav.push_str(&format!(
+                "<li><a href=\"{}\">{}</a>{}</li>\n",
+                first_href,
+                escape_xml(&entry.novel.title),
+                chapters
+            ));
+        }
+        let nav_body = format!("<nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n<ol>\n{}</ol>\n</nav>\n", escape_xml(&self.title), nav);
+        let mut nav = self.document_item("nav", "nav.xhtml", &self.title, &nav_body);
+        nav.properties = Some("nav");
+
+        let mut resources = vec![Item {
+            id: "style".to_owned(),
+            href: "style.css".to_owned(),
+            media_type: "text/css".to_owned(),
+            properties: None,
+            data: STYLE_CSS.as_bytes().to_vec(),
+        }];
+        if let Some(ref cover) = self.cover {
+            resources.push(Item {
+                id: "cover-image".to_owned(),
+                href: format!("images/cover.{}", cover.extension()),
+                media_type: cover.media_type.clone(),
+                properties: Some("cover-image"),
+                data: cover.data.clone(),
+            });
+        }
+        for (image_ref, image) in &self.images {
+            resources.push(Item {
+                id: image_ref.file_stem(),
+                href: image_hrefs[image_ref].clone(),
+                media_type: image.media_type.clone(),
+                properties: None,
+                data: image.data.clone(),
+            });
+        }
+
+        let mut zip = ZipWriter::new(writer);
+        // `mimetype` must come first, uncompressed.
+        zip.start_file("mimetype", SimpleFileOptions::default().compression_method(CompressionMethod::Stored))?;
+        zip.write_all(b"application/epub+zip")?;
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file("META-INF/container.xml", options)?;
+        zip.write_all(CONTAINER_XML.as_bytes())?;
+        zip.start_file("OEBPS/content.opf", options)?;
+        zip.write_all(self.package_document(&documents, &nav, &resources).as_bytes())?;
+        for item in documents.iter().chain(Some(&nav)).chain(&resources) {
+            zip.start_file(format!("OEBPS/{}", item.href), options)?;
+            zip.write_all(&item.data)?;
+        }
+        Ok(zip.finish()?)
+    }
+
+    fn document_item(&self, id: &str, href: &str, title: &str, body: &str) -> Item {
+        let depth = href.matches('/').count();
+        let document = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" xml:lang=\"{lang}\" lang=\"{lang}\">\n\
+             <head>\n<meta charset=\"UTF-8\"/>\n<title>{title}</title>\n<link rel=\"stylesheet\" type=\"text/css\" href=\"{root}style.css\"/>\n</head>\n\
+             <body>\n{body}</body>\n</html>\n",
+            lang = escape_xml(&self.language),
+            title = escape_xml(title),
+            root = "../".repeat(depth),
+            body = body,
+        );
+        Item {
+            id: id.to_owned(),
+            href: href.to_owned(),
+            media_type: "application/xhtml+xml".to_owned(),
+            properties: None,
+            data: document.into_bytes(),
+        }
+    }
+
+    fn package_document(&self, documents: &[Item], nav: &Item, resources: &[Item]) -> String {
+        let mut metadata = format!(
+            "<dc:identifier id=\"book-id\">{}</dc:identifier>\n<dc:title>{}</dc:title>\n<dc:language>{}</dc:language>\n",
+            escape_xml(&self.identifier),
+            escape_xml(&self.title),
+            escape_xml(&self.language)
+        );
+        if let Some(ref author) = self.author {
+            metadata.push_str(&format!("<dc:creator>{}</dc:creator>\n", escape_xml(author)));
+        }
+        if let Some(ref description) = self.description {
+            metadata.push_str(&format!("<dc:description>{}</dc:description>\n", escape_xml(&strip_html(description))));
+        }
+        let tags: BTreeSet<&str> = self
+            .entries
+            .iter()
+            .flat_map(|entry| &entry.novel.tags)
+            .map(|tag| tag.name.as_str())
+            .collect();
+        for tag in tags {
+            metadata.push_str(&format!("<dc:subject>{}</dc:subject>\n", escape_xml(tag)));
+        }
+        if let Some(date) = self.entries.iter().filter_map(|entry| entry.novel.create_date).min() {
+            metadata.push_str(&format!("<dc:date>{}</dc:date>\n", date.format("%Y-%m-%d")));
+        }
+        metadata.push_str(&format!(
+            "<meta property=\"dcterms:modified\">{}</meta>\n",
+            Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+        ));
+        if self.cover.is_some() {
+            metadata.push_str("<meta name=\"cover\" content=\"cover-image\"/>\n");
+        }
+
+        let mut manifest = String::new();
+        for item in documents.iter().chain(Some(nav)).chain(resources) {
+            manifest.push_str(&format!(
+                "<item id=\"{}\" href=\"{}\" media-type=\"{}\"{}/>\n",
+                item.id,
+                item.href,
+                escape_xml(&item.media_type),
+                item.properties.map(|properties| format!(" properties=\"{}\"", properties)).unwrap_or_default()
+            ));
+        }
+        let spine: String = documents
+            .iter()
+            .map(|item| format!("<itemref idref=\"{}\"/>\n", item.id))
+            .collect();
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\" xml:lang=\"{}\">\n\
+             <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n{}</metadata>\n\
+             <manifest>\n{}</manifest>\n<spine>\n{}</spine>\n</package>\n",
+            escape_xml(&self.language),
+            metadata,
+            manifest,
+            spine
+        )
+    }
+}
+
+// Renders a page as XHTML paragraphs, adding its chapters to the `nav` list items.
+#[cfg(feature = "epub")]
+fn xhtml_page(
+    page: &Page,
+    entry: usize,
+    page_number: usize,
+    image_hrefs: &BTreeMap<ImageRef, String>,
+    nav: &mut String,
+    href: &str,
+) -> String {
+    let mut out = String::new();
+    let mut line = String::new();
+    let mut chapter = 0;
+    // The line break right after a heading or figure (or the start of the page) doesn't make an empty paragraph.
+    let mut after_block = true;
+    fn flush(out: &mut String, line: &mut String) {
+        if line.is_empty() {
+            out.push_str("<p><br/></p>\n");
+        } else {
+            out.push_str(&format!("<p>{}</p>\n", line));
+            line.clear();
+        }
+    }
+    for element in &page.elements {
+        match *element {
+            Element::Text(ref text) => {
+                for (i, part) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        if !(line.is_empty() && after_block) {
+                            flush(&mut out, &mut line);
+                        }
+                        after_block = false;
+                    }
+                    line.push_str(&escape_xml(part));
+                }
+            }
+            Element::Chapter(ref title) => {
+                if !line.is_empty() {
+                    flush(&mut out, &mut line);
+                }
+                chapter += 1;
+                let id = format!("chapter-{}-{}", page_number, chapter);
+                let title = xhtml_inline(&NovelText::parse(title));
+                out.push_str(&format!("<h2 id=\"{}\">{}</h2>\n", id, title));
+                nav.push_str(&format!("<li><a href=\"{}#{}\">{}</a></li>", href, id, title));
+                after_block = true;
+            }
+            Element::Ruby { ref base, ref reading } => {
+                line.push_str(&format!("<ruby>{}<rt>{}</rt></ruby>", escape_xml(base), escape_xml(reading)));
+            }
+            Element::PixivImage { illust_id, page } => {
+                let image_ref = ImageRef::Pixiv { illust_id, page };
+                match image_hrefs.get(&image_ref) {
+                    Some(image_href) => {
+                        if !line.is_empty() {
+                            flush(&mut out, &mut line);
+                        }
+                        out.push_str(&format!("<figure><img src=\"../{}\" alt=\"{}\"/></figure>\n", image_href, illust_id));
+                        after_block = true;
+                    }
+                    None => line.push_str(&format!("<a href=\"{}\">[illustration {}]</a>", artwork_url(illust_id), illust_id)),
+                }
+            }
+            Element::UploadedImage(id) => {
+                if let Some(image_href) = image_hrefs.get(&ImageRef::Uploaded(id)) {
+                    if !line.is_empty() {
+                        flush(&mut out, &mut line);
+                    }
+                    out.push_str(&format!("<figure><img src=\"../{}\" alt=\"{}\"/></figure>\n", image_href, id));
+                    after_block = true;
+                }
+            }
+            Element::JumpPage(target) => {
+                line.push_str(&format!("<a href=\"{:03}-{:03}.xhtml\">(page {})</a>", entry, target, target));
+            }
+            Element::JumpUri { ref text, ref uri } => {
+                line.push_str(&format!("<a href=\"{}\">{}</a>", escape_xml(uri), escape_xml(text)));
+            }
+        }
+    }
+    if !line.is_empty() {
+        flush(&mut out, &mut line);
+    }
+    out
+}
+
+// Renders text within a heading, keeping ruby.
+#[cfg(feature = "epub")]
+fn xhtml_inline(text: &NovelText) -> String {
+    let mut out = String::new();
+    for element in text.pages.iter().flat_map(|page| &page.elements) {
+        match *element {
+            Element::Text(ref text) => out.push_str(&escape_xml(text)),
+            Element::Ruby { ref base, ref reading } => {
+                out.push_str(&format!("<ruby>{}<rt>{}</rt></ruby>", escape_xml(base), escape_xml(reading)))
+            }
+            ref other => out.push_str(&escape_xml(&other.to_string())),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn novel(id: usize, title: &str) -> Novel {
+        serde_json::from_value(json!({
+            "id": id,
+            "title": title,
+            "caption": "a<br />b &amp; c",
+            "tags": ["オリジナル"],
+            "create_date": "2018-02-22T12:00:00+09:00",
+            "user": {"id": 2, "name": "Author"}
+        }))
+        .unwrap()
+    }
+
+    fn series() -> Book {
+        let series: NovelSeries = serde_json::from_value(json!({"id": 9, "title": "Series", "user": {"id": 2, "name": "Author"}})).unwrap();
+        Book::from_series(
+            &series,
+            vec![
+                (novel(1, "First"), NovelText::parse("[chapter:[[rb:序 > じょ]]]\n*one*\ntwo\n\n[pixivimage:5][newpage][jump:1]")),
+                (novel(2, "Second"), NovelText::parse("# not a heading [uploadedimage:7]")),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_markdown() {
+        let book = series();
+        assert_eq!(
+            book.to_markdown(),
+            "# Series\n\nby Author\n\n## First\n\n### 序\n\n\\*one\\*  \ntwo\n\n\
+             [illustration 5](https://www.pixiv.net/artworks/5)\n\n---\n\n*(page 1)*\n\n\
+             ## Second\n\n\\# not a heading\n"
+        );
+        assert_eq!(
+            book.to_plain_text(),
+            "Series\nAuthor\n\nFirst\n\n序\n*one*\ntwo\n\nSecond\n\n# not a heading \n"
+        );
+        assert_eq!(
+            book.image_refs().into_iter().collect::<Vec<_>>(),
+            vec![ImageRef::Pixiv { illust_id: 5, page: None }, ImageRef::Uploaded(7)]
+        );
+        assert_eq!(strip_html("a<br />b &amp;lt; <b>c</b>"), "a\nb &lt; c");
+    }
+
+    #[cfg(feature = "epub")]
+    #[test]
+    fn test_epub() {
+        use std::io::{Cursor, Read};
+        use zip::ZipArchive;
+
+        let book = series()
+            .cover(Image::new("image/png", vec![1, 2, 3]))
+            .image(ImageRef::Pixiv { illust_id: 5, page: None }, Image::new("image/jpeg", vec![4, 5]));
+        let epub = book.write_epub(Cursor::new(Vec::new())).unwrap().into_inner();
+        let mut archive = ZipArchive::new(Cursor::new(epub)).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+        assert_eq!(archive.by_index(0).unwrap().compression(), CompressionMethod::Stored);
+
+        let mut read = |name: &str| {
+            let mut contents = String::new();
+            archive.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+            contents
+        };
+        let opf = read("OEBPS/content.opf");
+        assert!(opf.contains("<dc:identifier id=\"book-id\">urn:pixiv:novel-series:9</dc:identifier>"));
+        assert!(opf.contains("<dc:creator>Author</dc:creator>"));
+        assert!(opf.contains("<dc:subject>オリジナル</dc:subject>"));
+        assert!(opf.contains("<item id=\"cover-image\" href=\"images/cover.png\" media-type=\"image/png\" properties=\"cover-image\"/>"));
+        let spine: Vec<&str> = opf.lines().filter(|line| line.starts_with("<itemref")).collect();
+        assert_eq!(
+            spine,
+            vec![
+                "<itemref idref=\"cover\"/>",
+                "<itemref idref=\"page-001-001\"/>",
+                "<itemref idref=\"page-001-002\"/>",
+                "<itemref idref=\"page-002-001\"/>"
+            ]
+        );
+
+        let nav = read("OEBPS/nav.xhtml");
+        assert!(nav.contains("<li><a href=\"text/001-001.xhtml\">First</a>\n<ol><li><a href=\"text/001-001.xhtml#chapter-1-1\"><ruby>序<rt>じょ</rt></ruby></a></li></ol></li>"));
+        let page = read("OEBPS/text/001-001.xhtml");
+        assert!(page.contains("<h1>First</h1>\n<h2 id=\"chapter-1-1\"><ruby>序<rt>じょ</rt></ruby></h2>\n<p>*one*</p>\n<p>two</p>\n<p><br/></p>\n<figure><img src=\"../images/pixiv-5.jpg\" alt=\"5\"/></figure>"), "{}", page);
+        assert!(page.contains("href=\"../style.css\""));
+        assert!(read("OEBPS/text/001-002.xhtml").contains("<a href=\"001-001.xhtml\">(page 1)</a>"));
+        assert!(read("OEBPS/text/002-001.xhtml").contains("<p># not a heading </p>"));
+    }
+}
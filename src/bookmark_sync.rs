@@ -0,0 +1,423 @@
+//! Syncing bookmarks between two accounts, or between an account and a local snapshot.
+//!
+//! A `Snapshot` holds the bookmarks of an account: which works, with what publicity and bookmark tags.
+//! Fetch one from each account, or load one saved earlier, and compare them with `SyncPlan::new` to get
+//! the changes which would make the target match the source. Printing the plan is a dry run; `apply` it
+//! to an account, or `apply_to` a snapshot, to make the changes.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # use pixiv::Publicity;
+//! # use pixiv::bookmark_sync::{Bookmark, Snapshot, SyncPlan};
+//! # fn main() {
+//!     let personal: Snapshot = vec![
+//!         Bookmark::new(1, Publicity::Public, vec!["a"]),
+//!         Bookmark::new(2, Publicity::Private, Vec::<String>::new()),
+//!     ].into();
+//!     let mut team: Snapshot = vec![Bookmark::new(1, Publicity::Public, Vec::<String>::new())].into();
+//!
+//!     let plan = SyncPlan::new(&personal, &team, false);
+//!     assert_eq!(plan.to_string(), "~ 1 public a\n+ 2 private\n");
+//!     plan.apply_to(&mut team);
+//!     assert!(SyncPlan::new(&personal, &team, false).is_empty());
+//! # }
+//! ```
+
+use std::collections::btree_map::{self, BTreeMap};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+#[cfg(feature = "reqwest-client")]
+use std::thread;
+#[cfg(feature = "reqwest-client")]
+use std::time::Duration;
+
+use serde_json;
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+use models::FavoriteWork;
+use utils;
+#[cfg(feature = "reqwest-client")]
+use models::PixivResponse;
+#[cfg(feature = "reqwest-client")]
+use serde_json::Value;
+#[cfg(feature = "reqwest-client")]
+use PixivRequestBuilder;
+use Publicity;
+
+/// Error returned when loading or saving a `Snapshot`.
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl Error for SnapshotError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to read or write a bookmark snapshot."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            SnapshotError::Io(ref e) => Some(e),
+            SnapshotError::Json(ref e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SnapshotError::Io(ref e) => write!(f, "An error occurred while trying to access a bookmark snapshot. Reason: {}", e),
+            SnapshotError::Json(ref e) => write!(f, "Bookmark snapshot is invalid: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(e: serde_json::Error) -> Self {
+        SnapshotError::Json(e)
+    }
+}
+
+/// A bookmarked work. Tags are kept sorted and without duplicates, since their order doesn't matter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub work_id: usize,
+    pub publicity: Publicity,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The id `favorite_works_remove()` takes. Only known for bookmarks fetched from an account, and
+    /// never saved, since it's specific to that account.
+    #[serde(skip)]
+    pub favorite_id: Option<usize>,
+}
+
+impl Bookmark {
+    /// Creates a bookmark.
+    pub fn new<B, I>(work_id: usize, publicity: Publicity, tags: I) -> Bookmark
+    where
+        B: Into<String>,
+        I: IntoIterator<Item = B>,
+    {
+        let mut tags: Vec<String> = tags.into_iter().map(Into::into).collect();
+        tags.sort();
+        tags.dedup();
+        Bookmark {
+            work_id,
+            publicity,
+            tags,
+            favorite_id: None,
+        }
+    }
+    /// Creates a bookmark from an entry of `favorite_works()`.
+    pub fn from_favorite(favorite: &FavoriteWork) -> Bookmark {
+        Bookmark {
+            favorite_id: Some(favorite.id),
+            ..Bookmark::new(favorite.work.id, favorite.publicity, favorite.tags.iter().cloned())
+        }
+    }
+
+    // Whether the publicity and tags are the same.
+    fn same_as(&self, other: &Bookmark) -> bool {
+        self.publicity == other.publicity && self.tags == other.tags
+    }
+}
+
+/// The bookmarks of an account, keyed by work id. Saved as a JSON list of bookmarks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<Bookmark>", into = "Vec<Bookmark>")]
+pub struct Snapshot {
+    bookmarks: BTreeMap<usize, Bookmark>,
+}
+
+impl Snapshot {
+    /// Creates an empty snapshot.
+    pub fn new() -> Snapshot {
+        Snapshot::default()
+    }
+    /// Reads a snapshot written by `save()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Snapshot, SnapshotError> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+    /// Writes the snapshot as JSON. The file is replaced only once the snapshot is fully written, so an
+    /// interrupted save keeps the previous one.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        utils::replace_file(path, |out| Ok(serde_json::to_writer(out, self)?))
+    }
+    /// Fetches the public and private bookmarks of the account `pixiv` is logged in to, waiting `delay`
+    /// between requests.
+    #[cfg(feature = "reqwest-client")]
+    pub fn fetch(pixiv: &Pixiv, delay: Duration) -> Result<Snapshot, FetchError> {
+        let mut snapshot = Snapshot::new();
+        let mut fetched = false;
+        for &publicity in &[Publicity::Public, Publicity::Private] {
+            let mut page = 1;
+            loop {
+                if fetched {
+                    thread::sleep(delay);
+                }
+                fetched = true;
+                let builder = PixivRequestBuilder::favorite_works().publicity(publicity).page(page);
+                let response: PixivResponse<FavoriteWork> = pixiv.fetch(builder)?;
+                snapshot.extend(response.response.iter().map(Bookmark::from_favorite));
+                match response.pagination.and_then(|pagination| pagination.next) {
+                    Some(next) if next > page => page = next,
+                    _ => break,
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Get the number of bookmarks.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+    /// Whether there are no bookmarks.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+    /// Iterates over the bookmarks, ordered by work id.
+    pub fn iter(&self) -> btree_map::Values<'_, usize, Bookmark> {
+        self.bookmarks.values()
+    }
+    /// Get the bookmark of a work.
+    pub fn get(&self, work_id: usize) -> Option<&Bookmark> {
+        self.bookmarks.get(&work_id)
+    }
+    /// Adds a bookmark, replacing any of the same work. Returns the replaced one.
+    pub fn insert(&mut self, bookmark: Bookmark) -> Option<Bookmark> {
+        self.bookmarks.insert(bookmark.work_id, bookmark)
+    }
+    /// Removes the bookmark of a work.
+    pub fn remove(&mut self, work_id: usize) -> Option<Bookmark> {
+        self.bookmarks.remove(&work_id)
+    }
+}
+
+impl Extend<Bookmark> for Snapshot {
+    fn extend<I: IntoIterator<Item = Bookmark>>(&mut self, bookmarks: I) {
+        for bookmark in bookmarks {
+            self.insert(bookmark);
+        }
+    }
+}
+
+impl From<Vec<Bookmark>> for Snapshot {
+    fn from(bookmarks: Vec<Bookmark>) -> Self {
+        let mut snapshot = Snapshot::new();
+        snapshot.extend(bookmarks);
+        snapshot
+    }
+}
+
+impl From<Snapshot> for Vec<Bookmark> {
+    fn from(snapshot: Snapshot) -> Self {
+        snapshot.bookmarks.into_values().collect()
+    }
+}
+
+/// Whether `SyncPlan::apply` sends requests or only counts them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncMode {
+    DryRun,
+    Apply,
+}
+
+/// The changes which make a target's bookmarks match a source's.
+#[derive(Debug, Clone, Default)]
+pub struct SyncPlan {
+    /// Bookmarks missing from the target.
+    pub add: Vec<Bookmark>,
+    /// Bookmarks in both whose publicity or tags differ, as they are in the source.
+    pub update: Vec<Bookmark>,
+    /// Bookmarks only in the target, as they are there. Empty unless the plan removes missing bookmarks.
+    pub remove: Vec<Bookmark>,
+}
+
+impl SyncPlan {
+    /// Compares two snapshots. With `remove_missing`, bookmarks the source doesn't have are removed from
+    /// the target, making it a mirror; otherwise they're left alone.
+    pub fn new(source: &Snapshot, target: &Snapshot, remove_missing: bool) -> SyncPlan {
+        let mut plan = SyncPlan::default();
+        for bookmark in source.iter() {
+            match target.get(bookmark.work_id) {
+                // The source's favorite id means nothing to the target's account.
+                None => plan.add.push(Bookmark {
+                    favorite_id: None,
+                    ..bookmark.clone()
+                }),
+                Some(existing) if !existing.same_as(bookmark) => plan.update.push(Bookmark {
+                    favorite_id: existing.favorite_id,
+                    ..bookmark.clone()
+                }),
+                Some(_) => {}
+            }
+        }
+        if remove_missing {
+            plan.remove = target.iter().filter(|bookmark| source.get(bookmark.work_id).is_none()).cloned().collect();
+        }
+        plan
+    }
+
+    /// Get the number of changes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.add.len() + self.update.len() + self.remove.len()
+    }
+    /// Whether the target already matches the source.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Makes the changes to a snapshot, e.g. a backup being brought up to date with an account.
+    pub fn apply_to(&self, snapshot: &mut Snapshot) {
+        snapshot.extend(self.add.iter().chain(&self.update).cloned());
+        for bookmark in &self.remove {
+            snapshot.remove(bookmark.work_id);
+        }
+    }
+
+    /// Makes the changes to the account `pixiv` is logged in to, waiting `delay` between requests.
+    /// Returns how many changes were made, or with `SyncMode::DryRun`, would be.
+    ///
+    /// Bookmarks without tags are added with `favorite_work_add()`. The public API can't set bookmark tags,
+    /// so the rest are added and updated with `illust_bookmark_edit()`. Bookmarks are removed with
+    /// `favorite_works_remove()` if their favorite id is known, otherwise with `illust_bookmark_delete()`.
+    ///
+    /// Changes made before an error stay made. Planning again from fresh snapshots picks up the rest.
+    #[cfg(feature = "reqwest-client")]
+    pub fn apply(&self, pixiv: &Pixiv, delay: Duration, mode: SyncMode) -> Result<usize, FetchError> {
+        if mode == SyncMode::DryRun {
+            return Ok(self.len());
+        }
+        let mut applied = 0;
+        let wait = |applied: usize| {
+            if applied > 0 {
+                thread::sleep(delay);
+            }
+        };
+        for bookmark in &self.add {
+            wait(applied);
+            if bookmark.tags.is_empty() {
+                let builder = PixivRequestBuilder::favorite_work_add(bookmark.work_id).publicity(bookmark.publicity);
+                pixiv.fetch::<_, Value>(builder)?;
+            } else {
+                let builder =
+                    PixivRequestBuilder::illust_bookmark_edit(bookmark.work_id, bookmark.tags.iter().cloned(), bookmark.publicity);
+                pixiv.fetch::<_, Value>(builder)?;
+            }
+            applied += 1;
+        }
+        for bookmark in &self.update {
+            wait(applied);
+            let builder =
+                PixivRequestBuilder::illust_bookmark_edit(bookmark.work_id, bookmark.tags.iter().cloned(), bookmark.publicity);
+            pixiv.fetch::<_, Value>(builder)?;
+            applied += 1;
+        }
+        for bookmark in &self.remove {
+            wait(applied);
+            match bookmark.favorite_id {
+                Some(favorite_id) => {
+                    let builder = PixivRequestBuilder::favorite_works_remove([favorite_id]).publicity(bookmark.publicity);
+                    pixiv.fetch::<_, Value>(builder)?;
+                }
+                None => {
+                    pixiv.fetch::<_, Value>(PixivRequestBuilder::illust_bookmark_delete(bookmark.work_id))?;
+                }
+            }
+            applied += 1;
+        }
+        Ok(applied)
+    }
+}
+
+// One change per line: `+` to add, `~` to update and `-` to remove, then the work id, publicity and tags.
+impl fmt::Display for SyncPlan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let changes = self.update.iter().map(|bookmark| ('~', bookmark))
+            .chain(self.add.iter().map(|bookmark| ('+', bookmark)))
+            .chain(self.remove.iter().map(|bookmark| ('-', bookmark)));
+        for (sign, bookmark) in changes {
+            write!(f, "{} {} {}", sign, bookmark.work_id, bookmark.publicity.as_str())?;
+            for tag in &bookmark.tags {
+                write!(f, " {}", tag)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::PixivResponse;
+    use serde_json::json;
+
+    #[test]
+    fn test_plan() {
+        let response: PixivResponse<FavoriteWork> = serde_json::from_value(json!({
+            "status": "success",
+            "response": [
+                {"id": 901, "comment": null, "tags": ["b", "a", "a"], "publicity": "public", "work": {"id": 1, "title": "one"}},
+                {"id": 902, "tags": null, "publicity": "private", "work": {"id": 2, "title": "two"}},
+                {"id": 903, "tags": [], "publicity": "public", "work": {"id": 3, "title": "three"}}
+            ]
+        })).unwrap();
+        let target: Snapshot = response.response.iter().map(Bookmark::from_favorite).collect::<Vec<_>>().into();
+        assert_eq!(target.get(1).unwrap().tags, vec!["a", "b"]);
+        assert_eq!(target.get(2).unwrap().favorite_id, Some(902));
+
+        let source: Snapshot = vec![
+            Bookmark::new(1, Publicity::Public, vec!["a", "b"]),
+            Bookmark::new(2, Publicity::Public, vec!["c"]),
+            Bookmark::new(4, Publicity::Private, Vec::<String>::new()),
+        ].into();
+
+        let plan = SyncPlan::new(&source, &target, false);
+        assert_eq!(plan.update, vec![Bookmark { favorite_id: Some(902), ..Bookmark::new(2, Publicity::Public, vec!["c"]) }]);
+        assert_eq!(plan.add.iter().map(|bookmark| bookmark.work_id).collect::<Vec<_>>(), vec![4]);
+        assert!(plan.remove.is_empty());
+
+        let plan = SyncPlan::new(&source, &target, true);
+        assert_eq!(plan.to_string(), "~ 2 public c\n+ 4 private\n- 3 public\n");
+
+        let mut mirror = target.clone();
+        plan.apply_to(&mut mirror);
+        assert!(SyncPlan::new(&source, &mirror, true).is_empty());
+        assert!(mirror.get(3).is_none());
+
+        let json = serde_json::to_string(&mirror).unwrap();
+        assert_eq!(json.matches("favorite_id").count(), 0);
+        let loaded: Snapshot = serde_json::from_str(&json).unwrap();
+        assert!(SyncPlan::new(&mirror, &loaded, true).is_empty());
+    }
+
+    #[test]
+    fn test_add_then_remove() {
+        let source: Snapshot = vec![Bookmark { favorite_id: Some(901), ..Bookmark::new(1, Publicity::Public, vec!["a"]) }].into();
+        let mut target = Snapshot::new();
+        let plan = SyncPlan::new(&source, &target, false);
+        assert_eq!(plan.add, vec![Bookmark::new(1, Publicity::Public, vec!["a"])]);
+
+        plan.apply_to(&mut target);
+        assert_eq!(target.get(1).unwrap().favorite_id, None);
+        let plan = SyncPlan::new(&Snapshot::new(), &target, true);
+        assert_eq!(plan.remove.iter().map(|bookmark| bookmark.favorite_id).collect::<Vec<_>>(), vec![None]);
+    }
+}
@@ -0,0 +1,381 @@
+//! Local SQLite cache of `Work` and `User` records, enabled with the `sqlite-cache` feature.
+//!
+//! Records are keyed by id and stored along with the time they were fetched. `work()` and `user()`
+//! only hit the network when the cached record is missing or older than the cache's TTL, while the
+//! query methods run over everything stored locally.
+//!
+//! ```rust,no_run
+//! # extern crate pixiv;
+//! # extern crate reqwest;
+//! # use std::time::Duration;
+//! # use pixiv::cache::MetadataCache;
+//! # use pixiv::client::Pixiv;
+//! # use reqwest::Client;
+//! # fn main() {
+//! #   let client = Client::new();
+//! #   let mut pixiv: Pixiv = Pixiv::new(&client);
+//! #   pixiv.login("username", "password");
+//!     let cache = MetadataCache::open("pixiv.sqlite", Duration::from_secs(60 * 60 * 24))
+//!         .expect("Failed to open cache.");
+//!     let work = cache.work(&pixiv, 66024340).expect("Failed to get work.");
+//!     let more = cache.works_by_user(work.user.map_or(0, |user| user.id)).expect("Query failed.");
+//! # }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use rusqlite::{self, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde_json;
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+use models::{User, Work};
+use text;
+#[cfg(feature = "reqwest-client")]
+use models::PixivResponse;
+#[cfg(feature = "reqwest-client")]
+use PixivRequestBuilder;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS works (
+        id INTEGER PRIMARY KEY,
+        user_id INTEGER,
+        created_time TEXT,
+        fetched_at INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS works_user_id ON works (user_id);
+    CREATE INDEX IF NOT EXISTS works_created_time ON works (created_time);
+    CREATE TABLE IF NOT EXISTS work_tags (
+        work_id INTEGER NOT NULL REFERENCES works (id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (work_id, tag)
+    );
+    CREATE INDEX IF NOT EXISTS work_tags_tag ON work_tags (tag);
+    CREATE TABLE IF NOT EXISTS users (
+        id INTEGER PRIMARY KEY,
+        fetched_at INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+";
+
+// Same format as Pixiv's timestamps, which sorts chronologically as text.
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Error returned by `MetadataCache`.
+#[derive(Debug)]
+pub enum CacheError {
+    /// The database couldn't be read or written.
+    Sqlite(rusqlite::Error),
+    /// A stored record couldn't be (de)serialized.
+    Json(serde_json::Error),
+    /// A record missing from the cache couldn't be fetched.
+    #[cfg(feature = "reqwest-client")]
+    Fetch(FetchError),
+    /// Pixiv returned no record for the id.
+    NotFound(usize),
+}
+
+impl Error for CacheError {
+    fn description(&self) -> &str {
+        "An error occurred while using the metadata cache."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            CacheError::Sqlite(ref e) => Some(e),
+            CacheError::Json(ref e) => Some(e),
+            #[cfg(feature = "reqwest-client")]
+            CacheError::Fetch(ref e) => Some(e),
+            CacheError::NotFound(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CacheError::Sqlite(ref e) => write!(f, "Metadata cache database error: {}", e),
+            CacheError::Json(ref e) => write!(f, "Metadata cache record is invalid: {}", e),
+            #[cfg(feature = "reqwest-client")]
+            CacheError::Fetch(ref e) => write!(f, "{}", e),
+            CacheError::NotFound(id) => write!(f, "Pixiv returned no record for id {}", id),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for CacheError {
+    fn from(e: rusqlite::Error) -> Self {
+        CacheError::Sqlite(e)
+    }
+}
+
+impl From<serde_json::Error> for CacheError {
+    fn from(e: serde_json::Error) -> Self {
+        CacheError::Json(e)
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl From<FetchError> for CacheError {
+    fn from(e: FetchError) -> Self {
+        CacheError::Fetch(e)
+    }
+}
+
+/// A record stored in the cache, along with when it was fetched.
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    pub value: T,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// SQLite cache of `Work` and `User` records.
+#[derive(Debug)]
+pub struct MetadataCache {
+    conn: Connection,
+    ttl: Duration,
+}
+
+impl MetadataCache {
+    /// Opens or creates a cache at `path`. Records older than `ttl` are refetched by `work()` and `user()`.
+    pub fn open<P: AsRef<Path>>(path: P, ttl: Duration) -> Result<MetadataCache, CacheError> {
+        MetadataCache::with_connection(Connection::open(path)?, ttl)
+    }
+    /// Creates a cache which only lives in memory.
+    pub fn in_memory(ttl: Duration) -> Result<MetadataCache, CacheError> {
+        MetadataCache::with_connection(Connection::open_in_memory()?, ttl)
+    }
+
+    fn with_connection(conn: Connection, ttl: Duration) -> Result<MetadataCache, CacheError> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(MetadataCache { conn, ttl })
+    }
+    /// Get the TTL.
+    #[inline]
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+    /// Get a mutable reference to the TTL.
+    #[inline]
+    pub fn ttl_mut(&mut self) -> &mut Duration {
+        &mut self.ttl
+    }
+
+    /// Stores a work, replacing any previous record of it.
+    pub fn put_work(&self, work: &Work) -> Result<(), CacheError> {
+        self.put_work_at(work, Utc::now())
+    }
+
+    fn put_work_at(&self, work: &Work, fetched_at: DateTime<Utc>) -> Result<(), CacheError> {
+        let data = serde_json::to_string(work)?;
+        let created_time = work.created_time.map(|time| time.format(TIME_FORMAT).to_string());
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO works (id, user_id, created_time, fetched_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                work.id as i64,
+                work.user.as_ref().map(|user| user.id as i64),
+                created_time,
+                fetched_at.timestamp(),
+                data,
+            ],
+        )?;
+        tx.execute("DELETE FROM work_tags WHERE work_id = ?1", [work.id as i64])?;
+        for tag in &work.tags {
+            tx.execute(
+                "INSERT OR IGNORE INTO work_tags (work_id, tag) VALUES (?1, ?2)",
+                rusqlite::params![work.id as i64, tag.normalized()],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Stores a user, replacing any previous record of them.
+    pub fn put_user(&self, user: &User) -> Result<(), CacheError> {
+        self.put_user_at(user, Utc::now())
+    }
+
+    fn put_user_at(&self, user: &User, fetched_at: DateTime<Utc>) -> Result<(), CacheError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO users (id, fetched_at, data) VALUES (?1, ?2, ?3)",
+            rusqlite::params![user.id as i64, fetched_at.timestamp(), serde_json::to_string(user)?],
+        )?;
+        Ok(())
+    }
+
+    /// Get a stored work regardless of its age.
+    pub fn get_work(&self, id: usize) -> Result<Option<Cached<Work>>, CacheError> {
+        self.query_one("SELECT data, fetched_at FROM works WHERE id = ?1", id)
+    }
+    /// Get a stored user regardless of their age.
+    pub fn get_user(&self, id: usize) -> Result<Option<Cached<User>>, CacheError> {
+        self.query_one("SELECT data, fetched_at FROM users WHERE id = ?1", id)
+    }
+    /// Get a stored work if it was fetched within the TTL.
+    pub fn fresh_work(&self, id: usize) -> Result<Option<Work>, CacheError> {
+        Ok(self.get_work(id)?.filter(|cached| self.is_fresh(cached)).map(|cached| cached.value))
+    }
+    /// Get a stored user if they were fetched within the TTL.
+    pub fn fresh_user(&self, id: usize) -> Result<Option<User>, CacheError> {
+        Ok(self.get_user(id)?.filter(|cached| self.is_fresh(cached)).map(|cached| cached.value))
+    }
+
+    /// Returns a work from the cache if it's within the TTL, otherwise fetches it with `PixivRequestBuilder::work` and stores it.
+    #[cfg(feature = "reqwest-client")]
+    pub fn work(&self, pixiv: &Pixiv, id: usize) -> Result<Work, CacheError> {
+        if let Some(work) = self.fresh_work(id)? {
+            return Ok(work);
+        }
+        let response: PixivResponse<Work> = pixiv.fetch(PixivRequestBuilder::work(id))?;
+        let work = response.response.into_iter().next().ok_or(CacheError::NotFound(id))?;
+        self.put_work(&work)?;
+        Ok(work)
+    }
+    /// Returns a user from the cache if they're within the TTL, otherwise fetches them with `PixivRequestBuilder::user` and stores them.
+    #[cfg(feature = "reqwest-client")]
+    pub fn user(&self, pixiv: &Pixiv, id: usize) -> Result<User, CacheError> {
+        if let Some(user) = self.fresh_user(id)? {
+            return Ok(user);
+        }
+        let response: PixivResponse<User> = pixiv.fetch(PixivRequestBuilder::user(id))?;
+        let user = response.response.into_iter().next().ok_or(CacheError::NotFound(id))?;
+        self.put_user(&user)?;
+        Ok(user)
+    }
+
+    /// Get all stored works by a user, newest first.
+    pub fn works_by_user(&self, user_id: usize) -> Result<Vec<Cached<Work>>, CacheError> {
+        self.query_works(
+            "SELECT data, fetched_at FROM works WHERE user_id = ?1 ORDER BY created_time DESC, id DESC",
+            rusqlite::params![user_id as i64],
+        )
+    }
+    /// Get all stored works with the given tag, compared after normalisation with `text::normalize`, newest first.
+    pub fn works_by_tag(&self, tag: &str) -> Result<Vec<Cached<Work>>, CacheError> {
+        self.query_works(
+            "SELECT works.data, works.fetched_at FROM works
+             JOIN work_tags ON work_tags.work_id = works.id
+             WHERE work_tags.tag = ?1 ORDER BY works.created_time DESC, works.id DESC",
+            rusqlite::params![text::normalize(tag)],
+        )
+    }
+    /// Get all stored works created within `from..=to`, oldest first.
+    pub fn works_between(&self, from: NaiveDateTime, to: NaiveDateTime) -> Result<Vec<Cached<Work>>, CacheError> {
+        self.query_works(
+            "SELECT data, fetched_at FROM works WHERE created_time BETWEEN ?1 AND ?2
+             ORDER BY created_time, id",
+            rusqlite::params![from.format(TIME_FORMAT).to_string(), to.format(TIME_FORMAT).to_string()],
+        )
+    }
+    /// Removes every record fetched longer than the TTL ago, returning how many were removed.
+    pub fn purge_stale(&self) -> Result<usize, CacheError> {
+        let cutoff = self.cutoff();
+        let works = self.conn.execute("DELETE FROM works WHERE fetched_at < ?1", [cutoff])?;
+        let users = self.conn.execute("DELETE FROM users WHERE fetched_at < ?1", [cutoff])?;
+        Ok(works + users)
+    }
+
+    fn is_fresh<T>(&self, cached: &Cached<T>) -> bool {
+        cached.fetched_at.timestamp() >= self.cutoff()
+    }
+
+    fn cutoff(&self) -> i64 {
+        Utc::now().timestamp() - self.ttl.as_secs() as i64
+    }
+
+    fn query_one<T: DeserializeOwned>(&self, sql: &str, id: usize) -> Result<Option<Cached<T>>, CacheError> {
+        let row = self
+            .conn
+            .query_row(sql, [id as i64], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .optional()?;
+        match row {
+            Some((data, fetched_at)) => Ok(Some(to_cached(&data, fetched_at)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn query_works<P: rusqlite::Params>(&self, sql: &str, params: P) -> Result<Vec<Cached<Work>>, CacheError> {
+        let mut statement = self.conn.prepare(sql)?;
+        let rows = statement.query_map(params, |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+        let mut works = Vec::new();
+        for row in rows {
+            let (data, fetched_at) = row?;
+            works.push(to_cached(&data, fetched_at)?);
+        }
+        Ok(works)
+    }
+}
+
+fn to_cached<T: DeserializeOwned>(data: &str, fetched_at: i64) -> Result<Cached<T>, CacheError> {
+    Ok(Cached {
+        value: serde_json::from_str(data)?,
+        fetched_at: Utc.timestamp_opt(fetched_at, 0).single().unwrap_or_else(Utc::now),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn work(id: usize, user_id: usize, tags: &[&str], created: &str) -> Work {
+        serde_json::from_value(json!({
+            "id": id,
+            "title": format!("work {}", id),
+            "tags": tags,
+            "created_time": created,
+            "user": {"id": user_id, "account": "account", "name": "name"},
+        }))
+        .unwrap()
+    }
+
+    fn day(d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2018, 2, d).unwrap().and_hms_opt(0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_queries() {
+        let cache = MetadataCache::in_memory(Duration::from_secs(60)).unwrap();
+        cache.put_work(&work(1, 10, &["a", "b"], "2018-02-01 12:00:00")).unwrap();
+        cache.put_work(&work(2, 10, &["b"], "2018-02-05 12:00:00")).unwrap();
+        cache.put_work(&work(3, 20, &["a"], "2018-02-10 12:00:00")).unwrap();
+
+        let ids = |works: Vec<Cached<Work>>| works.into_iter().map(|w| w.value.id).collect::<Vec<_>>();
+        assert_eq!(ids(cache.works_by_user(10).unwrap()), vec![2, 1]);
+        assert_eq!(ids(cache.works_by_tag("a").unwrap()), vec![3, 1]);
+        assert_eq!(ids(cache.works_between(day(2), day(11)).unwrap()), vec![2, 3]);
+
+        // replacing a work replaces its tags
+        cache.put_work(&work(1, 10, &["c"], "2018-02-01 12:00:00")).unwrap();
+        assert_eq!(ids(cache.works_by_tag("a").unwrap()), vec![3]);
+        assert_eq!(ids(cache.works_by_tag("c").unwrap()), vec![1]);
+    }
+
+    #[test]
+    fn test_ttl() {
+        let cache = MetadataCache::in_memory(Duration::from_secs(60)).unwrap();
+        let stale = Utc::now() - ::chrono::Duration::seconds(120);
+        cache.put_work_at(&work(1, 10, &[], "2018-02-01 12:00:00"), stale).unwrap();
+        cache.put_work(&work(2, 10, &[], "2018-02-01 12:00:00")).unwrap();
+
+        assert!(cache.get_work(1).unwrap().is_some());
+        assert!(cache.fresh_work(1).unwrap().is_none());
+        assert!(cache.fresh_work(2).unwrap().is_some());
+        assert!(cache.fresh_user(10).unwrap().is_none());
+
+        assert_eq!(cache.purge_stale().unwrap(), 1);
+        assert!(cache.get_work(1).unwrap().is_none());
+        assert!(cache.works_by_tag("a").unwrap().is_empty());
+    }
+}
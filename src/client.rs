@@ -1,22 +1,79 @@
 use ::std::collections::HashMap;
+use ::std::error::Error;
+use ::std::fmt;
+use ::std::sync::Arc;
 
 use ::reqwest;
 use ::reqwest::{Response, Client};
+use ::http::{self, header, Method};
 use ::http::status::StatusCode;
+use ::serde::de::DeserializeOwned;
 use ::serde_json::Value;
 
-use super::{AuthError, PixivRequest};
+use super::{AuthError, PixivRequest, PixivRequestBuilder, RequestError};
+use super::params::Params;
+use super::http_cache::{self, CachedResponse, HttpCache, Lookup};
 
 // This is taken from the Android app, don't worry about it. It's not really "compromisable", to some degree.
 const CLIENT_ID: &str = "MOBrBDS8blbauoSck0ZfDbtuzpyT";
 const CLIENT_SECRET: &str = "lsACyCD94FhDUtGTXi3QzcFE2uU1hqtDaKeqrdwj";
 
+/// Error returned by `Pixiv::fetch`.
+#[derive(Debug)]
+pub enum FetchError {
+    /// The request was given invalid params.
+    Request(RequestError),
+    /// The request failed to send, or the response failed to parse.
+    Http(reqwest::Error),
+    /// Pixiv responded with an unsuccessful status.
+    Status(StatusCode),
+}
+
+impl Error for FetchError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to fetch a response."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            FetchError::Request(ref e) => Some(e),
+            FetchError::Http(ref e) => Some(e),
+            FetchError::Status(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FetchError::Request(ref e) => write!(f, "{}", e),
+            FetchError::Http(ref e) => write!(f, "An error occurred while trying to fetch a response. Reason: {}", e),
+            FetchError::Status(status) => write!(f, "Pixiv responded with an unsuccessful status: {}", status),
+        }
+    }
+}
+
+impl From<RequestError> for FetchError {
+    fn from(e: RequestError) -> Self {
+        FetchError::Request(e)
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        FetchError::Http(e)
+    }
+}
+
 /// Used to authenticate to the Pixiv servers and construct Pixiv requests through methods creating `PixivRequestBuilder`.
 #[derive(Debug, Clone)]
 pub struct Pixiv {
     client: Client,
     access_token: String,
     refresh_token: String,
+    // The user id logged in as, or the username if Pixiv didn't send one. Part of the HTTP cache key.
+    account: Option<String>,
+    http_cache: Option<Arc<HttpCache>>,
 }
 
 impl Pixiv {
@@ -27,6 +84,8 @@ impl Pixiv {
             client: client.clone(),
             access_token: String::default(),
             refresh_token: String::default(),
+            account: None,
+            http_cache: None,
         }
     }
     /// This is required to use all the other functions this library provides. Requires a valid username and password.
@@ -68,6 +127,7 @@ impl Pixiv {
             Value::String(s) => s,
             _ => panic!("Failed to get refresh token."),
         };
+        self.account = Some(user_id(&json_response).unwrap_or_else(|| username.to_owned()));
         Ok(())
     }
     /// Refreshes the authentication. You should use this when your access token is close to expiring.
@@ -106,6 +166,9 @@ impl Pixiv {
             Value::String(s) => s,
             _ => panic!("Failed to get refresh token."),
         };
+        if let Some(user_id) = user_id(&json_response) {
+            self.account = Some(user_id);
+        }
         Ok(())
     }
     /// Get the access token.
@@ -129,6 +192,23 @@ impl Pixiv {
         &mut self.refresh_token
     }
 
+    /// Get the HTTP response cache, if any.
+    #[inline]
+    pub fn http_cache(&self) -> Option<&HttpCache> {
+        self.http_cache.as_deref()
+    }
+    /// Makes `execute()` consult the given HTTP response cache. Clones of this `Pixiv` share it. Responses
+    /// are stored per account, so logging in as another account doesn't serve the previous one's.
+    #[inline]
+    pub fn set_http_cache(&mut self, cache: HttpCache) {
+        self.http_cache = Some(Arc::new(cache));
+    }
+    /// Stops `execute()` from using an HTTP response cache.
+    #[inline]
+    pub fn clear_http_cache(&mut self) {
+        self.http_cache = None;
+    }
+
     // private helper method
     fn send_auth_request(&self, data: &HashMap<&str, &str>) -> Result<Response, reqwest::Error> {
         self.client
@@ -138,23 +218,105 @@ impl Pixiv {
     }
 
     /// Executes a given `PixivRequest`.
-    pub fn execute(&self, request: PixivRequest) -> Result<Response, reqwest::Error> {
+    ///
+    /// If an HTTP cache is set, `GET` requests are answered from it while the stored response is fresh,
+    /// and revalidated with Pixiv once it's stale. See the `http_cache` module.
+    pub fn execute(&self, mut request: PixivRequest) -> Result<Response, reqwest::Error> {
+        let cache = match self.http_cache {
+            Some(ref cache) if request.method == Method::GET => cache,
+            _ => return self.send(request),
+        };
+        let (no_store, no_cache) = http_cache::request_directives(&request.headers);
+        if no_store {
+            return self.send(request);
+        }
+        let key = match self.account {
+            Some(ref account) => HttpCache::account_key(account, &request.method, &request.url),
+            None => HttpCache::key(&request.method, &request.url),
+        };
+        let stale = match cache.lookup(&key) {
+            Lookup::Fresh(ref cached) if !no_cache => return Ok(into_response(cached)),
+            Lookup::Fresh(cached) | Lookup::Stale(cached) => Some(cached),
+            Lookup::Miss => None,
+        };
+        if let Some(ref stale) = stale {
+            stale.add_validators(&mut request.headers);
+        }
+
+        let mut response = self.send(request)?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(mut cached) = stale {
+                cached.revalidated(response.headers());
+                cache.put(&key, &cached);
+                return Ok(into_response(&cached));
+            }
+        }
+        if response.status() != StatusCode::OK {
+            return Ok(response);
+        }
+        let mut body = Vec::new();
+        response.copy_to(&mut body)?;
+        let cached = CachedResponse::new(response.status(), response.headers(), body);
+        cache.put(&key, &cached);
+        Ok(into_response(&cached))
+    }
+
+    // private helper method
+    fn send(&self, request: PixivRequest) -> Result<Response, reqwest::Error> {
         let uri = format!("{}", request.url);
         let url = reqwest::Url::parse(&uri).unwrap();
-        self.client.request(request.method,  url)
-                   .headers(request.headers)
-                   .bearer_auth(self.access_token.clone())
+        let mut builder = self.client.request(request.method, url)
+                                     .headers(request.headers)
+                                     .bearer_auth(self.access_token.clone());
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        builder.send()
+    }
+
+    /// Builds and executes a request, then parses the response as `T`, e.g. `models::PixivResponse<models::Work>`.
+    pub fn fetch<P: Params, T: DeserializeOwned>(&self, builder: PixivRequestBuilder<P>) -> Result<T, FetchError> {
+        let mut response = self.execute(builder.try_build()?)?;
+        if !response.status().is_success() {
+            return Err(FetchError::Status(response.status()));
+        }
+        Ok(response.json()?)
+    }
+
+    /// Downloads a file from one of the urls in a response, e.g. `Work::image_url()`.
+    /// Pixiv's image servers refuse requests without a pixiv `Referer`, which this sets.
+    pub fn download(&self, url: &str) -> Result<Response, reqwest::Error> {
+        self.client.get(url)
+                   .header(header::REFERER, "https://app-api.pixiv.net/")
                    .send()
     }
 }
 
+// Reads the user id from a token response.
+fn user_id(json_response: &Value) -> Option<String> {
+    match json_response["response"]["user"]["id"] {
+        Value::String(ref id) => Some(id.clone()),
+        Value::Number(ref id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+// Rebuilds a `Response` from a cached one.
+fn into_response(cached: &CachedResponse) -> Response {
+    let mut response = http::Response::new(cached.body.clone());
+    *response.status_mut() = StatusCode::from_u16(cached.status).unwrap_or(StatusCode::OK);
+    *response.headers_mut() = cached.header_map();
+    Response::from(response)
+}
+
 #[cfg(test)]
 mod tests {
     use ::reqwest::Client;
-    use ::serde_json::Value;
-    use super::Pixiv;
+    use ::serde_json::{json, Value};
+    use super::{into_response, user_id, Pixiv};
 
     use super::super::*;
+    use super::super::http_cache::CachedResponse;
 
     #[test]
     fn test_login() {
@@ -281,7 +443,7 @@ mod tests {
             .expect("Failed to log in.");
 
         let request = PixivRequestBuilder::following_works()
-            .image_sizes(&["large"])
+            .image_sizes(&[ImageSize::Large])
             .include_sanity_level(false)
             .build();
         let following_works: Value = pixiv
@@ -293,6 +455,26 @@ mod tests {
         println!("{}", following_works);
     }
 
+    #[test]
+    fn test_cached_response() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(http::header::ETAG, http::HeaderValue::from_static("\"abc\""));
+        let cached = CachedResponse::new(http::StatusCode::OK, &headers, br#"{"status":"success"}"#.to_vec());
+
+        let mut response = into_response(&cached);
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(response.headers()[http::header::ETAG], "\"abc\"");
+        let json: Value = response.json().expect("Failed to parse as json.");
+        assert_eq!(json["status"], "success");
+    }
+
+    #[test]
+    fn test_user_id() {
+        assert_eq!(user_id(&json!({"response": {"user": {"id": "6996493"}}})), Some("6996493".to_owned()));
+        assert_eq!(user_id(&json!({"response": {"user": {"id": 6996493}}})), Some("6996493".to_owned()));
+        assert_eq!(user_id(&json!({"response": {}})), None);
+    }
+
     #[test]
     #[should_panic]
     fn test_login_fail() {
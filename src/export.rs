@@ -0,0 +1,686 @@
+//! Exporting works and users as CSV, JSON Lines or Parquet, enabled with the `export` feature
+//! (and the `parquet` feature for Parquet).
+//!
+//! `Work` and `User` are flattened into rows with a fixed set of columns, given by `Row::columns()`,
+//! which is the same for every format. Tags and tools are a list column in JSON Lines and Parquet,
+//! and space separated in CSV. Timestamps are written as `%Y-%m-%d %H:%M:%S`, or as Parquet timestamps.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # extern crate serde_json;
+//! # use pixiv::export::{export_works, ExportFormat};
+//! # use pixiv::models::Work;
+//! # fn main() {
+//! #   let works: Vec<Work> = vec![serde_json::from_str(r#"{"id": 1, "title": "title"}"#).unwrap()];
+//!     let csv = export_works(ExportFormat::Csv, Vec::new(), &works).expect("Failed to export works.");
+//!     assert!(String::from_utf8(csv).unwrap().starts_with("id,title,caption,user_id,"));
+//! # }
+//! ```
+
+use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+
+use chrono::NaiveDateTime;
+use csv;
+use serde_json;
+
+use models::{User, Work};
+use {ImageSize, ProfileImageSize};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Integer,
+    Boolean,
+    Text,
+    TextList,
+    Timestamp,
+}
+
+/// A named column of a `Row`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub name: &'static str,
+    pub column_type: ColumnType,
+}
+
+/// A value in a row. Every value except `Null` matches its column's `ColumnType`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Boolean(bool),
+    Text(String),
+    TextList(Vec<String>),
+    Timestamp(NaiveDateTime),
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Value {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Value {
+        Value::Integer(value as i64)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Value {
+        Value::Integer(i64::from(value))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        Value::Boolean(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Value {
+        Value::Text(value)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(value: &'a str) -> Value {
+        Value::Text(value.to_owned())
+    }
+}
+
+impl From<Vec<String>> for Value {
+    fn from(value: Vec<String>) -> Value {
+        Value::TextList(value)
+    }
+}
+
+impl From<NaiveDateTime> for Value {
+    fn from(value: NaiveDateTime) -> Value {
+        Value::Timestamp(value)
+    }
+}
+
+/// A type which can be flattened into a row of fixed columns.
+pub trait Row {
+    /// The columns, in order. These don't change between calls.
+    fn columns() -> &'static [Column];
+    /// The values, in the same order as `columns()`.
+    fn values(&self) -> Vec<Value>;
+}
+
+macro_rules! columns {
+    ($($name:ident: $ty:ident),* $(,)*) => {
+        &[$(Column { name: stringify!($name), column_type: ColumnType::$ty }),*]
+    };
+}
+
+const WORK_COLUMNS: &[Column] = columns![
+    id: Integer,
+    title: Text,
+    caption: Text,
+    user_id: Integer,
+    user_account: Text,
+    user_name: Text,
+    tags: TextList,
+    tools: TextList,
+    work_type: Text,
+    page_count: Integer,
+    width: Integer,
+    height: Integer,
+    sanity_level: Text,
+    age_limit: Text,
+    publicity: Integer,
+    views_count: Integer,
+    scored_count: Integer,
+    score: Integer,
+    favorited_public: Integer,
+    favorited_private: Integer,
+    commented_count: Integer,
+    created_time: Timestamp,
+    reuploaded_time: Timestamp,
+    image_url_px_128x128: Text,
+    image_url_px_480mw: Text,
+    image_url_small: Text,
+    image_url_medium: Text,
+    image_url_large: Text,
+];
+
+impl Row for Work {
+    fn columns() -> &'static [Column] {
+        WORK_COLUMNS
+    }
+
+    fn values(&self) -> Vec<Value> {
+        let user = self.user.as_ref();
+        let stats = self.stats.as_ref();
+        let favorited = stats.and_then(|stats| stats.favorited_count.as_ref());
+        vec![
+            self.id.into(),
+            self.title.as_str().into(),
+            self.caption.clone().into(),
+            user.map(|user| user.id).into(),
+            user.map(|user| user.account.as_str()).into(),
+            user.map(|user| user.name.as_str()).into(),
+            self.tags.iter().map(|tag| tag.name.clone()).collect::<Vec<_>>().into(),
+            self.tools.clone().into(),
+            self.work_type.map(|work_type| work_type.to_string()).into(),
+            self.page_count.into(),
+            self.width.into(),
+            self.height.into(),
+            self.sanity_level.clone().into(),
+            self.age_limit.clone().into(),
+            self.publicity.into(),
+            stats.and_then(|stats| stats.views_count).into(),
+            stats.and_then(|stats| stats.scored_count).into(),
+            stats.and_then(|stats| stats.score).into(),
+            favorited.map(|count| count.public).into(),
+            favorited.map(|count| count.private).into(),
+            stats.and_then(|stats| stats.commented_count).into(),
+            self.created_time.into(),
+            self.reuploaded_time.into(),
+            self.image_url(ImageSize::Px128x128).into(),
+            self.image_url(ImageSize::Px480mw).into(),
+            self.image_url(ImageSize::Small).into(),
+            self.image_url(ImageSize::Medium).into(),
+            self.image_url(ImageSize::Large).into(),
+        ]
+    }
+}
+
+const USER_COLUMNS: &[Column] = columns![
+    id: Integer,
+    account: Text,
+    name: Text,
+    is_premium: Boolean,
+    is_following: Boolean,
+    is_follower: Boolean,
+    is_friend: Boolean,
+    works: Integer,
+    favorites: Integer,
+    following: Integer,
+    friends: Integer,
+    profile_image_url_px_170x170: Text,
+    profile_image_url_px_50x50: Text,
+];
+
+impl Row for User {
+    fn columns() -> &'static [Column] {
+        USER_COLUMNS
+    }
+
+    fn values(&self) -> Vec<Value> {
+        let stats = self.stats.as_ref();
+        vec![
+            self.id.into(),
+            self.account.as_str().into(),
+            self.name.as_str().into(),
+            self.is_premium.into(),
+            self.is_following.into(),
+            self.is_follower.into(),
+            self.is_friend.into(),
+            stats.and_then(|stats| stats.works).into(),
+            stats.and_then(|stats| stats.favorites).into(),
+            stats.and_then(|stats| stats.following).into(),
+            stats.and_then(|stats| stats.friends).into(),
+            self.profile_image_url(ProfileImageSize::Px170x170).into(),
+            self.profile_image_url(ProfileImageSize::Px50x50).into(),
+        ]
+    }
+}
+
+/// Formats rows can be exported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+/// Error returned by `Exporter`.
+#[derive(Debug)]
+pub enum ExportError {
+    Io(io::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    #[cfg(feature = "parquet")]
+    Parquet(::parquet::errors::ParquetError),
+}
+
+impl Error for ExportError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to export rows."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            ExportError::Io(ref e) => Some(e),
+            ExportError::Csv(ref e) => Some(e),
+            ExportError::Json(ref e) => Some(e),
+            #[cfg(feature = "parquet")]
+            ExportError::Parquet(ref e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "An error occurred while trying to export rows. Reason: ")?;
+        match *self {
+            ExportError::Io(ref e) => write!(f, "{}", e),
+            ExportError::Csv(ref e) => write!(f, "{}", e),
+            ExportError::Json(ref e) => write!(f, "{}", e),
+            #[cfg(feature = "parquet")]
+            ExportError::Parquet(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(e: io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl From<csv::Error> for ExportError {
+    fn from(e: csv::Error) -> Self {
+        ExportError::Csv(e)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(e: serde_json::Error) -> Self {
+        ExportError::Json(e)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<::parquet::errors::ParquetError> for ExportError {
+    fn from(e: ::parquet::errors::ParquetError) -> Self {
+        ExportError::Parquet(e)
+    }
+}
+
+enum Sink<W: Write + Send> {
+    Csv(Box<csv::Writer<W>>),
+    JsonLines(W),
+    #[cfg(feature = "parquet")]
+    Parquet(Box<parquet::ParquetSink<W>>),
+}
+
+/// Writes rows of type `R` to `W` in one of the `ExportFormat`s.
+/// Call `finish()` once done, as CSV and Parquet output is buffered.
+pub struct Exporter<R: Row, W: Write + Send> {
+    sink: Sink<W>,
+    rows: usize,
+    row: PhantomData<fn(&R)>,
+}
+
+impl<R: Row, W: Write + Send> fmt::Debug for Exporter<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Exporter").field("rows", &self.rows).finish()
+    }
+}
+
+impl<R: Row, W: Write + Send> Exporter<R, W> {
+    /// Starts exporting to `writer`, writing the CSV header if needed.
+    pub fn new(format: ExportFormat, writer: W) -> Result<Self, ExportError> {
+        let sink = match format {
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(writer);
+                writer.write_record(R::columns().iter().map(|column| column.name))?;
+                Sink::Csv(Box::new(writer))
+            }
+            ExportFormat::JsonLines => Sink::JsonLines(writer),
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => Sink::Parquet(Box::new(parquet::ParquetSink::new(writer, R::columns())?)),
+        };
+        Ok(Exporter {
+            sink,
+            rows: 0,
+            row: PhantomData,
+        })
+    }
+    /// Get how many rows have been written.
+    #[inline]
+    pub fn rows_written(&self) -> usize {
+        self.rows
+    }
+    /// Writes one row.
+    pub fn write(&mut self, row: &R) -> Result<(), ExportError> {
+        let values = row.values();
+        match self.sink {
+            Sink::Csv(ref mut writer) => writer.write_record(values.iter().map(csv_field))?,
+            Sink::JsonLines(ref mut writer) => write_json_line(writer, R::columns(), &values)?,
+            #[cfg(feature = "parquet")]
+            Sink::Parquet(ref mut sink) => sink.write(values)?,
+        }
+        self.rows += 1;
+        Ok(())
+    }
+    /// Writes every row of an iterator, e.g. the works of each page of a response.
+    /// Returns how many rows were written.
+    pub fn write_all<B: Borrow<R>, I: IntoIterator<Item = B>>(&mut self, rows: I) -> Result<usize, ExportError> {
+        let mut count = 0;
+        for row in rows {
+            self.write(row.borrow())?;
+            count += 1;
+        }
+        Ok(count)
+    }
+    /// Flushes any buffered rows and returns the writer.
+    pub fn finish(self) -> Result<W, ExportError> {
+        match self.sink {
+            Sink::Csv(writer) => writer.into_inner().map_err(|e| ExportError::Io(e.into_error())),
+            Sink::JsonLines(mut writer) => {
+                writer.flush()?;
+                Ok(writer)
+            }
+            #[cfg(feature = "parquet")]
+            Sink::Parquet(sink) => sink.finish(),
+        }
+    }
+}
+
+/// Exports every work of an iterator to `writer`, returning the writer.
+pub fn export_works<W, B, I>(format: ExportFormat, writer: W, works: I) -> Result<W, ExportError>
+where
+    W: Write + Send,
+    B: Borrow<Work>,
+    I: IntoIterator<Item = B>,
+{
+    export::<Work, _, _, _>(format, writer, works)
+}
+
+/// Exports every user of an iterator to `writer`, returning the writer.
+pub fn export_users<W, B, I>(format: ExportFormat, writer: W, users: I) -> Result<W, ExportError>
+where
+    W: Write + Send,
+    B: Borrow<User>,
+    I: IntoIterator<Item = B>,
+{
+    export::<User, _, _, _>(format, writer, users)
+}
+
+/// Exports every row of an iterator to `writer`, returning the writer.
+pub fn export<R, W, B, I>(format: ExportFormat, writer: W, rows: I) -> Result<W, ExportError>
+where
+    R: Row,
+    W: Write + Send,
+    B: Borrow<R>,
+    I: IntoIterator<Item = B>,
+{
+    let mut exporter = Exporter::new(format, writer)?;
+    exporter.write_all(rows)?;
+    exporter.finish()
+}
+
+fn csv_field(value: &Value) -> String {
+    match *value {
+        Value::Null => String::new(),
+        Value::Integer(value) => value.to_string(),
+        Value::Boolean(value) => value.to_string(),
+        Value::Text(ref value) => value.clone(),
+        Value::TextList(ref values) => values.join(" "),
+        Value::Timestamp(ref value) => value.format(TIME_FORMAT).to_string(),
+    }
+}
+
+// Written by hand so the keys keep the column order.
+fn write_json_line<W: Write>(writer: &mut W, columns: &[Column], values: &[Value]) -> Result<(), ExportError> {
+    writer.write_all(b"{")?;
+    for (i, (column, value)) in columns.iter().zip(values).enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut *writer, column.name)?;
+        writer.write_all(b":")?;
+        match *value {
+            Value::Null => writer.write_all(b"null")?,
+            Value::Integer(value) => serde_json::to_writer(&mut *writer, &value)?,
+            Value::Boolean(value) => serde_json::to_writer(&mut *writer, &value)?,
+            Value::Text(ref value) => serde_json::to_writer(&mut *writer, value)?,
+            Value::TextList(ref values) => serde_json::to_writer(&mut *writer, values)?,
+            Value::Timestamp(ref value) => serde_json::to_writer(&mut *writer, &value.format(TIME_FORMAT).to_string())?,
+        }
+    }
+    writer.write_all(b"}\n")?;
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+mod parquet {
+    use std::fmt::Write as FmtWrite;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use parquet::data_type::{BoolType, ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    use super::{Column, ColumnType, ExportError, Value};
+
+    const ROW_GROUP_SIZE: usize = 8192;
+
+    pub struct ParquetSink<W: Write + Send> {
+        writer: SerializedFileWriter<W>,
+        columns: &'static [Column],
+        buffer: Vec<Vec<Value>>,
+    }
+
+    impl<W: Write + Send> ParquetSink<W> {
+        pub fn new(writer: W, columns: &'static [Column]) -> Result<Self, ExportError> {
+            let schema = Arc::new(parse_message_type(&schema(columns))?);
+            let properties = Arc::new(WriterProperties::builder().build());
+            Ok(ParquetSink {
+                writer: SerializedFileWriter::new(writer, schema, properties)?,
+                columns,
+                buffer: Vec::new(),
+            })
+        }
+
+        pub fn write(&mut self, values: Vec<Value>) -> Result<(), ExportError> {
+            self.buffer.push(values);
+            if self.buffer.len() >= ROW_GROUP_SIZE {
+                self.flush()?;
+            }
+            Ok(())
+        }
+
+        pub fn finish(mut self) -> Result<W, ExportError> {
+            self.flush()?;
+            Ok(self.writer.into_inner()?)
+        }
+
+        fn flush(&mut self) -> Result<(), ExportError> {
+            if self.buffer.is_empty() {
+                return Ok(());
+            }
+            let mut row_group = self.writer.next_row_group()?;
+            let mut index = 0;
+            while let Some(mut column) = row_group.next_column()? {
+                let values = self.buffer.iter().map(|row| &row[index]);
+                match self.columns[index].column_type {
+                    ColumnType::Integer | ColumnType::Timestamp => {
+                        let (values, levels) = optional(values, |value| match *value {
+                            Value::Integer(value) => Some(value),
+                            Value::Timestamp(ref value) => Some(value.and_utc().timestamp_millis()),
+                            _ => None,
+                        });
+                        column.typed::<Int64Type>().write_batch(&values, Some(&levels), None)?;
+                    }
+                    ColumnType::Boolean => {
+                        let (values, levels) = optional(values, |value| match *value {
+                            Value::Boolean(value) => Some(value),
+                            _ => None,
+                        });
+                        column.typed::<BoolType>().write_batch(&values, Some(&levels), None)?;
+                    }
+                    ColumnType::Text => {
+                        let (values, levels) = optional(values, |value| match *value {
+                            Value::Text(ref value) => Some(ByteArray::from(value.as_str())),
+                            _ => None,
+                        });
+                        column.typed::<ByteArrayType>().write_batch(&values, Some(&levels), None)?;
+                    }
+                    ColumnType::TextList => {
+                        let mut items = Vec::new();
+                        let mut definition = Vec::new();
+                        let mut repetition = Vec::new();
+                        for value in values {
+                            match *value {
+                                Value::TextList(ref list) if !list.is_empty() => {
+                                    for (i, item) in list.iter().enumerate() {
+                                        items.push(ByteArray::from(item.as_str()));
+                                        definition.push(1);
+                                        repetition.push(if i == 0 { 0 } else { 1 });
+                                    }
+                                }
+                                _ => {
+                                    definition.push(0);
+                                    repetition.push(0);
+                                }
+                            }
+                        }
+                        column
+                            .typed::<ByteArrayType>()
+                            .write_batch(&items, Some(&definition), Some(&repetition))?;
+                    }
+                }
+                column.close()?;
+                index += 1;
+            }
+            row_group.close()?;
+            self.buffer.clear();
+            Ok(())
+        }
+    }
+
+    fn schema(columns: &[Column]) -> String {
+        let mut schema = String::from("message pixiv {\n");
+        for column in columns {
+            let field = match column.column_type {
+                ColumnType::Integer => "optional int64 {}",
+                ColumnType::Boolean => "optional boolean {}",
+                ColumnType::Text => "optional binary {} (UTF8)",
+                ColumnType::TextList => "repeated binary {} (UTF8)",
+                ColumnType::Timestamp => "optional int64 {} (TIMESTAMP(MILLIS,false))",
+            };
+            let _ = writeln!(schema, "  {};", field.replace("{}", column.name));
+        }
+        schema.push('}');
+        schema
+    }
+
+    // Values of an optional column along with their definition levels.
+    fn optional<'a, T, I, F>(values: I, f: F) -> (Vec<T>, Vec<i16>)
+    where
+        I: Iterator<Item = &'a Value>,
+        F: Fn(&Value) -> Option<T>,
+    {
+        let mut present = Vec::new();
+        let mut levels = Vec::new();
+        for value in values {
+            match f(value) {
+                Some(value) => {
+                    present.push(value);
+                    levels.push(1);
+                }
+                None => levels.push(0),
+            }
+        }
+        (present, levels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn works() -> Vec<Work> {
+        vec![
+            serde_json::from_value(json!({
+                "id": 1,
+                "title": "a, \"quoted\" title",
+                "tags": ["オリジナル", "girl"],
+                "created_time": "2018-02-22 01:02:03",
+                "type": "illustration",
+                "stats": {"views_count": 100, "favorited_count": {"public": 3, "private": 1}},
+                "user": {"id": 10, "account": "account", "name": "name"},
+                "image_urls": {"large": "https://i.pximg.net/large.jpg"},
+            }))
+            .unwrap(),
+            serde_json::from_value(json!({"id": 2, "title": "b"})).unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_columns() {
+        let work = &works()[0];
+        assert_eq!(Work::columns().len(), work.values().len());
+        assert_eq!(User::columns().len(), work.user.as_ref().unwrap().values().len());
+        assert_eq!(Work::columns()[0].name, "id");
+        assert_eq!(Work::columns().last().unwrap().name, "image_url_large");
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = String::from_utf8(export_works(ExportFormat::Csv, Vec::new(), works()).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("id,title,caption,user_id,user_account,user_name,tags,tools,work_type,"));
+        assert!(lines[1].starts_with("1,\"a, \"\"quoted\"\" title\",,10,account,name,オリジナル girl,,illustration,"));
+        assert!(lines[1].contains(",100,,,3,1,,2018-02-22 01:02:03,,"));
+        assert!(lines[1].ends_with(",https://i.pximg.net/large.jpg"));
+        assert!(lines[2].starts_with("2,b,,,,,,,,"));
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let mut exporter = Exporter::<Work, _>::new(ExportFormat::JsonLines, Vec::new()).unwrap();
+        assert_eq!(exporter.write_all(works()).unwrap(), 2);
+        let output = String::from_utf8(exporter.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].starts_with(r#"{"id":1,"title":"a, \"quoted\" title","caption":null,"user_id":10,"#));
+
+        let row: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(row["tags"], json!(["オリジナル", "girl"]));
+        assert_eq!(row["created_time"], "2018-02-22 01:02:03");
+        assert_eq!(row["favorited_public"], 3);
+        let row: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(row["tags"], json!([]));
+        assert_eq!(row["views_count"], json!(null));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet() {
+        use std::env;
+        use std::fs::{self, File};
+
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let path = env::temp_dir().join(format!("pixiv-export-test-{}.parquet", ::std::process::id()));
+        export_works(ExportFormat::Parquet, File::create(&path).unwrap(), works()).unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 2);
+        assert_eq!(metadata.schema_descr().num_columns(), WORK_COLUMNS.len());
+
+        let rows: Vec<String> = reader.get_row_iter(None).unwrap().map(|row| row.unwrap().to_string()).collect();
+        assert!(rows[0].contains("tags: [\"オリジナル\", \"girl\"]"), "{}", rows[0]);
+        assert!(rows[1].contains("title: \"b\""), "{}", rows[1]);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,540 @@
+//! Atom and RSS 2.0 feeds of works, e.g. a user's works, a tag search or a ranking.
+//!
+//! Entries link to each work and carry its title, author, thumbnail, tags and posting date. Pixiv's
+//! image hosts refuse requests without a pixiv `Referer`, so some readers won't show the thumbnails.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # extern crate serde_json;
+//! # use pixiv::feed::Feed;
+//! # use pixiv::models::Work;
+//! # fn main() {
+//! #   let works: Vec<Work> = serde_json::from_str(r#"[{"id": 1, "title": "a", "user": {"id": 2, "name": "name"}}]"#).unwrap();
+//!     let feed = Feed::user_works(2, &works);
+//!     assert!(feed.to_atom().contains("<title>name on pixiv</title>"));
+//!     assert!(feed.to_rss().contains("<link>https://www.pixiv.net/artworks/1</link>"));
+//! # }
+//! ```
+//!
+//! With the `feed-server` feature, `FeedServer` serves feeds over HTTP, e.g. to a reader on the same
+//! machine.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
+#[cfg(feature = "feed-server")]
+use std::io;
+#[cfg(feature = "feed-server")]
+use std::net::{SocketAddr, ToSocketAddrs};
+#[cfg(feature = "feed-server")]
+use tiny_http::{Header, Request, Response, Server};
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+#[cfg(feature = "reqwest-client")]
+use models::PixivResponse;
+use models::{Ranking, Work};
+use utils::escape_xml;
+#[cfg(feature = "reqwest-client")]
+use {PixivRequestBuilder, RankingType, SearchMode};
+#[cfg(any(feature = "reqwest-client", all(test, feature = "feed-server")))]
+use RankingMode;
+use ImageSize;
+
+const PIXIV_URL: &str = "https://www.pixiv.net";
+
+/// A feed of works.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    pub title: String,
+    /// The page the feed follows, which is also its Atom id.
+    pub link: String,
+    /// Defaults to when the newest entry was posted.
+    pub updated: Option<NaiveDateTime>,
+    pub entries: Vec<FeedEntry>,
+}
+
+/// An entry of a `Feed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEntry {
+    pub title: String,
+    /// The permalink of the work, which is also the entry's id.
+    pub link: String,
+    pub author: Option<String>,
+    pub author_link: Option<String>,
+    pub thumbnail: Option<String>,
+    pub tags: Vec<String>,
+    /// When the work was posted, in JST like all of Pixiv's timestamps.
+    pub published: Option<NaiveDateTime>,
+    /// When the work was last reuploaded.
+    pub updated: Option<NaiveDateTime>,
+}
+
+impl FeedEntry {
+    /// Creates an entry for a work.
+    pub fn from_work(work: &Work) -> FeedEntry {
+        let thumbnail = [ImageSize::Px480mw, ImageSize::Medium, ImageSize::Px128x128, ImageSize::Small, ImageSize::Large]
+            .iter()
+            .filter_map(|&size| work.image_url(size))
+            .next();
+        FeedEntry {
+            title: work.title.clone(),
+            link: format!("{}/artworks/{}", PIXIV_URL, work.id),
+            author: work.user.as_ref().map(|user| user.name.clone()),
+            author_link: work.user.as_ref().map(|user| format!("{}/users/{}", PIXIV_URL, user.id)),
+            thumbnail: thumbnail.map(str::to_owned),
+            tags: work.tags.iter().map(|tag| tag.name.clone()).collect(),
+            published: work.created_time,
+            updated: work.reuploaded_time,
+        }
+    }
+}
+
+impl Feed {
+    /// Creates an empty feed.
+    pub fn new<T: Into<String>, L: Into<String>>(title: T, link: L) -> Feed {
+        Feed {
+            title: title.into(),
+            link: link.into(),
+            updated: None,
+            entries: Vec::new(),
+        }
+    }
+    /// Creates a feed of works.
+    pub fn from_works<'a, T, L, I>(title: T, link: L, works: I) -> Feed
+    where
+        T: Into<String>,
+        L: Into<String>,
+        I: IntoIterator<Item = &'a Work>,
+    {
+        Feed {
+            entries: works.into_iter().map(FeedEntry::from_work).collect(),
+            ..Feed::new(title, link)
+        }
+    }
+    /// Creates a feed of a user's works, e.g. from `user_works()`. It's named after the user if the works
+    /// include them.
+    pub fn user_works(user_id: usize, works: &[Work]) -> Feed {
+        let title = match works.iter().filter_map(|work| work.user.as_ref()).next() {
+            Some(user) if !user.name.is_empty() => format!("{} on pixiv", user.name),
+            _ => format!("pixiv user {}", user_id),
+        };
+        Feed::from_works(title, format!("{}/users/{}", PIXIV_URL, user_id), works)
+    }
+    /// Creates a feed of the works found for a tag or query, e.g. from `search_works()`.
+    pub fn search(query: &str, works: &[Work]) -> Feed {
+        let link = format!("{}/tags/{}/artworks", PIXIV_URL, percent_encode(query));
+        Feed::from_works(format!("{} on pixiv", query), link, works)
+    }
+    /// Creates a feed of a ranking, with each entry's title prefixed by its rank.
+    pub fn ranking(ranking: &Ranking) -> Feed {
+        let mode = ranking.mode.as_deref().unwrap_or("daily");
+        let mut feed = Feed::from_works(
+            format!("pixiv {} ranking", mode),
+            format!("{}/ranking.php?mode={}", PIXIV_URL, mode),
+            ranking.works.iter().map(|ranked| &ranked.work),
+        );
+        for (entry, ranked) in feed.entries.iter_mut().zip(&ranking.works) {
+            entry.title = format!("#{} {}", ranked.rank, entry.title);
+        }
+        feed
+    }
+
+    // The newest time of any entry, or now for an empty feed.
+    fn updated(&self) -> NaiveDateTime {
+        self.updated
+            .or_else(|| self.entries.iter().filter_map(|entry| entry.updated.max(entry.published)).max())
+            .unwrap_or_else(|| Utc::now().with_timezone(&jst_offset()).naive_local())
+    }
+
+    /// Renders the feed as an Atom document.
+    pub fn to_atom(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        let _ = writeln!(out, r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:media="http://search.yahoo.com/mrss/">"#);
+        let _ = writeln!(out, "  <id>{}</id>", escape_xml(&self.link));
+        let _ = writeln!(out, "  <title>{}</title>", escape_xml(&self.title));
+        let _ = writeln!(out, "  <updated>{}</updated>", jst(&self.updated()).to_rfc3339());
+        let _ = writeln!(out, r#"  <link rel="alternate" href="{}"/>"#, escape_xml(&self.link));
+        // Atom requires an author, which entries without one inherit from the feed.
+        let _ = writeln!(out, "  <author><name>pixiv</name><uri>{}</uri></author>", PIXIV_URL);
+        for entry in &self.entries {
+            let published = entry.published.unwrap_or_else(|| self.updated());
+            out.push_str("  <entry>\n");
+            let _ = writeln!(out, "    <id>{}</id>", escape_xml(&entry.link));
+            let _ = writeln!(out, "    <title>{}</title>", escape_xml(&entry.title));
+            let _ = writeln!(out, r#"    <link rel="alternate" href="{}"/>"#, escape_xml(&entry.link));
+            let _ = writeln!(out, "    <published>{}</published>", jst(&published).to_rfc3339());
+            let _ = writeln!(out, "    <updated>{}</updated>", jst(&entry.updated.unwrap_or(published)).to_rfc3339());
+            if let Some(ref author) = entry.author {
+                let _ = write!(out, "    <author><name>{}</name>", escape_xml(author));
+                if let Some(ref uri) = entry.author_link {
+                    let _ = write!(out, "<uri>{}</uri>", escape_xml(uri));
+                }
+                out.push_str("</author>\n");
+            }
+            for tag in &entry.tags {
+                let _ = writeln!(out, r#"    <category term="{}"/>"#, escape_xml(tag));
+            }
+            if let Some(ref thumbnail) = entry.thumbnail {
+                let _ = writeln!(out, r#"    <media:thumbnail url="{}"/>"#, escape_xml(thumbnail));
+                let _ = writeln!(
+                    out,
+                    r#"    <content type="html">{}</content>"#,
+                    escape_xml(&format!(r#"<img src="{}" alt="{}"/>"#, escape_xml(thumbnail), escape_xml(&entry.title)))
+                );
+            }
+            out.push_str("  </entry>\n");
+        }
+        out.push_str("</feed>\n");
+        out
+    }
+
+    /// Renders the feed as an RSS 2.0 document.
+    pub fn to_rss(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        let _ = writeln!(
+            out,
+            r#"<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:media="http://search.yahoo.com/mrss/">"#
+        );
+        out.push_str("  <channel>\n");
+        let _ = writeln!(out, "    <title>{}</title>", escape_xml(&self.title));
+        let _ = writeln!(out, "    <link>{}</link>", escape_xml(&self.link));
+        let _ = writeln!(out, "    <description>{}</description>", escape_xml(&self.title));
+        let _ = writeln!(out, "    <lastBuildDate>{}</lastBuildDate>", jst(&self.updated()).to_rfc2822());
+        for entry in &self.entries {
+            out.push_str("    <item>\n");
+            let _ = writeln!(out, "      <title>{}</title>", escape_xml(&entry.title));
+            let _ = writeln!(out, "      <link>{}</link>", escape_xml(&entry.link));
+            let _ = writeln!(out, r#"      <guid isPermaLink="true">{}</guid>"#, escape_xml(&entry.link));
+            if let Some(published) = entry.published {
+                let _ = writeln!(out, "      <pubDate>{}</pubDate>", jst(&published).to_rfc2822());
+            }
+            if let Some(ref author) = entry.author {
+                let _ = writeln!(out, "      <dc:creator>{}</dc:creator>", escape_xml(author));
+            }
+            for tag in &entry.tags {
+                let _ = writeln!(out, "      <category>{}</category>", escape_xml(tag));
+            }
+            if let Some(ref thumbnail) = entry.thumbnail {
+                let _ = writeln!(out, r#"      <media:thumbnail url="{}"/>"#, escape_xml(thumbnail));
+                let _ = writeln!(
+                    out,
+                    "      <description>{}</description>",
+                    escape_xml(&format!(r#"<img src="{}" alt="{}"/>"#, escape_xml(thumbnail), escape_xml(&entry.title)))
+                );
+            }
+            out.push_str("    </item>\n");
+        }
+        out.push_str("  </channel>\n</rss>\n");
+        out
+    }
+}
+
+fn jst_offset() -> FixedOffset {
+    FixedOffset::east_opt(9 * 3600).expect("JST is a valid offset.")
+}
+
+// Pixiv's timestamps are in JST.
+fn jst(time: &NaiveDateTime) -> DateTime<FixedOffset> {
+    jst_offset().from_local_datetime(time).single().expect("JST has no ambiguous times.")
+}
+
+// Percent-encodes everything but unreserved characters, for a path segment.
+fn percent_encode(text: &str) -> String {
+    let mut out = String::new();
+    for b in text.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(b as char),
+            _ => {
+                let _ = write!(out, "%{:02X}", b);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(any(feature = "reqwest-client", all(test, feature = "feed-server")))]
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Fetches the feed for a path, or `None` if the path isn't one of:
+///
+/// * `/users/{user_id}`, the user's works.
+/// * `/tags/{tag}`, the newest works with the tag.
+/// * `/ranking/{mode}`, e.g. `/ranking/daily`, today's ranking of all works.
+///
+/// The path is taken percent-encoded, as requested, so a tag may contain an encoded `/` or `%`.
+#[cfg(feature = "reqwest-client")]
+pub fn fetch(pixiv: &Pixiv, path: &str) -> Result<Option<Feed>, FetchError> {
+    match Route::parse(path) {
+        Some(Route::User(user_id)) => {
+            let response: PixivResponse<Work> = pixiv.fetch(PixivRequestBuilder::user_works(user_id))?;
+            Ok(Some(Feed::user_works(user_id, &response.response)))
+        }
+        Some(Route::Tag(tag)) => {
+            let builder = PixivRequestBuilder::search_works(tag.as_str()).search_mode(SearchMode::ExactTag);
+            let response: PixivResponse<Work> = pixiv.fetch(builder)?;
+            Ok(Some(Feed::search(&tag, &response.response)))
+        }
+        Some(Route::Ranking(mode)) => {
+            let builder = PixivRequestBuilder::ranking(RankingType::All).ranking_mode(mode);
+            let response: PixivResponse<Ranking> = pixiv.fetch(builder)?;
+            Ok(response.response.first().map(Feed::ranking))
+        }
+        None => Ok(None),
+    }
+}
+
+// The feed a path of `fetch` asks for.
+#[cfg(any(feature = "reqwest-client", all(test, feature = "feed-server")))]
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    User(usize),
+    Tag(String),
+    Ranking(RankingMode),
+}
+
+#[cfg(any(feature = "reqwest-client", all(test, feature = "feed-server")))]
+impl Route {
+    // Splits the still-encoded path into segments before decoding any, so an encoded `/` stays in its segment.
+    fn parse(path: &str) -> Option<Route> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match segments[..] {
+            ["users", user_id] => user_id.parse().ok().map(Route::User),
+            ["tags", tag] => match percent_decode(tag) {
+                Some(tag) if !tag.trim().is_empty() => Some(Route::Tag(tag)),
+                _ => None,
+            },
+            ["ranking", mode] => RankingMode::from_str(mode).map(Route::Ranking),
+            _ => None,
+        }
+    }
+}
+
+/// The format of a served feed.
+#[cfg(feature = "feed-server")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Atom,
+    Rss,
+}
+
+#[cfg(feature = "feed-server")]
+impl FeedFormat {
+    // Splits the format off a path by its extension, defaulting to Atom.
+    fn from_path(path: &str) -> (&str, FeedFormat) {
+        match path.strip_suffix(".rss") {
+            Some(path) => (path, FeedFormat::Rss),
+            None => (path.strip_suffix(".atom").unwrap_or(path), FeedFormat::Atom),
+        }
+    }
+    fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+        }
+    }
+}
+
+/// A small HTTP server for feeds, for a reader on the same machine or network. Paths ending in `.rss`
+/// are served as RSS, others as Atom.
+///
+/// ```rust,no_run
+/// # extern crate pixiv;
+/// # extern crate reqwest;
+/// # use pixiv::client::Pixiv;
+/// # use pixiv::feed::{self, FeedServer};
+/// # use reqwest::Client;
+/// # fn main() {
+/// #   let client = Client::new();
+/// #   let mut pixiv: Pixiv = Pixiv::new(&client);
+/// #   pixiv.login("username", "password");
+///     // e.g. http://127.0.0.1:8080/users/6996493.atom or /tags/風景.rss
+///     let server = FeedServer::bind("127.0.0.1:8080").expect("Failed to bind.");
+///     let error = server.serve(|path| feed::fetch(&pixiv, path).map_err(|e| e.to_string()));
+///     eprintln!("Stopped serving: {}", error);
+/// # }
+/// ```
+#[cfg(feature = "feed-server")]
+pub struct FeedServer {
+    server: Server,
+}
+
+#[cfg(feature = "feed-server")]
+impl FeedServer {
+    /// Listens on an address, e.g. `127.0.0.1:8080`.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<FeedServer> {
+        let server = Server::http(addr).map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(FeedServer { server })
+    }
+    /// Get the address the server listens on.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Serves requests until the server can't accept any more, and returns why. `feeds` is called with each
+    /// request's path, still percent-encoded and without its query or extension, and returns the feed,
+    /// `None` for a 404 or an error message for a 502.
+    pub fn serve<F: FnMut(&str) -> Result<Option<Feed>, String>>(&self, mut feeds: F) -> io::Error {
+        loop {
+            let request = match self.server.recv() {
+                Ok(request) => request,
+                Err(e) => return e,
+            };
+            // failing to respond only affects that client, e.g. one which hung up
+            let _ = respond(request, &mut feeds);
+        }
+    }
+    /// Waits for a request and responds to it like `serve`.
+    pub fn handle_next<F: FnMut(&str) -> Result<Option<Feed>, String>>(&self, mut feeds: F) -> io::Result<()> {
+        let request = self.server.recv()?;
+        respond(request, &mut feeds)
+    }
+}
+
+#[cfg(feature = "feed-server")]
+fn respond<F: FnMut(&str) -> Result<Option<Feed>, String>>(request: Request, feeds: &mut F) -> io::Result<()> {
+    let url = request.url().to_owned();
+    let path = url.split('?').next().unwrap_or_default();
+    let (path, format) = FeedFormat::from_path(path);
+    let result = feeds(path);
+    let text_header = Header::from_bytes("Content-Type", "text/plain; charset=utf-8").unwrap();
+    match result {
+        Ok(Some(feed)) => {
+            let body = match format {
+                FeedFormat::Atom => feed.to_atom(),
+                FeedFormat::Rss => feed.to_rss(),
+            };
+            let header = Header::from_bytes("Content-Type", format.content_type()).unwrap();
+            request.respond(Response::from_string(body).with_header(header))
+        }
+        Ok(None) => request.respond(Response::from_string("Not found").with_status_code(404).with_header(text_header)),
+        Err(message) => request.respond(Response::from_string(message).with_status_code(502).with_header(text_header)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn work() -> Work {
+        serde_json::from_value(json!({
+            "id": 66024340,
+            "title": "Sky & Sea",
+            "tags": ["風景", {"name": "オリジナル", "translated_name": "original"}],
+            "image_urls": {"px_480mw": "https://i.pximg.net/480mw.jpg", "large": "https://i.pximg.net/large.jpg"},
+            "created_time": "2017-11-26 00:00:11",
+            "user": {"id": 6996493, "name": "<name>"}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_atom() {
+        let atom = Feed::user_works(6996493, &[work()]).to_atom();
+        assert!(atom.contains("<title>&lt;name&gt; on pixiv</title>"));
+        assert!(atom.contains("<updated>2017-11-26T00:00:11+09:00</updated>"));
+        assert!(atom.contains("<id>https://www.pixiv.net/artworks/66024340</id>"));
+        assert!(atom.contains("<title>Sky &amp; Sea</title>"));
+        assert!(atom.contains("<author><name>&lt;name&gt;</name><uri>https://www.pixiv.net/users/6996493</uri></author>"));
+        assert!(atom.contains(r#"<category term="オリジナル"/>"#));
+        assert!(atom.contains(r#"<media:thumbnail url="https://i.pximg.net/480mw.jpg"/>"#));
+        assert!(atom.contains("&lt;img src=&quot;https://i.pximg.net/480mw.jpg&quot;"));
+    }
+
+    #[test]
+    fn test_rss() {
+        let ranking: Ranking = serde_json::from_value(json!({
+            "mode": "weekly",
+            "works": [{"rank": 1, "work": work()}]
+        }))
+        .unwrap();
+        let rss = Feed::ranking(&ranking).to_rss();
+        assert!(rss.contains("<link>https://www.pixiv.net/ranking.php?mode=weekly</link>"));
+        assert!(rss.contains("<title>#1 Sky &amp; Sea</title>"));
+        assert!(rss.contains("<pubDate>Sun, 26 Nov 2017 00:00:11 +0900</pubDate>"));
+        assert!(rss.contains("<dc:creator>&lt;name&gt;</dc:creator>"));
+        assert!(rss.contains("<category>風景</category>"));
+
+        let feed = Feed::search("風景 空", &[]);
+        assert_eq!(feed.link, "https://www.pixiv.net/tags/%E9%A2%A8%E6%99%AF%20%E7%A9%BA/artworks");
+        assert!(feed.to_rss().contains("<lastBuildDate>"));
+    }
+
+    #[cfg(feature = "feed-server")]
+    #[test]
+    fn test_server() {
+        use std::io::{Read, Write};
+        use std::net::TcpStream;
+        use std::thread;
+
+        let server = FeedServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let get = move |path: &'static str| {
+            thread::spawn(move || {
+                let mut stream = TcpStream::connect(addr).unwrap();
+                write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path).unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                response
+            })
+        };
+        // routed like `fetch`, answering tag feeds with a work titled after the tag
+        let feeds = |path: &str| match Route::parse(path) {
+            Some(Route::Tag(tag)) => {
+                let mut work = work();
+                work.title = tag.clone();
+                Ok(Some(Feed::search(&tag, &[work])))
+            }
+            _ if path == "/broken" => Err("Pixiv is down".to_owned()),
+            _ => Ok(None),
+        };
+
+        let client = get("/tags/%E9%A2%A8%E6%99%AF.rss?x=1");
+        server.handle_next(feeds).unwrap();
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("Content-Type: application/rss+xml; charset=utf-8"));
+        assert!(response.contains("<rss version=\"2.0\""));
+
+        let client = get("/tags/Fate%2FGrand%20Order.atom");
+        server.handle_next(feeds).unwrap();
+        let response = client.join().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("<title>Fate/Grand Order</title>"));
+
+        let client = get("/tags/100%25");
+        server.handle_next(feeds).unwrap();
+        assert!(client.join().unwrap().contains("<title>100%</title>"));
+
+        let client = get("/tags/%E9%A2%A8/%E6%99%AF");
+        server.handle_next(feeds).unwrap();
+        assert!(client.join().unwrap().starts_with("HTTP/1.1 404"));
+
+        let client = get("/missing.atom");
+        server.handle_next(feeds).unwrap();
+        assert!(client.join().unwrap().starts_with("HTTP/1.1 404"));
+
+        let client = get("/broken");
+        server.handle_next(feeds).unwrap();
+        assert!(client.join().unwrap().contains("Pixiv is down"));
+    }
+}
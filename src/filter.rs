@@ -0,0 +1,443 @@
+//! Client-side filtering of works by content rating, AI generation, tags and users.
+//!
+//! Params like `show_r18` and `include_sanity_level` are only hints to the server, so a `ContentFilter`
+//! checks the works that actually came back, and reports what it removed and why.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # extern crate serde_json;
+//! # use pixiv::filter::{ContentFilter, RemovalReason};
+//! # use pixiv::models::Work;
+//! # fn main() {
+//! #   let works: Vec<Work> = serde_json::from_str(r#"[
+//! #       {"id": 1, "title": "a", "sanity_level": "white", "age_limit": "all-age"},
+//! #       {"id": 2, "title": "b", "sanity_level": "black", "age_limit": "r18"}
+//! #   ]"#).unwrap();
+//!     let filter = ContentFilter::safe().block_tag("ネタバレ");
+//!     let (works, report) = filter.filter(works);
+//!     assert_eq!(works.len(), 1);
+//!     assert_eq!(report.removed[0].reason, RemovalReason::R18);
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use models::{Tag, Work};
+use text;
+
+/// How suggestive a work is, as Pixiv's `sanity_level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SanityLevel {
+    White,
+    Gray,
+    SemiBlack,
+    Black,
+}
+
+impl SanityLevel {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            SanityLevel::White => "white",
+            SanityLevel::Gray => "gray",
+            SanityLevel::SemiBlack => "semi_black",
+            SanityLevel::Black => "black",
+        }
+    }
+    fn from_str(value: &str) -> Option<SanityLevel> {
+        match value {
+            "white" => Some(SanityLevel::White),
+            "gray" => Some(SanityLevel::Gray),
+            "semi_black" => Some(SanityLevel::SemiBlack),
+            "black" => Some(SanityLevel::Black),
+            // the App API's numeric levels
+            "0" | "2" => Some(SanityLevel::White),
+            "4" => Some(SanityLevel::Gray),
+            "6" => Some(SanityLevel::SemiBlack),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SanityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a `ContentFilter` removed a work.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RemovalReason {
+    /// The work's sanity level is above the allowed one.
+    SanityLevel(SanityLevel),
+    R18,
+    R18G,
+    AiGenerated,
+    BlockedTag(String),
+    BlockedUser(usize),
+    /// The filter is strict and the work lacks the named field a rule needs.
+    Unknown(&'static str),
+}
+
+impl fmt::Display for RemovalReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RemovalReason::SanityLevel(level) => write!(f, "sanity level {}", level),
+            RemovalReason::R18 => f.write_str("R-18"),
+            RemovalReason::R18G => f.write_str("R-18G"),
+            RemovalReason::AiGenerated => f.write_str("AI-generated"),
+            RemovalReason::BlockedTag(ref tag) => write!(f, "blocked tag {}", tag),
+            RemovalReason::BlockedUser(id) => write!(f, "blocked user {}", id),
+            RemovalReason::Unknown(field) => write!(f, "unknown {}", field),
+        }
+    }
+}
+
+/// A work removed by a `ContentFilter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Removal {
+    pub work_id: usize,
+    pub title: String,
+    pub reason: RemovalReason,
+}
+
+/// What a `ContentFilter` kept and removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilterReport {
+    pub kept: usize,
+    pub removed: Vec<Removal>,
+}
+
+impl FilterReport {
+    /// Get how many works were removed for a reason.
+    pub fn removed_for(&self, reason: &RemovalReason) -> usize {
+        self.removed.iter().filter(|removal| removal.reason == *reason).count()
+    }
+
+    fn record(&mut self, work: &Work, result: Result<(), RemovalReason>) -> bool {
+        match result {
+            Ok(()) => {
+                self.kept += 1;
+                true
+            }
+            Err(reason) => {
+                self.removed.push(Removal {
+                    work_id: work.id,
+                    title: work.title.clone(),
+                    reason,
+                });
+                false
+            }
+        }
+    }
+}
+
+impl fmt::Display for FilterReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "kept {}, removed {}", self.kept, self.removed.len())?;
+        for removal in &self.removed {
+            write!(f, "\n  {} ({}): {}", removal.title, removal.work_id, removal.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Drops works by sanity level, R-18/R-18G rating, AI generation, tags and users.
+/// By default everything is allowed; works missing a field are only dropped when `strict`.
+#[derive(Debug, Clone)]
+pub struct ContentFilter {
+    max_sanity_level: Option<SanityLevel>,
+    allow_r18: bool,
+    allow_r18g: bool,
+    allow_ai: bool,
+    blocked_tags: HashSet<String>,
+    blocked_users: HashSet<usize>,
+    strict: bool,
+}
+
+impl Default for ContentFilter {
+    fn default() -> ContentFilter {
+        ContentFilter::new()
+    }
+}
+
+impl ContentFilter {
+    /// Creates a filter which allows everything.
+    pub fn new() -> ContentFilter {
+        ContentFilter {
+            max_sanity_level: None,
+            allow_r18: true,
+            allow_r18g: true,
+            allow_ai: true,
+            blocked_tags: HashSet::new(),
+            blocked_users: HashSet::new(),
+            strict: false,
+        }
+    }
+    /// Creates a strict filter for all-ages content: no R-18 or R-18G, and a sanity level of at most `Gray`.
+    pub fn safe() -> ContentFilter {
+        ContentFilter::new()
+            .max_sanity_level(SanityLevel::Gray)
+            .allow_r18(false)
+            .allow_r18g(false)
+            .strict(true)
+    }
+    /// Sets the highest sanity level allowed.
+    #[inline]
+    pub fn max_sanity_level(mut self, level: SanityLevel) -> Self {
+        self.max_sanity_level = Some(level);
+        self
+    }
+    /// Sets whether R-18 works are allowed (default: `true`).
+    #[inline]
+    pub fn allow_r18(mut self, value: bool) -> Self {
+        self.allow_r18 = value;
+        self
+    }
+    /// Sets whether R-18G works are allowed (default: `true`).
+    #[inline]
+    pub fn allow_r18g(mut self, value: bool) -> Self {
+        self.allow_r18g = value;
+        self
+    }
+    /// Sets whether AI-generated works are allowed (default: `true`).
+    #[inline]
+    pub fn allow_ai(mut self, value: bool) -> Self {
+        self.allow_ai = value;
+        self
+    }
+    /// Drops works with a tag, compared after normalisation with `text::normalize`
+    /// against both the original and translated names.
+    pub fn block_tag<S: AsRef<str>>(mut self, tag: S) -> Self {
+        self.blocked_tags.insert(text::normalize(tag.as_ref()));
+        self
+    }
+    /// Drops works with any of the tags.
+    pub fn block_tags<S: AsRef<str>, I: IntoIterator<Item = S>>(self, tags: I) -> Self {
+        tags.into_iter().fold(self, |filter, tag| filter.block_tag(tag))
+    }
+    /// Drops works by a user.
+    pub fn block_user(mut self, id: usize) -> Self {
+        self.blocked_users.insert(id);
+        self
+    }
+    /// Drops works by any of the users.
+    pub fn block_users<I: IntoIterator<Item = usize>>(mut self, ids: I) -> Self {
+        self.blocked_users.extend(ids);
+        self
+    }
+    /// Sets whether works are dropped when they lack a field one of the rules needs (default: `false`).
+    #[inline]
+    pub fn strict(mut self, value: bool) -> Self {
+        self.strict = value;
+        self
+    }
+
+    fn is_blocked(&self, tag: &Tag) -> bool {
+        !self.blocked_tags.is_empty()
+            && (self.blocked_tags.contains(&tag.normalized())
+                || tag.translated_name.as_ref().is_some_and(|translated| self.blocked_tags.contains(&text::normalize(translated))))
+    }
+
+    /// Checks a work, returning the first reason it should be removed.
+    pub fn check(&self, work: &Work) -> Result<(), RemovalReason> {
+        if !self.blocked_users.is_empty() {
+            match work.user {
+                Some(ref user) if self.blocked_users.contains(&user.id) => {
+                    return Err(RemovalReason::BlockedUser(user.id))
+                }
+                None if self.strict => return Err(RemovalReason::Unknown("user")),
+                _ => {}
+            }
+        }
+        if let Some(tag) = work.tags.iter().find(|tag| self.is_blocked(tag)) {
+            return Err(RemovalReason::BlockedTag(tag.name.clone()));
+        }
+        if !self.allow_r18 || !self.allow_r18g {
+            match restriction(work) {
+                Some(Restriction::R18) if !self.allow_r18 => return Err(RemovalReason::R18),
+                Some(Restriction::R18G) if !self.allow_r18g => return Err(RemovalReason::R18G),
+                None if self.strict => return Err(RemovalReason::Unknown("age_limit")),
+                _ => {}
+            }
+        }
+        if let Some(max) = self.max_sanity_level {
+            match work.sanity_level.as_ref().and_then(|level| SanityLevel::from_str(level)) {
+                Some(level) if level > max => return Err(RemovalReason::SanityLevel(level)),
+                None if self.strict => return Err(RemovalReason::Unknown("sanity_level")),
+                _ => {}
+            }
+        }
+        if !self.allow_ai {
+            match work.ai_type {
+                Some(2) => return Err(RemovalReason::AiGenerated),
+                Some(1) => {}
+                _ if self.strict => return Err(RemovalReason::Unknown("ai_type")),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    /// Whether a work passes the filter.
+    #[inline]
+    pub fn allows(&self, work: &Work) -> bool {
+        self.check(work).is_ok()
+    }
+    /// Splits works into the ones passing the filter and a report of the rest.
+    pub fn filter<I: IntoIterator<Item = Work>>(&self, works: I) -> (Vec<Work>, FilterReport) {
+        let mut filtered = self.filter_iter(works);
+        let kept = filtered.by_ref().collect();
+        (kept, filtered.into_report())
+    }
+    /// Removes works not passing the filter, e.g. from `PixivResponse::response`.
+    pub fn retain(&self, works: &mut Vec<Work>) -> FilterReport {
+        let mut report = FilterReport::default();
+        works.retain(|work| report.record(work, self.check(work)));
+        report
+    }
+    /// Wraps an iterator of works, skipping ones not passing the filter.
+    pub fn filter_iter<I: IntoIterator<Item = Work>>(&self, works: I) -> Filtered<'_, I::IntoIter> {
+        Filtered {
+            filter: self,
+            works: works.into_iter(),
+            report: FilterReport::default(),
+        }
+    }
+}
+
+/// Iterator returned by `ContentFilter::filter_iter`.
+#[derive(Debug)]
+pub struct Filtered<'a, I> {
+    filter: &'a ContentFilter,
+    works: I,
+    report: FilterReport,
+}
+
+impl<'a, I> Filtered<'a, I> {
+    /// Get the report of the works seen so far.
+    #[inline]
+    pub fn report(&self) -> &FilterReport {
+        &self.report
+    }
+    /// Get the report of the works seen so far, dropping the iterator.
+    #[inline]
+    pub fn into_report(self) -> FilterReport {
+        self.report
+    }
+}
+
+impl<'a, I: Iterator<Item = Work>> Iterator for Filtered<'a, I> {
+    type Item = Work;
+
+    fn next(&mut self) -> Option<Work> {
+        let filter = self.filter;
+        let report = &mut self.report;
+        self.works.find(|work| report.record(work, filter.check(work)))
+    }
+}
+
+enum Restriction {
+    AllAges,
+    R18,
+    R18G,
+}
+
+// Newer endpoints send `x_restrict`, older ones `age_limit`; R-18 tags are the fallback.
+fn restriction(work: &Work) -> Option<Restriction> {
+    match work.x_restrict {
+        Some(0) => return Some(Restriction::AllAges),
+        Some(1) => return Some(Restriction::R18),
+        Some(2) => return Some(Restriction::R18G),
+        _ => {}
+    }
+    match work.age_limit.as_deref() {
+        Some("all-age") => return Some(Restriction::AllAges),
+        Some("r18") => return Some(Restriction::R18),
+        Some("r18-g") => return Some(Restriction::R18G),
+        _ => {}
+    }
+    if work.tags.iter().any(|tag| tag.name == "R-18G") {
+        Some(Restriction::R18G)
+    } else if work.tags.iter().any(|tag| tag.name == "R-18") {
+        Some(Restriction::R18)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn works() -> Vec<Work> {
+        serde_json::from_value(json!([
+            {"id": 1, "title": "safe", "age_limit": "all-age", "sanity_level": "white", "illust_ai_type": 1,
+             "user": {"id": 10, "account": "a", "name": "a"}},
+            {"id": 2, "title": "r18", "x_restrict": 1, "sanity_level": "black", "illust_ai_type": 1},
+            {"id": 3, "title": "r18g", "age_limit": "r18-g", "sanity_level": "black"},
+            {"id": 4, "title": "suggestive", "age_limit": "all-age", "sanity_level": "semi_black"},
+            {"id": 5, "title": "ai", "age_limit": "all-age", "sanity_level": "white", "illust_ai_type": 2},
+            {"id": 6, "title": "tagged", "tags": ["R-18"], "user": {"id": 20, "account": "b", "name": "b"}},
+            {"id": 7, "title": "unknown"},
+        ]))
+        .unwrap()
+    }
+
+    fn ids(works: &[Work]) -> Vec<usize> {
+        works.iter().map(|work| work.id).collect()
+    }
+
+    #[test]
+    fn test_filter() {
+        let (kept, report) = ContentFilter::new().filter(works());
+        assert_eq!(ids(&kept), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert!(report.removed.is_empty());
+
+        let (kept, report) = ContentFilter::new().allow_r18(false).allow_ai(false).filter(works());
+        assert_eq!(ids(&kept), vec![1, 3, 4, 7]);
+        assert_eq!(report.kept, 4);
+        assert_eq!(report.removed_for(&RemovalReason::R18), 2);
+        assert_eq!(report.removed_for(&RemovalReason::AiGenerated), 1);
+
+        let (kept, report) = ContentFilter::safe().filter(works());
+        assert_eq!(ids(&kept), vec![1, 5]);
+        assert_eq!(report.removed_for(&RemovalReason::SanityLevel(SanityLevel::SemiBlack)), 1);
+        assert_eq!(report.removed_for(&RemovalReason::Unknown("age_limit")), 1);
+
+        let mut works = works();
+        let report = ContentFilter::new().block_tag("r-18").block_user(10).retain(&mut works);
+        assert_eq!(ids(&works), vec![2, 3, 4, 5, 7]);
+        assert_eq!(report.removed[0].reason, RemovalReason::BlockedUser(10));
+        assert_eq!(report.removed[1].reason, RemovalReason::BlockedTag("R-18".to_owned()));
+    }
+
+    #[test]
+    fn test_numeric_sanity_level() {
+        assert_eq!(SanityLevel::from_str("0"), Some(SanityLevel::White));
+        assert_eq!(SanityLevel::from_str("8"), None);
+
+        let works: Vec<Work> = serde_json::from_value(json!([
+            {"id": 1, "title": "all-ages", "sanity_level": 2},
+            {"id": 2, "title": "mild", "sanity_level": 4},
+            {"id": 3, "title": "suggestive", "sanity_level": 6},
+        ]))
+        .unwrap();
+        let (kept, report) = ContentFilter::new().max_sanity_level(SanityLevel::Gray).filter(works);
+        assert_eq!(ids(&kept), vec![1, 2]);
+        assert_eq!(report.removed_for(&RemovalReason::SanityLevel(SanityLevel::SemiBlack)), 1);
+    }
+
+    #[test]
+    fn test_filter_iter() {
+        let filter = ContentFilter::new().allow_r18g(false);
+        let mut filtered = filter.filter_iter(works());
+        assert_eq!(filtered.next().map(|work| work.id), Some(1));
+        assert_eq!(filtered.next().map(|work| work.id), Some(2));
+        assert_eq!(filtered.next().map(|work| work.id), Some(4));
+        assert_eq!(filtered.report().kept, 3);
+        assert_eq!(filtered.report().removed[0].work_id, 3);
+        assert_eq!(filtered.count(), 3);
+    }
+}
@@ -0,0 +1,459 @@
+//! Crawling the follow graph breadth-first from a few seed users.
+//!
+//! A `FollowGraph` holds the users found so far, who follows whom, and the users still to visit, so it
+//! can be saved between runs and the crawl resumed where it stopped. A `FollowCrawler` fetches each
+//! user's followings with `user_following()`, waiting between requests and backing off when Pixiv
+//! responds with `429 Too Many Requests`.
+//!
+//! ```rust,no_run
+//! # extern crate pixiv;
+//! # extern crate reqwest;
+//! # use std::fs::File;
+//! # use std::time::Duration;
+//! # use pixiv::client::Pixiv;
+//! # use pixiv::follow_graph::{FollowCrawler, FollowGraph};
+//! # use reqwest::Client;
+//! # fn main() {
+//! #   let client = Client::new();
+//! #   let mut pixiv: Pixiv = Pixiv::new(&client);
+//! #   pixiv.login("username", "password");
+//!     // Up to two hops from the seed, and at most 1000 users.
+//!     let mut graph = FollowGraph::load_or_new("graph.json", vec![6996493], 2, 1000)
+//!         .expect("Failed to read graph.");
+//!     FollowCrawler::new(&pixiv)
+//!         .delay(Duration::from_secs(1))
+//!         .crawl(&mut graph, Some("graph.json"))
+//!         .expect("Failed to crawl.");
+//!     graph.write_dot(File::create("graph.dot").unwrap()).unwrap();
+//! # }
+//! ```
+
+use std::collections::btree_map::{self, BTreeMap};
+use std::collections::{BTreeSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+#[cfg(feature = "reqwest-client")]
+use std::thread;
+#[cfg(feature = "reqwest-client")]
+use std::time::Duration;
+
+#[cfg(feature = "reqwest-client")]
+use http::StatusCode;
+#[cfg(feature = "reqwest-client")]
+use serde::de::DeserializeOwned;
+use serde_json;
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+#[cfg(feature = "reqwest-client")]
+use models::PixivResponse;
+use models::User;
+use utils::{self, escape_xml};
+#[cfg(feature = "reqwest-client")]
+use params::UserFollowingParams;
+#[cfg(feature = "reqwest-client")]
+use PixivRequestBuilder;
+
+/// Error returned when loading, saving or crawling a `FollowGraph`.
+#[derive(Debug)]
+pub enum GraphError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    #[cfg(feature = "reqwest-client")]
+    Fetch(FetchError),
+}
+
+impl Error for GraphError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to crawl or store a follow graph."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            GraphError::Io(ref e) => Some(e),
+            GraphError::Json(ref e) => Some(e),
+            #[cfg(feature = "reqwest-client")]
+            GraphError::Fetch(ref e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GraphError::Io(ref e) => write!(f, "An error occurred while trying to access a follow graph. Reason: {}", e),
+            GraphError::Json(ref e) => write!(f, "Follow graph is invalid: {}", e),
+            #[cfg(feature = "reqwest-client")]
+            GraphError::Fetch(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for GraphError {
+    fn from(e: io::Error) -> Self {
+        GraphError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for GraphError {
+    fn from(e: serde_json::Error) -> Self {
+        GraphError::Json(e)
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl From<FetchError> for GraphError {
+    fn from(e: FetchError) -> Self {
+        GraphError::Fetch(e)
+    }
+}
+
+/// A user in a `FollowGraph`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphUser {
+    pub id: usize,
+    /// Empty for seeds until they turn up in someone's followings.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub account: String,
+    /// How many hops the user is from the nearest seed.
+    pub depth: usize,
+}
+
+/// The state of a breadth-first crawl of the follow graph. Saved as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowGraph {
+    max_depth: usize,
+    max_users: usize,
+    users: BTreeMap<usize, GraphUser>,
+    /// `(follower, followed)` pairs.
+    edges: BTreeSet<(usize, usize)>,
+    queue: VecDeque<usize>,
+    visited: BTreeSet<usize>,
+}
+
+impl FollowGraph {
+    /// Starts a crawl from the seed users. The followings of users fewer than `max_depth` hops from a
+    /// seed are fetched, so `1` fetches only the seeds'. At most `max_users` users are added, seeds included.
+    pub fn new<I: IntoIterator<Item = usize>>(seeds: I, max_depth: usize, max_users: usize) -> FollowGraph {
+        let mut graph = FollowGraph {
+            max_depth,
+            max_users,
+            users: BTreeMap::new(),
+            edges: BTreeSet::new(),
+            queue: VecDeque::new(),
+            visited: BTreeSet::new(),
+        };
+        for id in seeds {
+            graph.add_user(GraphUser { id, name: String::new(), account: String::new(), depth: 0 });
+        }
+        graph
+    }
+    /// Reads a graph written by `save()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<FollowGraph, GraphError> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+    /// Reads a graph written by `save()` to resume its crawl, or starts a new one if `path` doesn't exist.
+    pub fn load_or_new<P, I>(path: P, seeds: I, max_depth: usize, max_users: usize) -> Result<FollowGraph, GraphError>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = usize>,
+    {
+        match File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(BufReader::new(file))?),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(FollowGraph::new(seeds, max_depth, max_users)),
+            Err(e) => Err(e.into()),
+        }
+    }
+    /// Writes the graph, including the progress of its crawl, as JSON. The file is replaced only once
+    /// the graph is fully written, so an interrupted save keeps the previous checkpoint.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GraphError> {
+        utils::replace_file(path, |out| Ok(serde_json::to_writer(out, self)?))
+    }
+
+    /// Get the number of users.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+    /// Whether there are no users.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+    /// Iterates over the users, ordered by id.
+    pub fn users(&self) -> btree_map::Values<'_, usize, GraphUser> {
+        self.users.values()
+    }
+    /// Get a user.
+    pub fn user(&self, user_id: usize) -> Option<&GraphUser> {
+        self.users.get(&user_id)
+    }
+    /// Get the `(follower, followed)` pairs, ordered by follower.
+    #[inline]
+    pub fn edges(&self) -> &BTreeSet<(usize, usize)> {
+        &self.edges
+    }
+    /// Get the user whose followings are fetched next, or `None` once the crawl is done.
+    pub fn next_user(&self) -> Option<usize> {
+        self.queue.front().cloned()
+    }
+    /// Whether there are no users left to visit.
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.queue.is_empty()
+    }
+    /// Get the number of users whose followings have been fetched.
+    #[inline]
+    pub fn visited(&self) -> usize {
+        self.visited.len()
+    }
+
+    /// Records the followings of the next user and marks them visited. New users are queued if they're
+    /// within `max_depth`, and dropped along with their edge once `max_users` is reached.
+    ///
+    /// # Panics
+    /// Panics if `user_id` isn't `next_user()`.
+    pub fn record(&mut self, user_id: usize, followings: &[User]) {
+        assert_eq!(self.next_user(), Some(user_id), "Followings must be recorded for the next user.");
+        self.queue.pop_front();
+        self.visited.insert(user_id);
+        let depth = self.users[&user_id].depth + 1;
+        for following in followings {
+            let full = self.users.len() >= self.max_users;
+            match self.users.get_mut(&following.id) {
+                Some(user) => {
+                    if user.name.is_empty() {
+                        user.name = following.name.clone();
+                        user.account = following.account.clone();
+                    }
+                }
+                None if full => continue,
+                None => self.add_user(GraphUser {
+                    id: following.id,
+                    name: following.name.clone(),
+                    account: following.account.clone(),
+                    depth,
+                }),
+            }
+            self.edges.insert((user_id, following.id));
+        }
+    }
+
+    fn add_user(&mut self, user: GraphUser) {
+        if user.depth < self.max_depth && !self.visited.contains(&user.id) {
+            self.queue.push_back(user.id);
+        }
+        self.users.entry(user.id).or_insert(user);
+    }
+
+    /// Writes the edges as tab separated `follower` and `followed` ids, one per line.
+    pub fn write_edge_list<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        for &(follower, followed) in &self.edges {
+            writeln!(writer, "{}\t{}", follower, followed)?;
+        }
+        writer.flush()
+    }
+    /// Writes the graph as GraphML, with each user's name, account and depth as node data.
+    pub fn write_graphml<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(writer, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+        writeln!(writer, r#"  <key id="name" for="node" attr.name="name" attr.type="string"/>"#)?;
+        writeln!(writer, r#"  <key id="account" for="node" attr.name="account" attr.type="string"/>"#)?;
+        writeln!(writer, r#"  <key id="depth" for="node" attr.name="depth" attr.type="int"/>"#)?;
+        writeln!(writer, r#"  <graph id="follows" edgedefault="directed">"#)?;
+        for user in self.users.values() {
+            writeln!(writer, r#"    <node id="{}">"#, user.id)?;
+            writeln!(writer, r#"      <data key="name">{}</data>"#, escape_xml(&user.name))?;
+            writeln!(writer, r#"      <data key="account">{}</data>"#, escape_xml(&user.account))?;
+            writeln!(writer, r#"      <data key="depth">{}</data>"#, user.depth)?;
+            writeln!(writer, "    </node>")?;
+        }
+        for &(follower, followed) in &self.edges {
+            writeln!(writer, r#"    <edge source="{}" target="{}"/>"#, follower, followed)?;
+        }
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")?;
+        writer.flush()
+    }
+    /// Writes the graph in Graphviz's DOT language, labelling users with their name.
+    pub fn write_dot<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "digraph follows {{")?;
+        for user in self.users.values() {
+            let label = if user.name.is_empty() { user.id.to_string() } else { user.name.clone() };
+            writeln!(writer, "  {} [label=\"{}\"];", user.id, escape_dot(&label))?;
+        }
+        for &(follower, followed) in &self.edges {
+            writeln!(writer, "  {} -> {};", follower, followed)?;
+        }
+        writeln!(writer, "}}")?;
+        writer.flush()
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Fetches followings for a `FollowGraph`.
+#[cfg(feature = "reqwest-client")]
+#[derive(Debug)]
+pub struct FollowCrawler<'a> {
+    pixiv: &'a Pixiv,
+    delay: Duration,
+    retries: usize,
+}
+
+#[cfg(feature = "reqwest-client")]
+impl<'a> FollowCrawler<'a> {
+    /// Creates a crawler which waits a second between requests and retries rate limited requests 3 times.
+    pub fn new(pixiv: &'a Pixiv) -> FollowCrawler<'a> {
+        FollowCrawler {
+            pixiv,
+            delay: Duration::from_secs(1),
+            retries: 3,
+        }
+    }
+    /// Sets how long to wait between requests (default: 1 second).
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+    /// Sets how often to retry a request Pixiv rate limited, waiting twice as long each time (default: 3).
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Fetches the followings of the graph's next user and records them. Users which no longer exist are
+    /// recorded as following nobody. Returns the user, or `None` if the crawl is done.
+    pub fn step(&self, graph: &mut FollowGraph) -> Result<Option<usize>, FetchError> {
+        let user_id = match graph.next_user() {
+            Some(user_id) => user_id,
+            None => return Ok(None),
+        };
+        let mut followings = Vec::new();
+        let mut page = 1;
+        loop {
+            let builder = PixivRequestBuilder::user_following(user_id).page(page);
+            let response: PixivResponse<User> = match self.fetch(builder) {
+                Err(FetchError::Status(StatusCode::NOT_FOUND)) => break,
+                response => response?,
+            };
+            followings.extend(response.response);
+            match response.pagination.and_then(|pagination| pagination.next) {
+                Some(next) if next > page => page = next,
+                _ => break,
+            }
+        }
+        graph.record(user_id, &followings);
+        Ok(Some(user_id))
+    }
+
+    /// Crawls until the graph is done, saving it to `checkpoint` after each user so an interrupted crawl
+    /// can be resumed by loading it. Returns how many users were visited.
+    pub fn crawl<P: AsRef<Path>>(&self, graph: &mut FollowGraph, checkpoint: Option<P>) -> Result<usize, GraphError> {
+        let mut visited = 0;
+        while self.step(graph)?.is_some() {
+            visited += 1;
+            if let Some(ref path) = checkpoint {
+                graph.save(path)?;
+            }
+        }
+        Ok(visited)
+    }
+
+    // Waits `delay` before each request, and doubles the wait after each `429` up to `retries` times.
+    fn fetch<T: DeserializeOwned>(&self, builder: PixivRequestBuilder<UserFollowingParams>) -> Result<T, FetchError> {
+        let mut wait = self.delay;
+        let mut retries = 0;
+        loop {
+            thread::sleep(wait);
+            match self.pixiv.fetch(builder.clone()) {
+                Err(FetchError::Status(StatusCode::TOO_MANY_REQUESTS)) if retries < self.retries => {
+                    retries += 1;
+                    wait *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn users(ids: &[usize]) -> Vec<User> {
+        ids.iter()
+            .map(|&id| serde_json::from_value(json!({"id": id, "name": format!("user {}", id)})).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_crawl_order() {
+        let mut graph = FollowGraph::new(vec![1], 2, 5);
+        assert_eq!(graph.next_user(), Some(1));
+        graph.record(1, &users(&[2, 3]));
+        assert_eq!(graph.next_user(), Some(2));
+        graph.record(2, &users(&[1, 4, 5, 6]));
+        // 4 and 5 are two hops out, so they aren't visited, and 6 is past the user limit.
+        graph.record(3, &users(&[4]));
+        assert!(graph.is_done());
+
+        assert_eq!(graph.len(), 5);
+        assert_eq!(graph.user(4).unwrap().depth, 2);
+        assert_eq!(graph.user(1).unwrap().name, "user 1");
+        assert_eq!(
+            graph.edges().iter().cloned().collect::<Vec<_>>(),
+            vec![(1, 2), (1, 3), (2, 1), (2, 4), (2, 5), (3, 4)]
+        );
+        assert!(graph.user(6).is_none());
+    }
+
+    #[test]
+    fn test_resume_and_export() {
+        let path = env::temp_dir().join(format!("pixiv-follow-graph-test-{}.json", ::std::process::id()));
+        let mut graph = FollowGraph::new(vec![1], 2, 10);
+        graph.save(&path).unwrap();
+        graph.record(1, &users(&[2]));
+        graph.save(&path).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let mut graph = FollowGraph::load_or_new(&path, vec![99], 1, 1).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(graph.next_user(), Some(2));
+        assert_eq!(graph.visited(), 1);
+        graph.record(2, &serde_json::from_value::<Vec<User>>(json!([{"id": 3, "name": "<a & \"b\">"}])).unwrap());
+        assert!(graph.is_done());
+
+        let mut edges = Vec::new();
+        graph.write_edge_list(&mut edges).unwrap();
+        assert_eq!(String::from_utf8(edges).unwrap(), "1\t2\n2\t3\n");
+
+        let mut dot = Vec::new();
+        graph.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.contains("  1 [label=\"1\"];\n"));
+        assert!(dot.contains("  3 [label=\"<a & \\\"b\\\">\"];\n"));
+        assert!(dot.contains("  2 -> 3;\n"));
+
+        let mut graphml = Vec::new();
+        graph.write_graphml(&mut graphml).unwrap();
+        let graphml = String::from_utf8(graphml).unwrap();
+        assert!(graphml.contains(r#"<data key="name">&lt;a &amp; &quot;b&quot;&gt;</data>"#));
+        assert!(graphml.contains(r#"<edge source="1" target="2"/>"#));
+    }
+}
@@ -0,0 +1,533 @@
+//! HTTP-level cache of Pixiv responses, used by `Pixiv::execute` once set with `Pixiv::set_http_cache`.
+//!
+//! Only successful `GET` responses are stored, keyed by method and canonicalized URL. A stored response
+//! is served without touching the network while it's fresh according to its `Cache-Control`, `Expires`
+//! and `Age` headers, or the cache's default TTL if Pixiv sent none of them. Once stale, it's revalidated
+//! with `If-None-Match` / `If-Modified-Since` and served again if Pixiv answers `304 Not Modified`.
+//!
+//! `Pixiv::execute` keys responses by the account logged in as well, so a cache can be shared between
+//! accounts without one being served another's responses.
+//!
+//! ```rust,no_run
+//! # extern crate pixiv;
+//! # extern crate reqwest;
+//! # use std::time::Duration;
+//! # use pixiv::client::Pixiv;
+//! # use pixiv::http_cache::HttpCache;
+//! # use pixiv::PixivRequestBuilder;
+//! # use reqwest::Client;
+//! # fn main() {
+//! #   let client = Client::new();
+//! #   let mut pixiv: Pixiv = Pixiv::new(&client);
+//! #   pixiv.login("username", "password");
+//!     let cache = HttpCache::on_disk("http-cache")
+//!         .expect("Failed to create cache directory.")
+//!         .with_default_ttl(Duration::from_secs(60 * 60));
+//!     pixiv.set_http_cache(cache);
+//!     // Only the first of these goes over the network.
+//!     for _ in 0..3 {
+//!         pixiv.execute(PixivRequestBuilder::bad_words().build()).expect("Request failed.");
+//!     }
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::DateTime;
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use http::{Method, StatusCode, Uri};
+use serde_json;
+use serde_urlencoded;
+
+use utils;
+
+/// A response stored in an `HttpCache`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    #[serde(skip)]
+    pub body: Vec<u8>,
+    /// Unix time the response was received or last revalidated.
+    pub stored_at: u64,
+}
+
+impl CachedResponse {
+    /// Creates a response received just now.
+    pub fn new(status: StatusCode, headers: &HeaderMap, body: Vec<u8>) -> CachedResponse {
+        let mut response = CachedResponse {
+            status: status.as_u16(),
+            headers: Vec::new(),
+            body,
+            stored_at: now(),
+        };
+        response.set_headers(headers);
+        response
+    }
+    /// Get the first value of a header, if any.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+    /// Get the headers as a `HeaderMap`, skipping any that are no longer valid.
+    pub fn header_map(&self) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (key, value) in &self.headers {
+            if let (Ok(key), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(value)) {
+                map.append(key, value);
+            }
+        }
+        map
+    }
+    /// Whether the response may be stored at all.
+    pub fn is_storable(&self) -> bool {
+        self.status == StatusCode::OK.as_u16() && !self.directives().iter().any(|(name, _)| name == "no-store")
+    }
+    /// How long the response stays fresh after `stored_at`. `default_ttl` is used if the response doesn't say.
+    pub fn freshness_lifetime(&self, default_ttl: Option<Duration>) -> Duration {
+        let directives = self.directives();
+        if directives.iter().any(|(name, _)| name == "no-cache") {
+            return Duration::from_secs(0);
+        }
+        let max_age = directives
+            .iter()
+            .find(|(name, _)| name == "max-age")
+            .and_then(|(_, value)| value.as_ref().and_then(|value| value.parse::<u64>().ok()));
+        let expires = match (self.header("expires").map(http_date), self.header("date").map(http_date)) {
+            (Some(Some(expires)), Some(Some(date))) => Some(expires.saturating_sub(date)),
+            (Some(Some(expires)), _) => Some(expires.saturating_sub(self.stored_at)),
+            // An invalid `Expires` means already expired.
+            (Some(None), _) => Some(0),
+            (None, _) => None,
+        };
+        let age = self.header("age").and_then(|age| age.trim().parse::<u64>().ok()).unwrap_or(0);
+        match max_age.or(expires) {
+            Some(lifetime) => Duration::from_secs(lifetime.saturating_sub(age)),
+            None => default_ttl.unwrap_or_else(|| Duration::from_secs(0)),
+        }
+    }
+    /// Whether the response can be served without revalidation at unix time `now`.
+    pub fn is_fresh(&self, default_ttl: Option<Duration>, now: u64) -> bool {
+        now.saturating_sub(self.stored_at) < self.freshness_lifetime(default_ttl).as_secs()
+    }
+    /// Whether the response has an `ETag` or `Last-Modified` to revalidate with.
+    pub fn has_validators(&self) -> bool {
+        self.header("etag").is_some() || self.header("last-modified").is_some()
+    }
+    /// Adds `If-None-Match` and `If-Modified-Since` headers for revalidating this response.
+    pub fn add_validators(&self, headers: &mut HeaderMap) {
+        if let Some(etag) = self.header("etag").and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = self.header("last-modified").and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert(header::IF_MODIFIED_SINCE, modified);
+        }
+    }
+    /// Updates the response from the headers of a `304 Not Modified`, marking it as received just now.
+    pub fn revalidated(&mut self, headers: &HeaderMap) {
+        let mut updated = self.header_map();
+        for name in headers.keys() {
+            if !is_hop_by_hop(name) {
+                updated.remove(name);
+                for value in headers.get_all(name) {
+                    updated.append(name.clone(), value.clone());
+                }
+            }
+        }
+        self.set_headers(&updated);
+        self.stored_at = now();
+    }
+
+    fn set_headers(&mut self, headers: &HeaderMap) {
+        self.headers = headers
+            .iter()
+            .filter(|&(name, _)| !is_hop_by_hop(name))
+            .filter_map(|(name, value)| value.to_str().ok().map(|value| (name.as_str().to_owned(), value.to_owned())))
+            .collect();
+    }
+
+    fn directives(&self) -> Vec<(String, Option<String>)> {
+        self.headers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case("cache-control"))
+            .flat_map(|(_, value)| cache_directives(value))
+            .collect()
+    }
+}
+
+/// Storage backing an `HttpCache`.
+pub trait CacheStore: fmt::Debug + Send + Sync {
+    /// Get the response stored under `key`, if any.
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+    /// Store a response under `key`, replacing any previous one.
+    fn put(&self, key: &str, response: &CachedResponse);
+    /// Remove the response stored under `key`, if any.
+    fn remove(&self, key: &str);
+}
+
+/// Keeps responses in memory for the lifetime of the cache.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl MemoryStore {
+    #[inline]
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.entries.lock().ok()?.get(key).cloned()
+    }
+
+    fn put(&self, key: &str, response: &CachedResponse) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key.to_owned(), response.clone());
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(key);
+        }
+    }
+}
+
+/// Keeps responses in a directory, as a `.json` file of status and headers and a `.body` file per key.
+/// Failing to read or write an entry is treated as a cache miss.
+#[derive(Debug, Clone)]
+pub struct DiskStore {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    key: String,
+    response: CachedResponse,
+    // FNV-1a of the body, to tell whether the `.body` file belongs to this entry.
+    body_hash: u64,
+}
+
+impl DiskStore {
+    /// Uses `dir` for the cache, creating it if needed.
+    pub fn new<P: AsRef<Path>>(dir: P) -> io::Result<DiskStore> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(DiskStore { dir: dir.as_ref().to_owned() })
+    }
+    /// Get the cache directory.
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn paths(&self, key: &str) -> (PathBuf, PathBuf) {
+        let name = format!("{:016x}", utils::fnv1a(key.as_bytes()));
+        (self.dir.join(format!("{}.json", name)), self.dir.join(format!("{}.body", name)))
+    }
+}
+
+impl CacheStore for DiskStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let (meta, body) = self.paths(key);
+        let entry: DiskEntry = serde_json::from_slice(&fs::read(meta).ok()?).ok()?;
+        // Different keys can hash to the same file.
+        if entry.key != key {
+            return None;
+        }
+        let mut response = entry.response;
+        response.body = fs::read(body).ok()?;
+        if utils::fnv1a(&response.body) != entry.body_hash {
+            return None;
+        }
+        Some(response)
+    }
+
+    fn put(&self, key: &str, response: &CachedResponse) {
+        let (meta, body) = self.paths(key);
+        let entry = DiskEntry {
+            key: key.to_owned(),
+            response: response.clone(),
+            body_hash: utils::fnv1a(&response.body),
+        };
+        // Each file is replaced whole, the metadata last, and `get` checks the body against its hash.
+        let written = utils::replace_file(&body, |out| out.write_all(&response.body))
+            .and_then(|_| utils::replace_file(&meta, |out| serde_json::to_writer(out, &entry).map_err(io::Error::from)));
+        if written.is_err() {
+            self.remove(key);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let (meta, body) = self.paths(key);
+        let _ = fs::remove_file(meta);
+        let _ = fs::remove_file(body);
+    }
+}
+
+/// Result of looking a request up in an `HttpCache`.
+#[derive(Debug, Clone)]
+pub enum Lookup {
+    /// The stored response can be used as is.
+    Fresh(CachedResponse),
+    /// The stored response has to be revalidated first.
+    Stale(CachedResponse),
+    /// Nothing usable is stored.
+    Miss,
+}
+
+/// HTTP response cache consulted by `Pixiv::execute`.
+#[derive(Debug)]
+pub struct HttpCache {
+    store: Box<dyn CacheStore>,
+    default_ttl: Option<Duration>,
+}
+
+impl HttpCache {
+    /// Creates a cache backed by the given store.
+    pub fn new<S: CacheStore + 'static>(store: S) -> HttpCache {
+        HttpCache {
+            store: Box::new(store),
+            default_ttl: None,
+        }
+    }
+    /// Creates a cache kept in memory.
+    pub fn in_memory() -> HttpCache {
+        HttpCache::new(MemoryStore::new())
+    }
+    /// Creates a cache kept in `dir`.
+    pub fn on_disk<P: AsRef<Path>>(dir: P) -> io::Result<HttpCache> {
+        Ok(HttpCache::new(DiskStore::new(dir)?))
+    }
+    /// Sets how long responses without any freshness information stay fresh.
+    /// Without it, such responses are always revalidated.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> HttpCache {
+        self.default_ttl = Some(ttl);
+        self
+    }
+    /// Get the default TTL.
+    #[inline]
+    pub fn default_ttl(&self) -> Option<Duration> {
+        self.default_ttl
+    }
+    /// Get the backing store.
+    #[inline]
+    pub fn store(&self) -> &dyn CacheStore {
+        &*self.store
+    }
+
+    /// Cache key of a request: its method and its URL with a lowercase scheme and host and sorted query params.
+    pub fn key(method: &Method, url: &Uri) -> String {
+        let mut key = format!("{} ", method);
+        if let Some(scheme) = url.scheme_part() {
+            key.push_str(&scheme.as_str().to_ascii_lowercase());
+            key.push_str("://");
+        }
+        if let Some(authority) = url.authority_part() {
+            key.push_str(&authority.as_str().to_ascii_lowercase());
+        }
+        key.push_str(url.path());
+        if let Some(query) = url.query() {
+            let mut params: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap_or_default();
+            params.sort();
+            if let Ok(query) = serde_urlencoded::to_string(&params) {
+                if !query.is_empty() {
+                    key.push('?');
+                    key.push_str(&query);
+                }
+            }
+        }
+        key
+    }
+    /// Cache key of a request made as an account, e.g. a user id: `key()` prefixed with the account.
+    pub fn account_key(account: &str, method: &Method, url: &Uri) -> String {
+        format!("{} {}", account, HttpCache::key(method, url))
+    }
+    /// Looks up the response stored under `key`.
+    pub fn lookup(&self, key: &str) -> Lookup {
+        match self.store.get(key) {
+            Some(response) => {
+                if response.is_fresh(self.default_ttl, now()) {
+                    Lookup::Fresh(response)
+                } else if response.has_validators() {
+                    Lookup::Stale(response)
+                } else {
+                    Lookup::Miss
+                }
+            }
+            None => Lookup::Miss,
+        }
+    }
+    /// Stores a response under `key` if it's storable, otherwise drops anything stored there.
+    pub fn put(&self, key: &str, response: &CachedResponse) {
+        if response.is_storable() {
+            self.store.put(key, response);
+        } else {
+            self.store.remove(key);
+        }
+    }
+    /// Drops the response stored under `key`.
+    pub fn remove(&self, key: &str) {
+        self.store.remove(key);
+    }
+}
+
+/// Whether request headers ask to skip the cache entirely (`no-store`) or to revalidate (`no-cache`).
+#[cfg(feature = "reqwest-client")]
+pub(crate) fn request_directives(headers: &HeaderMap) -> (bool, bool) {
+    let directives: Vec<_> = headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(cache_directives)
+        .collect();
+    let has = |directive: &str| directives.iter().any(|(name, _)| name == directive);
+    (has("no-store"), has("no-cache"))
+}
+
+fn cache_directives(value: &str) -> Vec<(String, Option<String>)> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| {
+            let mut parts = directive.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+            let value = parts.next().map(|value| value.trim().trim_matches('"').to_owned());
+            (name, value)
+        })
+        .collect()
+}
+
+fn is_hop_by_hop(name: &HeaderName) -> bool {
+    // The body is stored decoded and whole, so its framing headers no longer apply.
+    *name == header::CONNECTION
+        || *name == header::TRANSFER_ENCODING
+        || *name == header::CONTENT_ENCODING
+        || *name == header::CONTENT_LENGTH
+        || *name == header::SET_COOKIE
+}
+
+fn http_date(value: &str) -> Option<u64> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|date| date.timestamp().max(0) as u64)
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn response(headers: &[(&str, &str)]) -> CachedResponse {
+        CachedResponse {
+            status: 200,
+            headers: headers.iter().map(|&(k, v)| (k.to_owned(), v.to_owned())).collect(),
+            body: b"{}".to_vec(),
+            stored_at: 1000,
+        }
+    }
+
+    #[test]
+    fn test_key() {
+        let a: Uri = "https://Public-API.secure.pixiv.net/v1/ranking/all?page=2&mode=daily".parse().unwrap();
+        let b: Uri = "HTTPS://public-api.secure.pixiv.net/v1/ranking/all?mode=daily&page=2".parse().unwrap();
+        let c: Uri = "https://public-api.secure.pixiv.net/v1/ranking/all?mode=daily&page=3".parse().unwrap();
+        assert_eq!(HttpCache::key(&Method::GET, &a), HttpCache::key(&Method::GET, &b));
+        assert_ne!(HttpCache::key(&Method::GET, &a), HttpCache::key(&Method::GET, &c));
+        assert_ne!(HttpCache::key(&Method::GET, &a), HttpCache::key(&Method::POST, &a));
+        assert_eq!(
+            HttpCache::key(&Method::GET, &b),
+            "GET https://public-api.secure.pixiv.net/v1/ranking/all?mode=daily&page=2"
+        );
+        assert_ne!(HttpCache::account_key("1", &Method::GET, &a), HttpCache::account_key("2", &Method::GET, &a));
+    }
+
+    #[test]
+    fn test_freshness() {
+        let ttl = Some(Duration::from_secs(30));
+        let max_age = response(&[("Cache-Control", "public, max-age=60"), ("Age", "10")]);
+        assert!(max_age.is_fresh(None, 1049));
+        assert!(!max_age.is_fresh(None, 1050));
+
+        let expires = response(&[
+            ("Date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+            ("Expires", "Sun, 06 Nov 1994 08:50:37 GMT"),
+        ]);
+        assert_eq!(expires.freshness_lifetime(ttl), Duration::from_secs(60));
+
+        assert!(!response(&[("Cache-Control", "no-cache, max-age=60")]).is_fresh(ttl, 1000));
+        assert!(!response(&[("Expires", "0")]).is_fresh(ttl, 1000));
+        assert!(response(&[]).is_fresh(ttl, 1029));
+        assert!(!response(&[]).is_fresh(None, 1000));
+
+        assert!(response(&[]).is_storable());
+        assert!(!response(&[("cache-control", "No-Store")]).is_storable());
+    }
+
+    #[test]
+    fn test_revalidation() {
+        let mut cached = response(&[("ETag", "\"abc\""), ("Cache-Control", "max-age=0"), ("X-Other", "1")]);
+        assert!(cached.has_validators());
+
+        let mut headers = HeaderMap::new();
+        cached.add_validators(&mut headers);
+        assert_eq!(headers[header::IF_NONE_MATCH], "\"abc\"");
+        assert!(!headers.contains_key(header::IF_MODIFIED_SINCE));
+
+        let mut not_modified = HeaderMap::new();
+        not_modified.insert(header::CACHE_CONTROL, HeaderValue::from_static("max-age=60"));
+        not_modified.insert(header::CONTENT_LENGTH, HeaderValue::from_static("0"));
+        cached.revalidated(&not_modified);
+        assert_eq!(cached.header("cache-control"), Some("max-age=60"));
+        assert_eq!(cached.header("x-other"), Some("1"));
+        assert_eq!(cached.header("content-length"), None);
+        assert_eq!(cached.body, b"{}");
+
+        let cache = HttpCache::in_memory();
+        cache.put("key", &cached);
+        match cache.lookup("key") {
+            Lookup::Fresh(response) => assert_eq!(response.body, b"{}"),
+            other => panic!("expected a fresh response, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_disk_store() {
+        let dir = env::temp_dir().join(format!("pixiv-http-cache-test-{}", ::std::process::id()));
+        let store = DiskStore::new(&dir).unwrap();
+        let cached = response(&[("ETag", "\"abc\"")]);
+
+        store.put("GET https://example.com/", &cached);
+        let read = store.get("GET https://example.com/").unwrap();
+        assert_eq!(read.body, cached.body);
+        assert_eq!(read.headers, cached.headers);
+        assert_eq!(read.stored_at, cached.stored_at);
+        assert!(store.get("GET https://example.com/other").is_none());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+
+        // a body which doesn't belong to the metadata, as after a crash between the two writes
+        let (_, body) = store.paths("GET https://example.com/");
+        fs::write(body, b"[]").unwrap();
+        assert!(store.get("GET https://example.com/").is_none());
+
+        store.remove("GET https://example.com/");
+        assert!(store.get("GET https://example.com/").is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
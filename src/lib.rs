@@ -74,7 +74,7 @@
 //! # extern crate reqwest;
 //! # extern crate serde_json;
 //! # use pixiv::client::Pixiv;
-//! # use pixiv::PixivRequestBuilder;
+//! # use pixiv::{ImageSize, PixivRequestBuilder};
 //! # use reqwest::Client;
 //! # use serde_json::Value;
 //! # fn main() {
@@ -82,7 +82,7 @@
 //! #   let mut pixiv: Pixiv = Pixiv::new(&client);
 //! #   pixiv.login("username", "password");
 //!     let request = PixivRequestBuilder::following_works()
-//!        .image_sizes(&["large"])
+//!        .image_sizes(&[ImageSize::Large])
 //!        .include_sanity_level(false)
 //!        .build();
 //!     let following_works: Value = pixiv
@@ -93,30 +93,57 @@
 //! # }
 //! ```
 //!
+//! `build()` panics if any of the params are invalid. If the params come from user input, use `try_date()` and `try_build()` instead, which return a `RequestError` describing the problem:
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # use pixiv::{PixivRequestBuilder, RankingType};
+//! # fn main() {
+//!     let request = PixivRequestBuilder::ranking(RankingType::Illust)
+//!         .try_date("2018-02-30")
+//!         .and_then(|builder| builder.try_build());
+//!     assert!(request.is_err());
+//! # }
+//! ```
+//!
 //! Since `work` is of type `serde_json::Value`, it's up to you to figure out how you want to parse this response for your program.
+//! Alternatively, typed models for common responses are provided in the `models` module.
 //!
 //! You may want to refer [here](https://www.snip2code.com/Snippet/798193/Unofficial-API-specification-extracted-f) for what a response from Pixiv may look like.
 //!
 //! ## Future Support (Maybe)
 //!
 //! * More examples!
-//! * More versatile support for handling and parsing responses (instead of just the raw response)
 //! * More API support (although pixiv doesn't document their public API anywhere to my knowledge...)
 
-extern crate chrono;
+pub extern crate chrono;
 #[cfg(feature = "reqwest-client")]
 pub extern crate reqwest;
 pub extern crate http;
 extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 extern crate serde_json;
 extern crate serde_urlencoded;
 extern crate bytes;
+#[cfg(feature = "bad-words")]
+extern crate aho_corasick;
+extern crate unicode_normalization;
+#[cfg(feature = "sqlite-cache")]
+extern crate rusqlite;
+#[cfg(feature = "export")]
+extern crate csv;
+#[cfg(feature = "parquet")]
+extern crate parquet;
+#[cfg(feature = "epub")]
+extern crate zip;
+#[cfg(feature = "feed-server")]
+extern crate tiny_http;
 
 #[cfg(test)]
 extern crate kankyo;
 
 use std::borrow::{Borrow, Cow};
-use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::io::Write;
@@ -126,10 +153,31 @@ use chrono::naive::NaiveDate;
 pub use http::{HttpTryFrom, header, HeaderMap, Method, uri::Uri};
 
 mod utils;
+pub mod models;
+pub mod params;
+pub mod http_cache;
+pub mod ranking;
+pub mod search;
+pub mod filter;
+pub mod text;
+#[cfg(feature = "bad-words")]
+pub mod bad_words;
+pub mod tags;
+pub mod novel;
+pub mod book;
+pub mod series;
+pub mod bookmark_sync;
+pub mod follow_graph;
+pub mod watcher;
+pub mod feed;
 #[cfg(feature = "reqwest-client")]
 pub mod client;
+#[cfg(feature = "sqlite-cache")]
+pub mod cache;
+#[cfg(feature = "export")]
+pub mod export;
 
-use utils::comma_delimited;
+use params::*;
 
 /// Pixiv request. You can create this using `PixivRequestBuilder::build`. This is for if you wish to inspect the request before sending.
 #[derive(Debug, Clone)]
@@ -137,13 +185,17 @@ pub struct PixivRequest {
     pub method: Method,
     pub url: Uri,
     pub headers: HeaderMap,
+    pub body: Option<String>,
 }
 
 /// Pixiv request builder. You can create this using any of the provided methods in `Pixiv`, or through `PixivRequestBuilder::new`.
+///
+/// Each endpoint has its own params type from the `params` module, so only the params it accepts can be set.
 #[derive(Debug, Clone)]
-pub struct PixivRequestBuilder<'a> {
+pub struct PixivRequestBuilder<P> {
     request: PixivRequest,
-    params: HashMap<&'a str, Cow<'a, str>>,
+    params: P,
+    error: Option<RequestError>,
 }
 /// Error returned on failure to authorize with pixiv.
 #[derive(Debug)]
@@ -167,8 +219,155 @@ impl fmt::Display for AuthError {
     }
 }
 
+/// Error returned when a `PixivRequestBuilder` is given invalid parameters.
+#[derive(Debug, Clone)]
+pub struct RequestError {
+    reason: String,
+}
+
+impl RequestError {
+    fn new<S: Into<String>>(reason: S) -> RequestError {
+        RequestError {
+            reason: reason.into(),
+        }
+    }
+    /// Get the reason the request was rejected.
+    #[inline]
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Error for RequestError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to build a request."
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "An error occurred while trying to build a request. Reason: {:?}",
+            self.reason
+        )
+    }
+}
+
+/// Values accepted by the `date` param, i.e. a `NaiveDate` or a string in the form of `%Y-%m-%d`.
+pub trait DateParam {
+    /// Converts the value into a `NaiveDate`, returning an error if it isn't a valid date.
+    fn to_naive_date(&self) -> Result<NaiveDate, RequestError>;
+}
+
+impl DateParam for NaiveDate {
+    #[inline]
+    fn to_naive_date(&self) -> Result<NaiveDate, RequestError> {
+        Ok(*self)
+    }
+}
+
+impl DateParam for str {
+    fn to_naive_date(&self) -> Result<NaiveDate, RequestError> {
+        NaiveDate::parse_from_str(self, "%Y-%m-%d").map_err(|e| {
+            RequestError::new(format!(
+                "Invalid date {:?}, expected the form of `%Y-%m-%d`: {}",
+                self, e
+            ))
+        })
+    }
+}
+
+impl DateParam for &str {
+    #[inline]
+    fn to_naive_date(&self) -> Result<NaiveDate, RequestError> {
+        (**self).to_naive_date()
+    }
+}
+
+impl DateParam for String {
+    #[inline]
+    fn to_naive_date(&self) -> Result<NaiveDate, RequestError> {
+        self.as_str().to_naive_date()
+    }
+}
+
+impl<'b> DateParam for Cow<'b, str> {
+    #[inline]
+    fn to_naive_date(&self) -> Result<NaiveDate, RequestError> {
+        (**self).to_naive_date()
+    }
+}
+
+/// Enum to set image sizes param.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ImageSize {
+    Px128x128,
+    Px480mw,
+    Small,
+    Medium,
+    Large,
+}
+
+impl ImageSize {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            ImageSize::Px128x128 => "px_128x128",
+            ImageSize::Px480mw => "px_480mw",
+            ImageSize::Small => "small",
+            ImageSize::Medium => "medium",
+            ImageSize::Large => "large",
+        }
+    }
+    fn from_str(value: &str) -> Option<ImageSize> {
+        match value {
+            "px_128x128" => Some(ImageSize::Px128x128),
+            "px_480mw" => Some(ImageSize::Px480mw),
+            "small" => Some(ImageSize::Small),
+            "medium" => Some(ImageSize::Medium),
+            "large" => Some(ImageSize::Large),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ImageSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Enum to set profile image sizes param.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProfileImageSize {
+    Px170x170,
+    Px50x50,
+}
+
+impl ProfileImageSize {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            ProfileImageSize::Px170x170 => "px_170x170",
+            ProfileImageSize::Px50x50 => "px_50x50",
+        }
+    }
+    fn from_str(value: &str) -> Option<ProfileImageSize> {
+        match value {
+            "px_170x170" => Some(ProfileImageSize::Px170x170),
+            "px_50x50" => Some(ProfileImageSize::Px50x50),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProfileImageSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Enum to set publicity param.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Publicity {
     Public,
     Private,
@@ -181,10 +380,17 @@ impl Publicity {
             Publicity::Private => "private",
         }
     }
+    fn from_str(value: &str) -> Option<Publicity> {
+        match value {
+            "public" => Some(Publicity::Public),
+            "private" => Some(Publicity::Private),
+            _ => None,
+        }
+    }
 }
 
 /// Enum to set ranking type param.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RankingType {
     All,
     Illust,
@@ -201,10 +407,27 @@ impl RankingType {
             RankingType::Ugoira => "ugoira",
         }
     }
+    /// Parses the name `Display` writes, e.g. `illust`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Option<RankingType> {
+        match value {
+            "all" => Some(RankingType::All),
+            "illust" => Some(RankingType::Illust),
+            "manga" => Some(RankingType::Manga),
+            "ugoira" => Some(RankingType::Ugoira),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RankingType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Enum to set ranking mode param.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RankingMode {
     Daily,
     Weekly,
@@ -237,6 +460,31 @@ impl RankingMode {
             RankingMode::R18G => "r18g",
         }
     }
+    /// Parses the name `Display` writes, e.g. `daily_r18`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(value: &str) -> Option<RankingMode> {
+        match value {
+            "daily" => Some(RankingMode::Daily),
+            "weekly" => Some(RankingMode::Weekly),
+            "monthly" => Some(RankingMode::Monthly),
+            "rookie" => Some(RankingMode::Rookie),
+            "original" => Some(RankingMode::Original),
+            "male" => Some(RankingMode::Male),
+            "female" => Some(RankingMode::Female),
+            "daily_r18" => Some(RankingMode::DailyR18),
+            "weekly_r18" => Some(RankingMode::WeeklyR18),
+            "male_r18" => Some(RankingMode::MaleR18),
+            "female_r18" => Some(RankingMode::FemaleR18),
+            "r18g" => Some(RankingMode::R18G),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RankingMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Enum to set search period param.
@@ -279,6 +527,56 @@ impl SearchMode {
     }
 }
 
+/// Enum to set search sort param.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchSort {
+    Date,
+    Popular,
+    PopularMale,
+    PopularFemale,
+}
+
+impl SearchSort {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            SearchSort::Date => "date",
+            SearchSort::Popular => "popular",
+            SearchSort::PopularMale => "popular_male",
+            SearchSort::PopularFemale => "popular_female",
+        }
+    }
+}
+
+/// Enum to set search types param. Also used as the type of a work in `models`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum WorkType {
+    #[serde(rename = "illustration", alias = "illust")]
+    Illustration,
+    #[serde(rename = "manga")]
+    Manga,
+    #[serde(rename = "ugoira")]
+    Ugoira,
+    #[serde(rename = "novel")]
+    Novel,
+}
+
+impl WorkType {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            WorkType::Illustration => "illustration",
+            WorkType::Manga => "manga",
+            WorkType::Ugoira => "ugoira",
+            WorkType::Novel => "novel",
+        }
+    }
+}
+
+impl fmt::Display for WorkType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Enum to set search order param.
 #[derive(Debug, Clone, Copy)]
 pub enum SearchOrder {
@@ -295,6 +593,82 @@ impl SearchOrder {
     }
 }
 
+/// Enum to set the ranking mode param of `novel_ranking()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NovelRankingMode {
+    Day,
+    Week,
+    DayMale,
+    DayFemale,
+    WeekRookie,
+    DayR18,
+    DayMaleR18,
+    DayFemaleR18,
+    WeekR18,
+    WeekR18G,
+}
+
+impl NovelRankingMode {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            NovelRankingMode::Day => "day",
+            NovelRankingMode::Week => "week",
+            NovelRankingMode::DayMale => "day_male",
+            NovelRankingMode::DayFemale => "day_female",
+            NovelRankingMode::WeekRookie => "week_rookie",
+            NovelRankingMode::DayR18 => "day_r18",
+            NovelRankingMode::DayMaleR18 => "day_male_r18",
+            NovelRankingMode::DayFemaleR18 => "day_female_r18",
+            NovelRankingMode::WeekR18 => "week_r18",
+            NovelRankingMode::WeekR18G => "week_r18g",
+        }
+    }
+}
+
+impl fmt::Display for NovelRankingMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Enum to set the search target param of `search_novels()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchTarget {
+    PartialMatchForTags,
+    ExactMatchForTags,
+    Text,
+    Keyword,
+}
+
+impl SearchTarget {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            SearchTarget::PartialMatchForTags => "partial_match_for_tags",
+            SearchTarget::ExactMatchForTags => "exact_match_for_tags",
+            SearchTarget::Text => "text",
+            SearchTarget::Keyword => "keyword",
+        }
+    }
+}
+
+/// Enum to set the sort param of `search_novels()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NovelSearchSort {
+    DateDescending,
+    DateAscending,
+    PopularDescending,
+}
+
+impl NovelSearchSort {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            NovelSearchSort::DateDescending => "date_desc",
+            NovelSearchSort::DateAscending => "date_asc",
+            NovelSearchSort::PopularDescending => "popular_desc",
+        }
+    }
+}
+
 impl PixivRequest {
     /// Create a new `PixivRequest`.
     /// A `PixivRequest` is returned when calling `build()` on `PixivRequestBuilder`, so it is recommended you use that instead.
@@ -304,6 +678,7 @@ impl PixivRequest {
             method,
             url,
             headers,
+            body: None,
         }
     }
     /// Get the method.
@@ -337,34 +712,69 @@ impl PixivRequest {
         &mut self.headers
     }
 
+    /// Get the body.
+    #[inline]
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+    /// Get a mutable reference to the body.
+    #[inline]
+    pub fn body_mut(&mut self) -> &mut Option<String> {
+        &mut self.body
+    }
+
+    ///Sets body as a form using `serde_urlencoded`
+    fn set_form_params<Q: serde::Serialize>(mut self, params: &Q) -> Result<Self, RequestError> {
+        let body = serde_urlencoded::to_string(params)
+            .map_err(|e| RequestError::new(format!("Unable to url-encode params: {}", e)))?;
+        self.headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        self.body = Some(body);
+        Ok(self)
+    }
+
     ///Sets query using `serde_urlencoded`
-    fn set_query_params<Q: serde::Serialize>(mut self, params: &Q) -> Self {
+    fn set_query_params<Q: serde::Serialize>(mut self, params: &Q) -> Result<Self, RequestError> {
         let mut uri_parts = self.url.into_parts();
         let path = uri_parts.path_and_query;
 
         let mut buffer = utils::BytesWriter::with_smol_capacity();
-        let query = serde_urlencoded::to_string(params).expect("To url-encode");
+        let query = serde_urlencoded::to_string(params)
+            .map_err(|e| RequestError::new(format!("Unable to url-encode params: {}", e)))?;
 
         let _ = match path {
             Some(path) => write!(buffer, "{}?{}", path.path(), query),
             None => write!(buffer, "?{}", query),
         };
 
-        uri_parts.path_and_query = Some(http::uri::PathAndQuery::from_shared(buffer.into_inner().freeze()).expect("To create path and query"));
+        let path_and_query = http::uri::PathAndQuery::from_shared(buffer.into_inner().freeze())
+            .map_err(|e| RequestError::new(format!("Unable to create path and query: {}", e)))?;
+        uri_parts.path_and_query = Some(path_and_query);
 
-        self.url = match http::Uri::from_parts(uri_parts) {
-            Ok(uri) => uri,
-            Err(error) => panic!("Unable to set query for URI: {}", error)
-        };
+        self.url = http::Uri::from_parts(uri_parts)
+            .map_err(|e| RequestError::new(format!("Unable to set query for URI: {}", e)))?;
 
-        self
+        Ok(self)
     }
 }
 
-impl<'a> PixivRequestBuilder<'a> {
+// Default `image_sizes` and `profile_image_sizes` params.
+const DETAIL_IMAGE_SIZES: [ImageSize; 5] = [
+    ImageSize::Px128x128,
+    ImageSize::Small,
+    ImageSize::Medium,
+    ImageSize::Large,
+    ImageSize::Px480mw,
+];
+const LIST_IMAGE_SIZES: [ImageSize; 3] = [ImageSize::Px128x128, ImageSize::Px480mw, ImageSize::Large];
+const PROFILE_IMAGE_SIZES: [ProfileImageSize; 2] = [ProfileImageSize::Px170x170, ProfileImageSize::Px50x50];
+
+impl<P: Params> PixivRequestBuilder<P> {
     /// Create a new `PixivRequestBuilder`.
     /// Functions in `Pixiv` expedite a lot of this for you, so using this directly isn't recommended unless you know what you want.
-    pub fn new(method: Method, url: Uri, params: HashMap<&'a str, Cow<'a, str>>) -> Self {
+    pub fn new(method: Method, url: Uri, params: P) -> Self {
         // set headers
         let mut headers = HeaderMap::new();
         headers.insert(header::REFERER, header::HeaderValue::from_static("http://spapi.pixiv.net/"));
@@ -372,127 +782,187 @@ impl<'a> PixivRequestBuilder<'a> {
         PixivRequestBuilder {
             request: PixivRequest::new(method, url, headers),
             params,
+            error: None,
         }
     }
-    /// Sets the `page` param.
-    #[inline]
-    pub fn page(self, value: usize) -> Self {
-        self.raw_param("page", value.to_string())
+    // Parses `url`, deferring any error until the request is built.
+    fn parse_new(method: Method, url: &str, params: P) -> Self {
+        match Uri::try_from(url) {
+            Ok(uri) => PixivRequestBuilder::new(method, uri, params),
+            Err(e) => {
+                let mut builder = PixivRequestBuilder::new(method, Uri::default(), params);
+                builder.error = Some(RequestError::new(format!("Invalid url {:?}: {}", url, e)));
+                builder
+            }
+        }
     }
-    /// Sets the `per_page` param.
+    /// Get the params.
     #[inline]
-    pub fn per_page(self, value: usize) -> Self {
-        self.raw_param("value", value.to_string())
+    pub fn params(&self) -> &P {
+        &self.params
     }
-    /// Sets the `max_id` param.
+    /// Get a mutable reference to the params.
     #[inline]
-    pub fn max_id(self, value: usize) -> Self {
-        self.raw_param("max_id", value.to_string())
+    pub fn params_mut(&mut self) -> &mut P {
+        &mut self.params
     }
-    /// Sets the `image_sizes` param. Available types: `px_128x128`, `small`, `medium`, `large`, `px_480mw`
+    /// Returns a `PixivRequest` which can be inspected and/or executed with `Pixiv::execute()`.
+    /// # Panics
+    /// Panics if any of the params are invalid. Use `try_build` for user-supplied input.
     #[inline]
-    pub fn image_sizes(self, values: &[&str]) -> Self {
-        self.raw_param("image_sizes", comma_delimited::<&str, _, _>(values))
+    pub fn build(self) -> PixivRequest {
+        self.try_build().expect("Invalid request params given.")
     }
-    /// Sets the `profile_image_sizes` param. Available types: `px_170x170,px_50x50`
-    #[inline]
-    pub fn profile_image_sizes(self, values: &[&str]) -> Self {
-        self.raw_param("profile_image_sizes", comma_delimited::<&str, _, _>(values))
+    /// Validates all params and returns a `PixivRequest`, or an error describing the first invalid param.
+    ///
+    /// Params are sent in the query string for `GET` requests, and as a form body otherwise.
+    pub fn try_build(self) -> Result<PixivRequest, RequestError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.params.validate()?;
+        if self.request.method == Method::GET {
+            self.request.set_query_params(&self.params)
+        } else {
+            self.request.set_form_params(&self.params)
+        }
     }
-    /// Sets the `publicity` param. Must be a value of enum `Publicity`.
+}
+
+impl<P: HasPage> PixivRequestBuilder<P> {
+    /// Sets the `page` param.
     #[inline]
-    pub fn publicity(self, value: Publicity) -> Self {
-        self.raw_param("publicity", value.as_str())
+    pub fn page(mut self, value: usize) -> Self {
+        *self.params.page_mut() = value;
+        self
     }
-    /// Sets the `show_r18` param. `true` means R-18 works will be included.
+}
+
+impl<P: HasPerPage> PixivRequestBuilder<P> {
+    /// Sets the `per_page` param.
     #[inline]
-    pub fn show_r18(self, value: bool) -> Self {
-        if value {
-            self.raw_param("show_r18", "1")
-        } else {
-            self.raw_param("show_r18", "0")
-        }
+    pub fn per_page(mut self, value: usize) -> Self {
+        *self.params.per_page_mut() = value;
+        self
     }
-    /// Sets the `include_stats` param.
+}
+
+impl<P: HasMaxId> PixivRequestBuilder<P> {
+    /// Sets the `max_id` param.
     #[inline]
-    pub fn include_stats(self, value: bool) -> Self {
-        if value {
-            self.raw_param("include_stats", "true")
-        } else {
-            self.raw_param("include_stats", "false")
-        }
+    pub fn max_id(mut self, value: usize) -> Self {
+        *self.params.max_id_mut() = Some(value);
+        self
     }
-    /// Sets the `include_sanity_level` param.
+}
+
+impl<P: HasOffset> PixivRequestBuilder<P> {
+    /// Sets the `offset` param, i.e. how many results to skip. App API endpoints page with this instead of `page`.
     #[inline]
-    pub fn include_sanity_level(self, value: bool) -> Self {
-        if value {
-            self.raw_param("include_sanity_level", "true")
-        } else {
-            self.raw_param("include_sanity_level", "false")
-        }
+    pub fn offset(mut self, value: usize) -> Self {
+        *self.params.offset_mut() = value;
+        self
     }
-    /// Sets the ranking mode in the case of a `ranking()` call. Must be a value of enum `RankingMode`.
+}
+
+impl<P: HasImageSizes> PixivRequestBuilder<P> {
+    /// Sets the `image_sizes` param. Must be values of enum `ImageSize`.
     #[inline]
-    pub fn ranking_mode(self, value: RankingMode) -> Self {
-        self.raw_param("mode", value.as_str())
+    pub fn image_sizes(mut self, values: &[ImageSize]) -> Self {
+        *self.params.image_sizes_mut() = values.to_vec();
+        self
     }
-    /// Sets the `date` param. Must be a valid date in the form of `%Y-%m-%d`, e.g. `2018-2-22`.
-    pub fn date<V>(self, value: V) -> Self
-    where
-        Cow<'a, str>: From<V>,
-    {
-        let value: Cow<_> = value.into();
-        // just to validate the date format
-        NaiveDate::parse_from_str(&*value, "%Y-%m-%d").expect("Invalid date or format given.");
-        self.raw_param::<Cow<_>>("date", value)
+}
+
+impl<P: HasProfileImageSizes> PixivRequestBuilder<P> {
+    /// Sets the `profile_image_sizes` param. Must be values of enum `ProfileImageSize`.
+    #[inline]
+    pub fn profile_image_sizes(mut self, values: &[ProfileImageSize]) -> Self {
+        *self.params.profile_image_sizes_mut() = values.to_vec();
+        self
     }
-    /// Sets the `period` param in the case of a `search_works()` call. Must be a value of enum `SearchPeriod`.
+}
+
+impl<P: HasPublicity> PixivRequestBuilder<P> {
+    /// Sets the `publicity` param. Must be a value of enum `Publicity`.
     #[inline]
-    pub fn search_period(self, value: SearchPeriod) -> Self {
-        self.raw_param("period", value.as_str())
+    pub fn publicity(mut self, value: Publicity) -> Self {
+        *self.params.publicity_mut() = value;
+        self
     }
-    /// Sets the `mode` param in the case of a `search_works()` call. Must be a value of enum `SearchMode`.
+}
+
+impl<P: HasMaxBookmarkId> PixivRequestBuilder<P> {
+    /// Sets the `max_bookmark_id` param. The App API pages bookmarks with this, see e.g. `NovelList::next_param`.
     #[inline]
-    pub fn search_mode(self, value: SearchMode) -> Self {
-        self.raw_param("mode", value.as_str())
+    pub fn max_bookmark_id(mut self, value: usize) -> Self {
+        *self.params.max_bookmark_id_mut() = Some(value);
+        self
     }
-    /// Sets the `order` param in the case of a `search_works()` call. Must be a value of enum `SearchOrder`.
+}
+
+impl<P: HasBookmarkTag> PixivRequestBuilder<P> {
+    /// Sets the `tag` param, limiting results to bookmarks with this bookmark tag.
     #[inline]
-    pub fn search_order(self, value: SearchOrder) -> Self {
-        self.raw_param("order", value.as_str())
+    pub fn bookmark_tag<V: Into<String>>(mut self, value: V) -> Self {
+        *self.params.bookmark_tag_mut() = Some(value.into());
+        self
     }
-    /// Sets the `sort` param in the case of a `search_works()` call. Not sure if there's any variations here, but this function is included for convenience.
-    pub fn search_sort<V>(self, value: V) -> Self
-    where
-        Cow<'a, str>: From<V>,
-    {
-        self.raw_param("sort", value)
+}
+
+impl<P: HasShowR18> PixivRequestBuilder<P> {
+    /// Sets the `show_r18` param. `true` means R-18 works will be included.
+    #[inline]
+    pub fn show_r18(mut self, value: bool) -> Self {
+        *self.params.show_r18_mut() = value;
+        self
     }
-    /// Sets the `types` param in the case of a `search_works()` call. Available values: `illustration`, `manga`, `ugoira`.
+}
+
+impl<P: HasIncludeStats> PixivRequestBuilder<P> {
+    /// Sets the `include_stats` param.
     #[inline]
-    pub fn search_types(self, values: &[&str]) -> Self {
-        self.raw_param("types", comma_delimited::<&str, _, _>(values))
+    pub fn include_stats(mut self, value: bool) -> Self {
+        *self.params.include_stats_mut() = value;
+        self
     }
-    fn raw_param<V>(mut self, key: &'a str, value: V) -> Self
-    where
-        Cow<'a, str>: From<V>,
-    {
-        self.params.insert(key, value.into());
+}
+
+impl<P: HasIncludeSanityLevel> PixivRequestBuilder<P> {
+    /// Sets the `include_sanity_level` param.
+    #[inline]
+    pub fn include_sanity_level(mut self, value: bool) -> Self {
+        *self.params.include_sanity_level_mut() = value;
         self
     }
+}
+
+impl<P: HasDate> PixivRequestBuilder<P> {
+    /// Sets the `date` param. Must be a `NaiveDate` or a valid date in the form of `%Y-%m-%d`, e.g. `2018-2-22`.
+    /// # Panics
+    /// Panics if the date is invalid. Use `try_date` for user-supplied input.
+    pub fn date<D: DateParam>(self, value: D) -> Self {
+        self.try_date(value).expect("Invalid date or format given.")
+    }
+    /// Sets the `date` param, returning an error if the given date is invalid.
+    pub fn try_date<D: DateParam>(mut self, value: D) -> Result<Self, RequestError> {
+        *self.params.date_mut() = Some(value.to_naive_date()?);
+        Ok(self)
+    }
+}
 
+impl PixivRequestBuilder<BadWordsParams> {
     /// Used to build a request to retrive `bad_words.json`.
     /// # Request Transforms
     /// None
     pub fn bad_words() -> Self {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1.1/bad_words.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1.1/bad_words.json";
         let url = Uri::from_static(API_URL);
-        PixivRequestBuilder::new(
-            Method::GET,
-            url,
-            HashMap::default(),
-        )
+        PixivRequestBuilder::new(Method::GET, url, BadWordsParams::default())
     }
+}
+
+impl PixivRequestBuilder<WorkParams> {
     /// Used to build a request to retrieve information of a work.
     /// # Request Transforms
     /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
@@ -502,14 +972,15 @@ impl<'a> PixivRequestBuilder<'a> {
             "https://public-api.secure.pixiv.net/v1/works/{}.json",
             illust_id
         );
-        let extra_params = [
-            ("image_sizes", "px_128x128,small,medium,large,px_480mw"),
-            ("include_stats", "true"),
-        ];
-        let url = Uri::try_from(url).unwrap();
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
-        PixivRequestBuilder::new(Method::GET, url, params)
+        let params = WorkParams {
+            image_sizes: DETAIL_IMAGE_SIZES.to_vec(),
+            include_stats: true,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
     }
+}
+
+impl PixivRequestBuilder<UserParams> {
     /// Used to build a request to retrieve information of a user.
     /// # Request Transforms
     /// * `profile_image_sizes` (default: `px_170x170,px_50x50`)
@@ -520,68 +991,77 @@ impl<'a> PixivRequestBuilder<'a> {
             "https://public-api.secure.pixiv.net/v1/users/{}.json",
             user_id
         );
-        let extra_params = [
-            ("profile_image_sizes", "px_170x170,px_50x50"),
-            ("image_sizes", "px_128x128,small,medium,large,px_480mw"),
-            ("include_stats", "1"),
-            ("include_profile", "1"),
-            ("include_workspace", "1"),
-            ("include_contacts", "1"),
-        ];
-        let url = Uri::try_from(&url).unwrap();
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
-        PixivRequestBuilder::new(Method::GET, url, params)
+        let params = UserParams {
+            profile_image_sizes: PROFILE_IMAGE_SIZES.to_vec(),
+            image_sizes: DETAIL_IMAGE_SIZES.to_vec(),
+            include_stats: true,
+            include_profile: true,
+            include_workspace: true,
+            include_contacts: true,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
     }
+}
+
+impl PixivRequestBuilder<FeedParams> {
     /// Used to build a request to retrieve your account's feed.
     /// # Request Transforms
     /// * `show_r18` (default: `true`)
     /// * `max_id`
     pub fn feed() -> Self {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/me/feeds.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/me/feeds.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [
-            ("relation", "all"),
-            ("type", "touch_nottext"),
-            ("show_r18", "1"),
-        ];
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
-        PixivRequestBuilder::new(Method::GET, url, params)
+        PixivRequestBuilder::new(Method::GET, url, FeedParams::default())
     }
-    /// Used to build a request to retrieve works favorited on your account.
+    /// Used to build a request to retrive a user's feed.
     /// # Request Transforms
-    /// * `page` (default: `1`)
-    /// * `per_page` (default: `50`)
-    /// * `publicity` (default: `public`)
-    /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
+    /// * `show_r18` (default: `true`)
+    /// * `max_id`
+    pub fn user_feed(user_id: usize) -> Self {
+        let url = format!(
+            "https://public-api.secure.pixiv.net/v1/users/{}/feeds.json",
+            user_id
+        );
+        PixivRequestBuilder::parse_new(Method::GET, &url, FeedParams::default())
+    }
+}
+
+impl PixivRequestBuilder<FavoriteWorksParams> {
+    /// Used to build a request to retrieve works favorited on your account.
+    /// # Request Transforms
+    /// * `page` (default: `1`)
+    /// * `per_page` (default: `50`)
+    /// * `publicity` (default: `public`)
+    /// * `image_sizes` (default: `px_128x128,px_480mw,large`)
     pub fn favorite_works() -> Self {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/me/favorite_works.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/me/favorite_works.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [
-            ("page", "1"),
-            ("per_page", "50"),
-            ("publicity", "public"),
-            ("image_sizes", "px_128x128,px_480mw,large"),
-        ];
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
+        let params = FavoriteWorksParams {
+            page: 1,
+            per_page: 50,
+            publicity: Publicity::Public,
+            image_sizes: LIST_IMAGE_SIZES.to_vec(),
+        };
         PixivRequestBuilder::new(Method::GET, url, params)
     }
+}
+
+impl PixivRequestBuilder<FavoriteWorkAddParams> {
     /// Used to build a request to favorite a work on your account.
     /// # Request Transforms
     /// * `publicity` (default: `public`)
     pub fn favorite_work_add(work_id: usize) -> Self {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/me/favorite_works.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/me/favorite_works.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [("publicity", "public")];
-        let params = extra_params
-            .iter()
-            .map(|&(k, v)| (k, v.into()))
-            .chain(Some(("work_id", work_id.to_string().into())))
-            .collect();
+        let params = FavoriteWorkAddParams {
+            work_id,
+            publicity: Publicity::Public,
+        };
         PixivRequestBuilder::new(Method::POST, url, params)
     }
+}
+
+impl PixivRequestBuilder<FavoriteWorksRemoveParams> {
     /// Used to build a request to remove favorited works on your account.
     /// # Request Transforms
     /// * `publicity` (default: `public`)
@@ -590,68 +1070,74 @@ impl<'a> PixivRequestBuilder<'a> {
         B: Borrow<usize>,
         I: IntoIterator<Item = B>,
     {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/me/favorite_works.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/me/favorite_works.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [("publicity", "public")];
-        let params = extra_params
-            .iter()
-            .map(|&(k, v)| (k, v.into()))
-            .chain(Some(("ids", comma_delimited(work_ids).into())))
-            .collect();
+        let params = FavoriteWorksRemoveParams {
+            ids: work_ids.into_iter().map(|id| *id.borrow()).collect(),
+            publicity: Publicity::Public,
+        };
         PixivRequestBuilder::new(Method::DELETE, url, params)
     }
+}
+
+impl PixivRequestBuilder<FollowingWorksParams> {
     /// Used to build a request to retrieve newest works from whoever you follow on your account.
     /// # Request Transforms
     /// * `page` (default: `1`)
     /// * `per_page` (default: `30`)
-    /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
+    /// * `image_sizes` (default: `px_128x128,px_480mw,large`)
     /// * `include_stats` (default: `true`)
     /// * `include_sanity_level` (default: `true`)
+    /// * `max_id`
     pub fn following_works() -> Self {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/me/following/works.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/me/following/works.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [
-            ("page", "1"),
-            ("per_page", "30"),
-            ("image_sizes", "px_128x128,px480mw,large"),
-            ("include_stats", "true"),
-            ("include_sanity_level", "true"),
-        ];
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
+        let params = FollowingWorksParams {
+            page: 1,
+            per_page: 30,
+            image_sizes: LIST_IMAGE_SIZES.to_vec(),
+            include_stats: true,
+            include_sanity_level: true,
+            max_id: None,
+        };
         PixivRequestBuilder::new(Method::GET, url, params)
     }
+}
+
+impl PixivRequestBuilder<FollowingParams> {
     /// Used to build a request to retrieve users you follow.
     /// # Request Transforms
     /// * `page` (default: `1`)
     /// * `per_page` (default: `30`)
-    /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
-    /// * `include_stats` (default: `true`)
-    /// * `include_sanity_level` (default: `true`)
+    /// * `publicity` (default: `public`)
     pub fn following() -> Self {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/me/following.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/me/following.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [("page", "1"), ("per_page", "30"), ("publicity", "public")];
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
+        let params = FollowingParams {
+            page: 1,
+            per_page: 30,
+            publicity: Publicity::Public,
+        };
         PixivRequestBuilder::new(Method::GET, url, params)
     }
+}
+
+impl PixivRequestBuilder<FollowingAddParams> {
     /// Used to build a request to follow a user on your account.
     /// # Request Transforms
     /// * `publicity` (default: `public`)
     pub fn following_add(user_id: usize) -> Self {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/me/favorite-users.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/me/favorite-users.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [("publicity", "public")];
-        let params = extra_params
-            .iter()
-            .map(|&(k, v)| (k, v.into()))
-            .chain(Some(("target_user_id", user_id.to_string().into())))
-            .collect();
+        let params = FollowingAddParams {
+            target_user_id: user_id,
+            publicity: Publicity::Public,
+        };
         PixivRequestBuilder::new(Method::POST, url, params)
     }
+}
+
+impl PixivRequestBuilder<FollowingRemoveParams> {
     /// Used to build a request to unfollow users on your account.
     /// # Request Transforms
     /// * `publicity` (default: `public`)
@@ -660,22 +1146,22 @@ impl<'a> PixivRequestBuilder<'a> {
         B: Borrow<usize>,
         I: IntoIterator<Item = B>,
     {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/me/favorite-users.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/me/favorite-users.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [("publicity", "public")];
-        let params = extra_params
-            .iter()
-            .map(|&(k, v)| (k, v.into()))
-            .chain(Some(("delete_ids", comma_delimited(user_ids).into())))
-            .collect();
+        let params = FollowingRemoveParams {
+            delete_ids: user_ids.into_iter().map(|id| *id.borrow()).collect(),
+            publicity: Publicity::Public,
+        };
         PixivRequestBuilder::new(Method::DELETE, url, params)
     }
+}
+
+impl PixivRequestBuilder<UserWorksParams> {
     /// Used to build a request to retrive a list of works submitted by a user.
     /// # Request Transforms
     /// * `page` (default: `1`)
     /// * `per_page` (default: `30`)
-    /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
+    /// * `image_sizes` (default: `px_128x128,px_480mw,large`)
     /// * `include_stats` (default: `true`)
     /// * `include_sanity_level` (default: `true`)
     pub fn user_works(user_id: usize) -> Self {
@@ -683,55 +1169,40 @@ impl<'a> PixivRequestBuilder<'a> {
             "https://public-api.secure.pixiv.net/v1/users/{}/works.json",
             user_id
         );
-        let extra_params = [
-            ("page", "1"),
-            ("per_page", "30"),
-            ("image_sizes", "px_128x128,px480mw,large"),
-            ("include_stats", "true"),
-            ("include_sanity_level", "true"),
-        ];
-        let url = Uri::try_from(&url).unwrap();
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
-        PixivRequestBuilder::new(Method::GET, url, params)
+        let params = UserWorksParams {
+            page: 1,
+            per_page: 30,
+            image_sizes: LIST_IMAGE_SIZES.to_vec(),
+            include_stats: true,
+            include_sanity_level: true,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
     }
+}
+
+impl PixivRequestBuilder<UserFavoriteWorksParams> {
     /// Used to build a request to retrive a list of works favorited by a user.
     /// # Request Transforms
     /// * `page` (default: `1`)
     /// * `per_page` (default: `30`)
-    /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
+    /// * `image_sizes` (default: `px_128x128,px_480mw,large`)
     /// * `include_sanity_level` (default: `true`)
     pub fn user_favorite_works(user_id: usize) -> Self {
         let url = format!(
             "https://public-api.secure.pixiv.net/v1/users/{}/favorite_works.json",
             user_id
         );
-        let extra_params = [
-            ("page", "1"),
-            ("per_page", "30"),
-            ("image_sizes", "px_128x128,px480mw,large"),
-            ("include_sanity_level", "true"),
-        ];
-        let url = Uri::try_from(&url).unwrap();
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
-        PixivRequestBuilder::new(Method::GET, url, params)
-    }
-    /// Used to build a request to retrive a user's feed.
-    /// # Request Transforms
-    /// * `show_r18` (default: `true`)
-    pub fn user_feed(user_id: usize) -> Self {
-        let url = format!(
-            "https://public-api.secure.pixiv.net/v1/users/{}/feeds.json",
-            user_id
-        );
-        let extra_params = [
-            ("relation", "all"),
-            ("type", "touch_nottext"),
-            ("show_r18", "1"),
-        ];
-        let url = Uri::try_from(&url).unwrap();
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
-        PixivRequestBuilder::new(Method::GET, url, params)
+        let params = UserFavoriteWorksParams {
+            page: 1,
+            per_page: 30,
+            image_sizes: LIST_IMAGE_SIZES.to_vec(),
+            include_sanity_level: true,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
     }
+}
+
+impl PixivRequestBuilder<UserFollowingParams> {
     /// Used to build a request to retrieve users a user follows.
     /// # Request Transforms
     /// * `page` (default: `1`)
@@ -742,16 +1213,22 @@ impl<'a> PixivRequestBuilder<'a> {
             "https://public-api.secure.pixiv.net/v1/users/{}/following.json",
             user_id
         );
-        let extra_params = [("page", "1"), ("per_page", "30")];
-        let url = Uri::try_from(&url).unwrap();
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
-        PixivRequestBuilder::new(Method::GET, url, params)
+        let params = UserFollowingParams {
+            page: 1,
+            per_page: 30,
+            max_id: None,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
     }
+}
+
+impl PixivRequestBuilder<RankingParams> {
     /// Used to build a request to retrieve a list of ranking posts.
     /// # Request Transforms
     /// * `ranking_mode` (default: `RankingMode::Daily`)
     /// * `page` (default: `1`)
     /// * `per_page` (default: `50`)
+    /// * `date`
     /// * `include_stats` (default: `true`)
     /// * `include_sanity_level` (default: `true`)
     /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
@@ -761,87 +1238,515 @@ impl<'a> PixivRequestBuilder<'a> {
             "https://public-api.secure.pixiv.net/v1/ranking/{}.json",
             ranking_type.as_str()
         );
-        let extra_params = [
-            ("mode", "daily"),
-            ("page", "1"),
-            ("per_page", "50"),
-            ("include_stats", "True"),
-            ("include_sanity_level", "True"),
-            ("image_sizes", "px_128x128,small,medium,large,px_480mw"),
-            ("profile_image_sizes", "px_170x170,px_50x50"),
-        ];
-        let url = Uri::try_from(&url).unwrap();
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
-        PixivRequestBuilder::new(Method::GET, url, params)
+        let params = RankingParams {
+            mode: RankingMode::Daily,
+            page: 1,
+            per_page: 50,
+            date: None,
+            include_stats: true,
+            include_sanity_level: true,
+            image_sizes: DETAIL_IMAGE_SIZES.to_vec(),
+            profile_image_sizes: PROFILE_IMAGE_SIZES.to_vec(),
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
     }
+    /// Sets the ranking mode. Must be a value of enum `RankingMode`.
+    #[inline]
+    pub fn ranking_mode(mut self, value: RankingMode) -> Self {
+        self.params.mode = value;
+        self
+    }
+}
+
+impl PixivRequestBuilder<SearchWorksParams> {
     /// Used to build a request to search for posts on a query.
     /// # Request Transforms
     /// * `page` (default: `1`)
     /// * `per_page` (default: `30`)
     /// * `date`
+    /// * `date_range`
     /// * `search_mode` (default: `SearchMode::Text`)
     /// * `search_period` (default: `SearchPeriod::All`)
-    /// * `search_order` (default: `desc`)
-    /// * `search_sort` (default: `date`)
-    /// * `search_types` (default: `illustration,manga,ugoira`)
+    /// * `search_order` (default: `SearchOrder::Descending`)
+    /// * `search_sort` (default: `SearchSort::Date`)
+    /// * `search_types` (default: `WorkType::Illustration`, `WorkType::Manga`, `WorkType::Ugoira`)
+    /// * `exclude_ai` (default: `false`)
     /// * `include_stats` (default: `true`)
     /// * `include_sanity_level` (default: `true`)
-    /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
-    pub fn search_works<V>(query: V) -> PixivRequestBuilder<'a>
-    where
-        Cow<'a, str>: From<V>,
-    {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/search/works.json";
+    /// * `image_sizes` (default: `px_128x128,px_480mw,large`)
+    pub fn search_works<V: Into<String>>(query: V) -> Self {
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/search/works.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [
-            ("page", "1"),
-            ("per_page", "30"),
-            ("mode", "text"),
-            ("period", "all"),
-            ("order", "desc"),
-            ("sort", "date"),
-            ("types", "illustration,manga,ugoira"),
-            ("include_stats", "true"),
-            ("include_sanity_level", "true"),
-            ("image_sizes", "px_128x128,px480mw,large"),
-        ];
-        let params = extra_params
-            .iter()
-            .map(|&(k, v)| (k, v.into()))
-            .chain(Some(("q", query.into())))
-            .collect();
+        let params = SearchWorksParams {
+            q: query.into(),
+            page: 1,
+            per_page: 30,
+            date: None,
+            start_date: None,
+            end_date: None,
+            mode: SearchMode::Text,
+            period: SearchPeriod::All,
+            order: SearchOrder::Descending,
+            sort: SearchSort::Date,
+            types: vec![WorkType::Illustration, WorkType::Manga, WorkType::Ugoira],
+            exclude_ai: false,
+            include_stats: true,
+            include_sanity_level: true,
+            image_sizes: LIST_IMAGE_SIZES.to_vec(),
+        };
         PixivRequestBuilder::new(Method::GET, url, params)
     }
+    /// Sets the `period` param. Must be a value of enum `SearchPeriod`.
+    #[inline]
+    pub fn search_period(mut self, value: SearchPeriod) -> Self {
+        self.params.period = value;
+        self
+    }
+    /// Sets the `mode` param. Must be a value of enum `SearchMode`.
+    #[inline]
+    pub fn search_mode(mut self, value: SearchMode) -> Self {
+        self.params.mode = value;
+        self
+    }
+    /// Sets the `order` param. Must be a value of enum `SearchOrder`.
+    #[inline]
+    pub fn search_order(mut self, value: SearchOrder) -> Self {
+        self.params.order = value;
+        self
+    }
+    /// Sets the `sort` param. Must be a value of enum `SearchSort`.
+    #[inline]
+    pub fn search_sort(mut self, value: SearchSort) -> Self {
+        self.params.sort = value;
+        self
+    }
+    /// Sets the `types` param. Must be values of enum `WorkType`.
+    #[inline]
+    pub fn search_types(mut self, values: &[WorkType]) -> Self {
+        self.params.types = values.to_vec();
+        self
+    }
+    /// Sets the `start_date` and `end_date` params, limiting results to works posted within them (inclusive).
+    /// Takes the same values as `date`.
+    /// # Panics
+    /// Panics if a date is invalid. Use `try_date_range` for user-supplied input.
+    pub fn date_range<S: DateParam, E: DateParam>(self, start: S, end: E) -> Self {
+        self.try_date_range(start, end).expect("Invalid date or format given.")
+    }
+    /// Sets the `start_date` and `end_date` params, returning an error if either date is invalid.
+    pub fn try_date_range<S: DateParam, E: DateParam>(mut self, start: S, end: E) -> Result<Self, RequestError> {
+        self.params.start_date = Some(start.to_naive_date()?);
+        self.params.end_date = Some(end.to_naive_date()?);
+        Ok(self)
+    }
+    /// Sets whether AI-generated works are left out of the results.
+    #[inline]
+    pub fn exclude_ai(mut self, value: bool) -> Self {
+        self.params.exclude_ai = value;
+        self
+    }
+}
 
+impl PixivRequestBuilder<LatestWorksParams> {
     /// Used to build a request to retrieve the latest submitted works by everyone.
     /// # Request Transforms
     /// * `page` (default: `1`)
-    /// * `per_page` (default: `50`)
+    /// * `per_page` (default: `30`)
     /// * `date`
     /// * `include_stats` (default: `true`)
     /// * `include_sanity_level` (default: `true`)
-    /// * `image_sizes` (default: `px_128x128,small,medium,large,px_480mw`)
+    /// * `image_sizes` (default: `px_128x128,px_480mw,large`)
     /// * `profile_image_sizes` (default: `px_170x170,px_50x50`)
     pub fn latest_works() -> Self {
-        const API_URL: &'static str = "https://public-api.secure.pixiv.net/v1/works.json";
+        const API_URL: &str = "https://public-api.secure.pixiv.net/v1/works.json";
         let url = Uri::from_static(API_URL);
-
-        let extra_params = [
-            ("page", "1"),
-            ("per_page", "30"),
-            ("include_stats", "true"),
-            ("include_sanity_level", "true"),
-            ("image_sizes", "px_128x128,px480mw,large"),
-            ("profile_image_sizes", "px_170x170,px_50x50"),
-        ];
-        let params = extra_params.iter().map(|&(k, v)| (k, v.into())).collect();
+        let params = LatestWorksParams {
+            page: 1,
+            per_page: 30,
+            date: None,
+            include_stats: true,
+            include_sanity_level: true,
+            image_sizes: LIST_IMAGE_SIZES.to_vec(),
+            profile_image_sizes: PROFILE_IMAGE_SIZES.to_vec(),
+        };
         PixivRequestBuilder::new(Method::GET, url, params)
     }
-    /// Returns a `PixivRequest` which can be inspected and/or executed with `Pixiv::execute()`.
+}
+
+// Base url of the App API, which serves the novel endpoints.
+const APP_API_URL: &str = "https://app-api.pixiv.net";
+
+impl PixivRequestBuilder<NovelParams> {
+    /// Used to build a request to retrieve information of a novel, as `models::NovelDetail`.
+    /// # Request Transforms
+    /// None
+    pub fn novel(novel_id: usize) -> Self {
+        let url = format!("{}/v2/novel/detail", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::GET, &url, NovelParams { novel_id })
+    }
+    /// Used to build a request to retrieve the body text of a novel, as `models::NovelTextResponse`.
+    /// # Request Transforms
+    /// None
+    pub fn novel_text(novel_id: usize) -> Self {
+        let url = format!("{}/v1/novel/text", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::GET, &url, NovelParams { novel_id })
+    }
+}
+
+impl PixivRequestBuilder<NovelSeriesParams> {
+    /// Used to build a request to retrieve a novel series and its novels, as `models::NovelSeriesResponse`.
+    /// # Request Transforms
+    /// * `last_order`
+    pub fn novel_series(series_id: usize) -> Self {
+        let url = format!("{}/v2/novel/series", APP_API_URL);
+        let params = NovelSeriesParams {
+            series_id,
+            last_order: None,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
+    }
+    /// Sets the `last_order` param, i.e. how many novels of the series to skip.
     #[inline]
-    pub fn build(self) -> PixivRequest {
-        self.request.set_query_params(&self.params)
+    pub fn last_order(mut self, value: usize) -> Self {
+        self.params.last_order = Some(value);
+        self
+    }
+}
+
+impl PixivRequestBuilder<UserNovelsParams> {
+    /// Used to build a request to retrieve novels submitted by a user, as `models::NovelList`.
+    /// # Request Transforms
+    /// * `offset` (default: `0`)
+    pub fn user_novels(user_id: usize) -> Self {
+        let url = format!("{}/v1/user/novels", APP_API_URL);
+        let params = UserNovelsParams { user_id, offset: 0 };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
+    }
+}
+
+impl PixivRequestBuilder<UserBookmarkedNovelsParams> {
+    /// Used to build a request to retrieve novels bookmarked by a user, as `models::NovelList`.
+    /// # Request Transforms
+    /// * `publicity` (default: `public`, sent as `restrict`)
+    /// * `max_bookmark_id`
+    /// * `bookmark_tag`
+    pub fn user_bookmarked_novels(user_id: usize) -> Self {
+        let url = format!("{}/v1/user/bookmarks/novel", APP_API_URL);
+        let params = UserBookmarkedNovelsParams {
+            user_id,
+            publicity: Publicity::Public,
+            max_bookmark_id: None,
+            tag: None,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
+    }
+}
+
+impl PixivRequestBuilder<NovelRankingParams> {
+    /// Used to build a request to retrieve a novel ranking, as `models::NovelList`.
+    /// # Request Transforms
+    /// * `novel_ranking_mode` (default: `NovelRankingMode::Day`)
+    /// * `date`
+    /// * `offset` (default: `0`)
+    pub fn novel_ranking() -> Self {
+        let url = format!("{}/v1/novel/ranking", APP_API_URL);
+        let params = NovelRankingParams {
+            mode: NovelRankingMode::Day,
+            date: None,
+            offset: 0,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
+    }
+    /// Sets the ranking mode. Must be a value of enum `NovelRankingMode`.
+    #[inline]
+    pub fn novel_ranking_mode(mut self, value: NovelRankingMode) -> Self {
+        self.params.mode = value;
+        self
+    }
+}
+
+impl PixivRequestBuilder<SearchNovelsParams> {
+    /// Used to build a request to search for novels on a query, as `models::NovelList`.
+    /// # Request Transforms
+    /// * `search_target` (default: `SearchTarget::PartialMatchForTags`)
+    /// * `novel_search_sort` (default: `NovelSearchSort::DateDescending`)
+    /// * `date_range`
+    /// * `offset` (default: `0`)
+    pub fn search_novels<V: Into<String>>(query: V) -> Self {
+        let url = format!("{}/v1/search/novel", APP_API_URL);
+        let params = SearchNovelsParams {
+            word: query.into(),
+            search_target: SearchTarget::PartialMatchForTags,
+            sort: NovelSearchSort::DateDescending,
+            start_date: None,
+            end_date: None,
+            offset: 0,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
+    }
+    /// Sets the `search_target` param. Must be a value of enum `SearchTarget`.
+    #[inline]
+    pub fn search_target(mut self, value: SearchTarget) -> Self {
+        self.params.search_target = value;
+        self
+    }
+    /// Sets the `sort` param. Must be a value of enum `NovelSearchSort`.
+    #[inline]
+    pub fn novel_search_sort(mut self, value: NovelSearchSort) -> Self {
+        self.params.sort = value;
+        self
+    }
+    /// Sets the `start_date` and `end_date` params, limiting results to novels posted within them (inclusive).
+    /// # Panics
+    /// Panics if a date is invalid. Use `try_date_range` for user-supplied input.
+    pub fn date_range<S: DateParam, E: DateParam>(self, start: S, end: E) -> Self {
+        self.try_date_range(start, end).expect("Invalid date or format given.")
+    }
+    /// Sets the `start_date` and `end_date` params, returning an error if either date is invalid.
+    pub fn try_date_range<S: DateParam, E: DateParam>(mut self, start: S, end: E) -> Result<Self, RequestError> {
+        self.params.start_date = Some(start.to_naive_date()?);
+        self.params.end_date = Some(end.to_naive_date()?);
+        Ok(self)
+    }
+}
+
+impl PixivRequestBuilder<IllustParams> {
+    /// Used to build a request to retrieve a work from the App API, as `models::IllustDetail`.
+    /// Unlike `work()`, the response includes the series the work belongs to.
+    /// # Request Transforms
+    /// None
+    pub fn illust_detail(illust_id: usize) -> Self {
+        let url = format!("{}/v1/illust/detail", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::GET, &url, IllustParams { illust_id })
+    }
+}
+
+impl PixivRequestBuilder<IllustSeriesParams> {
+    /// Used to build a request to retrieve an illustration or manga series and a page of its works,
+    /// as `models::IllustSeriesResponse`.
+    /// # Request Transforms
+    /// * `offset` (default: `0`)
+    pub fn illust_series(series_id: usize) -> Self {
+        let url = format!("{}/v1/illust/series", APP_API_URL);
+        let params = IllustSeriesParams {
+            illust_series_id: series_id,
+            offset: 0,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
+    }
+}
+
+impl PixivRequestBuilder<MangaWatchlistParams> {
+    /// Used to build a request to retrieve the series on your manga watchlist, as `models::MangaWatchlist`.
+    /// # Request Transforms
+    /// * `offset` (default: `0`)
+    pub fn manga_watchlist() -> Self {
+        let url = format!("{}/v1/watchlist/manga", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::GET, &url, MangaWatchlistParams::default())
+    }
+}
+
+impl PixivRequestBuilder<MangaWatchlistEditParams> {
+    /// Used to build a request to add a series to your manga watchlist.
+    /// # Request Transforms
+    /// None
+    pub fn manga_watchlist_add(series_id: usize) -> Self {
+        let url = format!("{}/v1/watchlist/manga/add", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::POST, &url, MangaWatchlistEditParams { series_id })
+    }
+    /// Used to build a request to remove a series from your manga watchlist.
+    /// # Request Transforms
+    /// None
+    pub fn manga_watchlist_remove(series_id: usize) -> Self {
+        let url = format!("{}/v1/watchlist/manga/delete", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::POST, &url, MangaWatchlistEditParams { series_id })
+    }
+}
+
+impl PixivRequestBuilder<CommentsParams> {
+    /// Used to build a request to retrieve the comments on a work, newest first, as `models::CommentList`.
+    /// Replies aren't included; comments with `has_replies` set can be expanded with `comment_replies()`.
+    /// # Request Transforms
+    /// * `offset` (default: `0`)
+    pub fn comments(illust_id: usize) -> Self {
+        let url = format!("{}/v3/illust/comments", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::GET, &url, CommentsParams { illust_id, offset: 0 })
+    }
+}
+
+impl PixivRequestBuilder<CommentRepliesParams> {
+    /// Used to build a request to retrieve the replies to a comment, as `models::CommentList`.
+    /// # Request Transforms
+    /// * `offset` (default: `0`)
+    pub fn comment_replies(comment_id: usize) -> Self {
+        let url = format!("{}/v2/illust/comment/replies", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::GET, &url, CommentRepliesParams { comment_id, offset: 0 })
+    }
+}
+
+impl PixivRequestBuilder<CommentAddParams> {
+    /// Used to build a request to post a comment on a work. The response is a `models::CommentAdded`.
+    /// # Request Transforms
+    /// * `reply_to` (default: none, i.e. a top-level comment)
+    pub fn comment_add<V: Into<String>>(illust_id: usize, comment: V) -> Self {
+        let url = format!("{}/v1/illust/comment/add", APP_API_URL);
+        let params = CommentAddParams {
+            illust_id,
+            comment: Some(comment.into()),
+            stamp_id: None,
+            parent_comment_id: None,
+        };
+        PixivRequestBuilder::parse_new(Method::POST, &url, params)
+    }
+    /// Used to build a request to post a stamp on a work. The response is a `models::CommentAdded`.
+    /// # Request Transforms
+    /// * `reply_to` (default: none, i.e. a top-level comment)
+    pub fn comment_add_stamp(illust_id: usize, stamp_id: usize) -> Self {
+        let url = format!("{}/v1/illust/comment/add", APP_API_URL);
+        let params = CommentAddParams {
+            illust_id,
+            comment: None,
+            stamp_id: Some(stamp_id),
+            parent_comment_id: None,
+        };
+        PixivRequestBuilder::parse_new(Method::POST, &url, params)
+    }
+    /// Sets the `parent_comment_id` param, posting the comment as a reply.
+    #[inline]
+    pub fn reply_to(mut self, comment_id: usize) -> Self {
+        self.params.parent_comment_id = Some(comment_id);
+        self
+    }
+}
+
+impl PixivRequestBuilder<CommentDeleteParams> {
+    /// Used to build a request to delete one of your comments.
+    /// # Request Transforms
+    /// None
+    pub fn comment_delete(comment_id: usize) -> Self {
+        let url = format!("{}/v1/illust/comment/delete", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::POST, &url, CommentDeleteParams { comment_id })
+    }
+}
+
+impl PixivRequestBuilder<SearchUserParams> {
+    /// Used to build a request to search users by name or account, as `models::UserPreviews`.
+    /// # Request Transforms
+    /// * `offset` (default: `0`)
+    pub fn search_user<V: Into<String>>(query: V) -> Self {
+        let url = format!("{}/v1/search/user", APP_API_URL);
+        let params = SearchUserParams {
+            word: query.into(),
+            offset: 0,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
+    }
+}
+
+impl PixivRequestBuilder<UserRecommendedParams> {
+    /// Used to build a request to retrieve users Pixiv recommends you follow, as `models::UserPreviews`.
+    /// # Request Transforms
+    /// * `offset` (default: `0`)
+    pub fn user_recommended() -> Self {
+        let url = format!("{}/v1/user/recommended", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::GET, &url, UserRecommendedParams::default())
+    }
+}
+
+impl PixivRequestBuilder<IllustParams> {
+    /// Used to build a request to retrieve whether and how you bookmarked a work, as
+    /// `models::BookmarkDetailResponse`. The tags include every bookmark tag you've used, marked by
+    /// whether they're on this bookmark.
+    /// # Request Transforms
+    /// None
+    pub fn illust_bookmark_detail(illust_id: usize) -> Self {
+        let url = format!("{}/v2/illust/bookmark/detail", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::GET, &url, IllustParams { illust_id })
+    }
+    /// Used to build a request to remove a work from your bookmarks.
+    /// # Request Transforms
+    /// None
+    pub fn illust_bookmark_delete(illust_id: usize) -> Self {
+        let url = format!("{}/v1/illust/bookmark/delete", APP_API_URL);
+        PixivRequestBuilder::parse_new(Method::POST, &url, IllustParams { illust_id })
+    }
+}
+
+impl PixivRequestBuilder<IllustBookmarkAddParams> {
+    /// Used to build a request to bookmark a work through the App API, which unlike `favorite_work_add()`
+    /// accepts bookmark tags.
+    /// # Request Transforms
+    /// * `publicity` (default: `public`)
+    /// * `bookmark_tags` (default: none)
+    pub fn illust_bookmark_add(illust_id: usize) -> Self {
+        let url = format!("{}/v2/illust/bookmark/add", APP_API_URL);
+        let params = IllustBookmarkAddParams {
+            illust_id,
+            publicity: Publicity::Public,
+            tags: Vec::new(),
+        };
+        PixivRequestBuilder::parse_new(Method::POST, &url, params)
+    }
+    /// Used to build a request to edit a bookmark, replacing its tags and publicity. Bookmarking a work
+    /// again is how the App API edits a bookmark, so this also bookmarks the work if it wasn't.
+    /// # Request Transforms
+    /// * `publicity`
+    /// * `bookmark_tags`
+    pub fn illust_bookmark_edit<B, I>(illust_id: usize, tags: I, publicity: Publicity) -> Self
+    where
+        B: Into<String>,
+        I: IntoIterator<Item = B>,
+    {
+        PixivRequestBuilder::illust_bookmark_add(illust_id)
+            .bookmark_tags(tags)
+            .publicity(publicity)
+    }
+    /// Sets the bookmark tags, replacing any set before. Tags may not contain spaces, as Pixiv separates
+    /// them with one.
+    #[inline]
+    pub fn bookmark_tags<B, I>(mut self, tags: I) -> Self
+    where
+        B: Into<String>,
+        I: IntoIterator<Item = B>,
+    {
+        self.params.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl PixivRequestBuilder<UserBookmarkTagsParams> {
+    /// Used to build a request to retrieve the bookmark tags a user has used for works, with how many
+    /// bookmarks each is on, as `models::BookmarkTags`.
+    /// # Request Transforms
+    /// * `publicity` (default: `public`)
+    /// * `offset` (default: `0`)
+    pub fn user_bookmark_tags(user_id: usize) -> Self {
+        let url = format!("{}/v1/user/bookmark-tags/illust", APP_API_URL);
+        let params = UserBookmarkTagsParams {
+            user_id,
+            publicity: Publicity::Public,
+            offset: 0,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
+    }
+}
+
+impl PixivRequestBuilder<UserBookmarksParams> {
+    /// Used to build a request to retrieve works bookmarked by a user through the App API, newest
+    /// bookmark first, as `models::IllustList`.
+    /// # Request Transforms
+    /// * `publicity` (default: `public`)
+    /// * `max_bookmark_id`
+    /// * `bookmark_tag`
+    pub fn user_bookmarks(user_id: usize) -> Self {
+        let url = format!("{}/v1/user/bookmarks/illust", APP_API_URL);
+        let params = UserBookmarksParams {
+            user_id,
+            publicity: Publicity::Public,
+            max_bookmark_id: None,
+            tag: None,
+        };
+        PixivRequestBuilder::parse_new(Method::GET, &url, params)
     }
 }
 
@@ -864,4 +1769,225 @@ mod tests {
         PixivRequestBuilder::following_remove(vec);
         PixivRequestBuilder::following_remove(iter);
     }
+
+    #[test]
+    fn test_try_date() {
+        let date = NaiveDate::from_ymd_opt(2018, 2, 22).unwrap();
+
+        let request = PixivRequestBuilder::ranking(RankingType::All)
+            .try_date(date)
+            .unwrap()
+            .build();
+        assert!(request.url().query().unwrap().contains("date=2018-02-22"));
+
+        let request = PixivRequestBuilder::ranking(RankingType::All)
+            .try_date("2018-2-22")
+            .unwrap()
+            .build();
+        assert!(request.url().query().unwrap().contains("date=2018-02-22"));
+
+        assert!(PixivRequestBuilder::ranking(RankingType::All)
+            .try_date("2018-02-30")
+            .is_err());
+        assert!(PixivRequestBuilder::ranking(RankingType::All)
+            .try_date(String::from("22/02/2018"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_try_build() {
+        assert!(PixivRequestBuilder::work(66024340).try_build().is_ok());
+        assert!(PixivRequestBuilder::user_works(6996493).page(0).try_build().is_err());
+        assert!(PixivRequestBuilder::following().per_page(0).try_build().is_err());
+        assert!(PixivRequestBuilder::search_works("  ").try_build().is_err());
+        assert!(PixivRequestBuilder::favorite_works_remove(Vec::<usize>::new()).try_build().is_err());
+    }
+
+    #[test]
+    fn test_form_body() {
+        let request = PixivRequestBuilder::favorite_work_add(66024340).build();
+        assert_eq!(request.url().query(), None);
+        let body = request.body().unwrap();
+        assert!(body.contains("work_id=66024340"));
+        assert!(body.contains("publicity=public"));
+        assert_eq!(
+            request.headers()[header::CONTENT_TYPE],
+            "application/x-www-form-urlencoded"
+        );
+
+        let request = PixivRequestBuilder::following_remove([1, 2, 3]).build();
+        assert!(request.body().unwrap().contains("delete_ids=1%2C2%2C3"));
+
+        let request = PixivRequestBuilder::work(66024340).build();
+        assert_eq!(request.body(), None);
+        assert!(request.url().query().unwrap().contains("include_stats=true"));
+    }
+
+    #[test]
+    fn test_image_sizes() {
+        let request = PixivRequestBuilder::following_works().build();
+        assert!(request.url().query().unwrap().contains("image_sizes=px_128x128%2Cpx_480mw%2Clarge"));
+
+        let request = PixivRequestBuilder::ranking(RankingType::Illust)
+            .image_sizes(&[ImageSize::Medium, ImageSize::Large])
+            .profile_image_sizes(&[ProfileImageSize::Px50x50])
+            .build();
+        let query = request.url().query().unwrap();
+        assert!(query.contains("image_sizes=medium%2Clarge"));
+        assert!(query.contains("profile_image_sizes=px_50x50"));
+    }
+
+    #[test]
+    fn test_params_order() {
+        let request = PixivRequestBuilder::work(66024340).build();
+        assert_eq!(
+            request.url().query(),
+            Some("image_sizes=px_128x128%2Csmall%2Cmedium%2Clarge%2Cpx_480mw&include_stats=true")
+        );
+
+        let request = PixivRequestBuilder::user_following(6996493)
+            .max_id(100)
+            .per_page(10)
+            .build();
+        assert_eq!(request.url().query(), Some("page=1&per_page=10&max_id=100"));
+    }
+
+    #[test]
+    fn test_flag_params() {
+        let request = PixivRequestBuilder::user(6996493).build();
+        assert_eq!(
+            request.url().query(),
+            Some("profile_image_sizes=px_170x170%2Cpx_50x50&image_sizes=px_128x128%2Csmall%2Cmedium%2Clarge%2Cpx_480mw\
+                  &include_stats=1&include_profile=1&include_workspace=1&include_contacts=1")
+        );
+
+        let request = PixivRequestBuilder::ranking(RankingType::Illust).build();
+        assert_eq!(
+            request.url().query(),
+            Some("mode=daily&page=1&per_page=50&include_stats=True&include_sanity_level=True\
+                  &image_sizes=px_128x128%2Csmall%2Cmedium%2Clarge%2Cpx_480mw&profile_image_sizes=px_170x170%2Cpx_50x50")
+        );
+        let request = PixivRequestBuilder::ranking(RankingType::Illust).include_stats(false).build();
+        assert!(request.url().query().unwrap().contains("&include_stats=False&"));
+
+        let request = PixivRequestBuilder::user_feed(6996493).show_r18(false).build();
+        assert!(request.url().query().unwrap().ends_with("&show_r18=0"));
+    }
+
+    #[test]
+    fn test_novel_params() {
+        let request = PixivRequestBuilder::novel(123).build();
+        assert_eq!(request.url().to_string(), "https://app-api.pixiv.net/v2/novel/detail?novel_id=123");
+
+        let request = PixivRequestBuilder::user_bookmarked_novels(6996493)
+            .publicity(Publicity::Private)
+            .bookmark_tag("お気に入り")
+            .build();
+        let query = request.url().query().unwrap();
+        assert!(query.contains("restrict=private"));
+        assert!(query.contains("tag=%E3%81%8A"));
+
+        let request = PixivRequestBuilder::novel_ranking()
+            .novel_ranking_mode(NovelRankingMode::WeekR18G)
+            .date("2018-02-22")
+            .offset(30)
+            .build();
+        assert_eq!(request.url().query(), Some("mode=week_r18g&date=2018-02-22&offset=30"));
+
+        let request = PixivRequestBuilder::search_novels("original")
+            .search_target(SearchTarget::ExactMatchForTags)
+            .date_range("2018-02-01", "2018-02-28")
+            .build();
+        assert_eq!(
+            request.url().query(),
+            Some("word=original&search_target=exact_match_for_tags&sort=date_desc&start_date=2018-02-01&end_date=2018-02-28&offset=0")
+        );
+        assert!(PixivRequestBuilder::search_novels("").try_build().is_err());
+    }
+
+    #[test]
+    fn test_series_params() {
+        let request = PixivRequestBuilder::illust_series(123).offset(30).build();
+        assert_eq!(
+            request.url().to_string(),
+            "https://app-api.pixiv.net/v1/illust/series?illust_series_id=123&offset=30"
+        );
+
+        let request = PixivRequestBuilder::manga_watchlist_remove(123).build();
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.url().path(), "/v1/watchlist/manga/delete");
+        assert_eq!(request.body(), Some("series_id=123"));
+    }
+
+    #[test]
+    fn test_comment_params() {
+        let request = PixivRequestBuilder::comments(123).offset(30).build();
+        assert_eq!(
+            request.url().to_string(),
+            "https://app-api.pixiv.net/v3/illust/comments?illust_id=123&offset=30"
+        );
+
+        let request = PixivRequestBuilder::comment_add(123, "nice & clean").reply_to(456).build();
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.url().path(), "/v1/illust/comment/add");
+        assert_eq!(request.body(), Some("illust_id=123&comment=nice+%26+clean&parent_comment_id=456"));
+
+        let request = PixivRequestBuilder::comment_add_stamp(123, 301).build();
+        assert_eq!(request.body(), Some("illust_id=123&stamp_id=301"));
+        assert!(PixivRequestBuilder::comment_add(123, " ").try_build().is_err());
+    }
+
+    #[test]
+    fn test_user_search_params() {
+        let request = PixivRequestBuilder::search_user("name & co").offset(30).build();
+        assert_eq!(
+            request.url().to_string(),
+            "https://app-api.pixiv.net/v1/search/user?word=name+%26+co&offset=30"
+        );
+        assert!(PixivRequestBuilder::search_user(" ").try_build().is_err());
+
+        let request = PixivRequestBuilder::user_recommended().build();
+        assert_eq!(request.url().to_string(), "https://app-api.pixiv.net/v1/user/recommended?offset=0");
+    }
+
+    #[test]
+    fn test_bookmark_params() {
+        let request = PixivRequestBuilder::illust_bookmark_add(123)
+            .bookmark_tags(vec!["a", "お気に入り"])
+            .publicity(Publicity::Private)
+            .build();
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(
+            request.body(),
+            Some("illust_id=123&restrict=private&tags%5B%5D=a+%E3%81%8A%E6%B0%97%E3%81%AB%E5%85%A5%E3%82%8A")
+        );
+        assert!(PixivRequestBuilder::illust_bookmark_add(123).bookmark_tags(vec!["a b"]).try_build().is_err());
+
+        let request = PixivRequestBuilder::illust_bookmark_edit(123, Vec::<String>::new(), Publicity::Public).build();
+        assert_eq!(request.body(), Some("illust_id=123&restrict=public"));
+
+        let request = PixivRequestBuilder::user_bookmarks(6996493)
+            .publicity(Publicity::Private)
+            .bookmark_tag("tag")
+            .max_bookmark_id(99)
+            .build();
+        assert_eq!(
+            request.url().to_string(),
+            "https://app-api.pixiv.net/v1/user/bookmarks/illust?user_id=6996493&restrict=private&max_bookmark_id=99&tag=tag"
+        );
+
+        let request = PixivRequestBuilder::user_bookmark_tags(6996493).offset(30).build();
+        assert_eq!(request.url().query(), Some("user_id=6996493&restrict=public&offset=30"));
+    }
+
+    #[test]
+    fn test_search_params() {
+        let request = PixivRequestBuilder::search_works("original")
+            .search_sort(SearchSort::PopularFemale)
+            .search_types(&[WorkType::Illustration, WorkType::Ugoira])
+            .build();
+        let query = request.url().query().unwrap();
+        assert!(query.contains("sort=popular_female"));
+        assert!(query.contains("types=illustration%2Cugoira"));
+    }
 }
@@ -0,0 +1,1206 @@
+//! Typed models for responses from the Pixiv API.
+//!
+//! These can be used in place of `serde_json::Value` when parsing a response:
+//!
+//! ```rust,no_run
+//! # extern crate pixiv;
+//! # extern crate reqwest;
+//! # use pixiv::client::Pixiv;
+//! # use pixiv::models::{PixivResponse, Work};
+//! # use pixiv::{ImageSize, PixivRequestBuilder};
+//! # use reqwest::Client;
+//! # fn main() {
+//! #   let client = Client::new();
+//! #   let mut pixiv: Pixiv = Pixiv::new(&client);
+//! #   pixiv.login("username", "password");
+//!     let request = PixivRequestBuilder::work(66024340).build();
+//!     let work: PixivResponse<Work> = pixiv
+//!         .execute(request)
+//!         .expect("Request failed.")
+//!         .json()
+//!         .expect("Failed to parse as json.");
+//!
+//!     let large = work.response[0].image_url(ImageSize::Large);
+//! # }
+//! ```
+//!
+//! Fields which Pixiv doesn't always include are wrapped in an `Option`.
+
+use std::collections::btree_map::{self, BTreeMap};
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::de::{self, Deserialize, DeserializeOwned, Deserializer};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{self, Value};
+use serde_urlencoded;
+
+use super::{ImageSize, ProfileImageSize, Publicity, WorkType};
+use novel::NovelText;
+use text;
+
+/// A response from the Pixiv API, wrapping a list of `T`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PixivResponse<T> {
+    pub status: String,
+    pub response: Vec<T>,
+    #[serde(default)]
+    pub count: Option<usize>,
+    #[serde(default)]
+    pub pagination: Option<Pagination>,
+}
+
+/// Pagination info of a `PixivResponse`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pagination {
+    #[serde(default)]
+    pub previous: Option<usize>,
+    #[serde(default)]
+    pub next: Option<usize>,
+    #[serde(default)]
+    pub current: Option<usize>,
+    #[serde(default)]
+    pub per_page: Option<usize>,
+    #[serde(default)]
+    pub total: Option<usize>,
+    #[serde(default)]
+    pub pages: Option<usize>,
+}
+
+/// A size which can be used as a key of `SizedUrls`, i.e. `ImageSize` or `ProfileImageSize`.
+pub trait SizeKey: Copy + Ord {
+    /// Get the name of the size as used by the API.
+    fn key(&self) -> &'static str;
+    /// Parse the name of a size as used by the API.
+    fn from_key(key: &str) -> Option<Self>;
+}
+
+impl SizeKey for ImageSize {
+    #[inline]
+    fn key(&self) -> &'static str {
+        self.as_str()
+    }
+    #[inline]
+    fn from_key(key: &str) -> Option<Self> {
+        ImageSize::from_str(key)
+    }
+}
+
+impl SizeKey for ProfileImageSize {
+    #[inline]
+    fn key(&self) -> &'static str {
+        self.as_str()
+    }
+    #[inline]
+    fn from_key(key: &str) -> Option<Self> {
+        ProfileImageSize::from_str(key)
+    }
+}
+
+/// URLs of an image, keyed by its size. Sizes unknown to this crate are skipped when parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizedUrls<S: SizeKey> {
+    urls: BTreeMap<S, String>,
+}
+
+/// URLs of a work's image, keyed by `ImageSize`.
+pub type ImageUrls = SizedUrls<ImageSize>;
+/// URLs of a user's profile image, keyed by `ProfileImageSize`.
+pub type ProfileImageUrls = SizedUrls<ProfileImageSize>;
+
+impl<S: SizeKey> SizedUrls<S> {
+    /// Get the url of the given size, if it was included in the response.
+    #[inline]
+    pub fn get(&self, size: S) -> Option<&str> {
+        self.urls.get(&size).map(String::as_str)
+    }
+    /// Insert the url of the given size, returning the previous one.
+    #[inline]
+    pub fn insert(&mut self, size: S, url: String) -> Option<String> {
+        self.urls.insert(size, url)
+    }
+    /// Iterate over all sizes and their urls.
+    #[inline]
+    pub fn iter(&self) -> SizedUrlsIter<'_, S> {
+        SizedUrlsIter {
+            inner: self.urls.iter(),
+        }
+    }
+    /// Get the amount of urls.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+    /// Returns `true` if there are no urls.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+}
+
+impl<S: SizeKey> Default for SizedUrls<S> {
+    #[inline]
+    fn default() -> Self {
+        SizedUrls {
+            urls: BTreeMap::new(),
+        }
+    }
+}
+
+/// Iterator over the urls of `SizedUrls`.
+pub struct SizedUrlsIter<'a, S: 'a> {
+    inner: btree_map::Iter<'a, S, String>,
+}
+
+impl<'a, S: SizeKey> Iterator for SizedUrlsIter<'a, S> {
+    type Item = (S, &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(size, url)| (*size, url.as_str()))
+    }
+}
+
+impl<'a, S: SizeKey> IntoIterator for &'a SizedUrls<S> {
+    type Item = (S, &'a str);
+    type IntoIter = SizedUrlsIter<'a, S>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<S: SizeKey> Serialize for SizedUrls<S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut map = serializer.serialize_map(Some(self.urls.len()))?;
+        for (size, url) in &self.urls {
+            map.serialize_entry(size.key(), url)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, S: SizeKey> Deserialize<'de> for SizedUrls<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw: Option<BTreeMap<String, Option<String>>> = Deserialize::deserialize(deserializer)?;
+        let urls = raw
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(key, url)| match (S::from_key(&key), url) {
+                (Some(size), Some(url)) => Some((size, url)),
+                _ => None,
+            })
+            .collect();
+        Ok(SizedUrls { urls })
+    }
+}
+
+/// A work (illustration, manga or ugoira).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Work {
+    pub id: usize,
+    pub title: String,
+    #[serde(default)]
+    pub caption: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tags: Vec<Tag>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub image_urls: ImageUrls,
+    #[serde(default)]
+    pub width: Option<usize>,
+    #[serde(default)]
+    pub height: Option<usize>,
+    #[serde(default)]
+    pub stats: Option<WorkStats>,
+    #[serde(default)]
+    pub publicity: Option<u32>,
+    #[serde(default)]
+    pub age_limit: Option<String>,
+    #[serde(default, with = "timestamp", alias = "create_date")]
+    pub created_time: Option<NaiveDateTime>,
+    #[serde(default, with = "timestamp")]
+    pub reuploaded_time: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub user: Option<User>,
+    #[serde(default)]
+    pub is_manga: Option<bool>,
+    #[serde(default)]
+    pub is_liked: Option<bool>,
+    #[serde(default)]
+    pub favorite_id: Option<usize>,
+    #[serde(default)]
+    pub page_count: Option<usize>,
+    #[serde(default)]
+    pub book_style: Option<String>,
+    /// `None` for types `WorkType` doesn't know.
+    #[serde(rename = "type", default, deserialize_with = "unknown_as_none")]
+    pub work_type: Option<WorkType>,
+    /// A name such as `white`, or a number from the App API, where `2` is all-ages and higher is more suggestive.
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub sanity_level: Option<String>,
+    /// `1` for R-18 and `2` for R-18G works, otherwise `0`. Only sent by some endpoints.
+    #[serde(default)]
+    pub x_restrict: Option<u32>,
+    /// `2` for AI-generated works, `1` for others and `0` if unknown. Only sent by some endpoints.
+    #[serde(default, alias = "illust_ai_type")]
+    pub ai_type: Option<u32>,
+    #[serde(default)]
+    pub metadata: Option<WorkMetadata>,
+    /// The series the work belongs to. Only sent by the App API.
+    #[serde(default, deserialize_with = "non_empty")]
+    pub series: Option<SeriesSummary>,
+}
+
+impl Work {
+    /// Get the url of the work's image in the given size, if it was requested.
+    #[inline]
+    pub fn image_url(&self, size: ImageSize) -> Option<&str> {
+        self.image_urls.get(size)
+    }
+}
+
+/// A tag of a `Work`. The public API sends bare names, the App API sends objects with a `translated_name`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Tag {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_name: Option<String>,
+}
+
+impl Tag {
+    /// Creates a tag without a translation.
+    pub fn new<S: Into<String>>(name: S) -> Tag {
+        Tag { name: name.into(), translated_name: None }
+    }
+
+    /// Get the translated name if there is one, otherwise the original.
+    #[inline]
+    pub fn display_name(&self) -> &str {
+        self.translated_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Get the name normalised with `text::normalize`, for grouping and comparison.
+    pub fn normalized(&self) -> String {
+        text::normalize(&self.name)
+    }
+
+    /// Whether `name` is this tag's original or translated name, once both are normalised.
+    pub fn matches(&self, name: &str) -> bool {
+        let name = text::normalize(name);
+        self.normalized() == name
+            || self.translated_name.as_ref().is_some_and(|translated| text::normalize(translated) == name)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Name(String),
+            Entry {
+                name: String,
+                #[serde(default)]
+                translated_name: Option<String>,
+            },
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::Name(name) => Tag::new(name),
+            Raw::Entry { name, translated_name } => Tag {
+                name,
+                translated_name: translated_name.filter(|translated| !translated.is_empty()),
+            },
+        })
+    }
+}
+
+/// Stats of a `Work`. Only included when requested with `include_stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkStats {
+    #[serde(default)]
+    pub scored_count: Option<usize>,
+    #[serde(default)]
+    pub score: Option<usize>,
+    #[serde(default)]
+    pub views_count: Option<usize>,
+    #[serde(default)]
+    pub favorited_count: Option<FavoritedCount>,
+    #[serde(default)]
+    pub commented_count: Option<usize>,
+}
+
+/// A work favorited on your account, as returned by `favorite_works()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavoriteWork {
+    /// The id of the favorite, which `favorite_works_remove()` takes.
+    pub id: usize,
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// The bookmark tags.
+    #[serde(default, deserialize_with = "nullable")]
+    pub tags: Vec<String>,
+    pub publicity: Publicity,
+    pub work: Work,
+}
+
+/// Amount of times a `Work` has been favorited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FavoritedCount {
+    #[serde(default)]
+    pub public: usize,
+    #[serde(default)]
+    pub private: usize,
+}
+
+/// Metadata of a `Work`, which includes its pages in the case of manga.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkMetadata {
+    #[serde(default, deserialize_with = "nullable")]
+    pub pages: Vec<WorkPage>,
+}
+
+/// A page of a multi-page `Work`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkPage {
+    #[serde(default)]
+    pub image_urls: ImageUrls,
+}
+
+/// A user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: usize,
+    #[serde(default)]
+    pub account: String,
+    #[serde(default)]
+    pub name: String,
+    /// Whether you follow the user. The App API calls this `is_followed`.
+    #[serde(default, alias = "is_followed")]
+    pub is_following: Option<bool>,
+    #[serde(default)]
+    pub is_follower: Option<bool>,
+    #[serde(default)]
+    pub is_friend: Option<bool>,
+    #[serde(default)]
+    pub is_premium: Option<bool>,
+    #[serde(default)]
+    pub profile_image_urls: ProfileImageUrls,
+    #[serde(default)]
+    pub stats: Option<UserStats>,
+    #[serde(default)]
+    pub profile: Option<Value>,
+}
+
+impl User {
+    /// Get the url of the user's profile image in the given size, if it was requested.
+    #[inline]
+    pub fn profile_image_url(&self, size: ProfileImageSize) -> Option<&str> {
+        self.profile_image_urls.get(size)
+    }
+}
+
+/// A ranking of works for a single date, as returned by `ranking()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ranking {
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub works: Vec<RankedWork>,
+}
+
+/// A work in a `Ranking`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedWork {
+    pub rank: usize,
+    /// The rank of the work on the previous date, or `0` if it wasn't ranked.
+    #[serde(default)]
+    pub previous_rank: usize,
+    pub work: Work,
+}
+
+/// Stats of a `User`. Only included when requested with `include_stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserStats {
+    #[serde(default)]
+    pub works: Option<usize>,
+    #[serde(default)]
+    pub favorites: Option<usize>,
+    #[serde(default)]
+    pub following: Option<usize>,
+    #[serde(default)]
+    pub friends: Option<usize>,
+}
+
+/// An entry of the `bad_words()` response. Pixiv has sent both bare strings and objects with a `word`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BadWord {
+    pub word: String,
+}
+
+impl<'de> Deserialize<'de> for BadWord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Word(String),
+            Entry { word: String },
+        }
+
+        let word = match Raw::deserialize(deserializer)? {
+            Raw::Word(word) | Raw::Entry { word } => word,
+        };
+        Ok(BadWord { word })
+    }
+}
+
+/// A novel, as sent by the App API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Novel {
+    pub id: usize,
+    pub title: String,
+    #[serde(default)]
+    pub caption: String,
+    /// `0` for public, `1` for my pixiv only and `2` for private novels.
+    #[serde(default)]
+    pub restrict: u32,
+    /// `1` for R-18 and `2` for R-18G novels, otherwise `0`.
+    #[serde(default)]
+    pub x_restrict: u32,
+    #[serde(default)]
+    pub is_original: bool,
+    /// Cover image urls keyed by size, e.g. `square_medium`, `medium` and `large`.
+    #[serde(default, deserialize_with = "nullable")]
+    pub image_urls: BTreeMap<String, String>,
+    #[serde(default, with = "timestamp")]
+    pub create_date: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub page_count: usize,
+    #[serde(default)]
+    pub text_length: usize,
+    #[serde(default)]
+    pub user: Option<User>,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub series: Option<SeriesSummary>,
+    #[serde(default)]
+    pub total_bookmarks: usize,
+    #[serde(default)]
+    pub total_view: usize,
+    #[serde(default)]
+    pub total_comments: usize,
+    #[serde(default)]
+    pub is_bookmarked: bool,
+    #[serde(default)]
+    pub visible: Option<bool>,
+    /// `2` for AI-generated novels, `1` for others and `0` if unknown.
+    #[serde(default, rename = "novel_ai_type")]
+    pub ai_type: Option<u32>,
+}
+
+/// The series a `Novel` or `Work` belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeriesSummary {
+    pub id: usize,
+    #[serde(default)]
+    pub title: String,
+}
+
+/// A novel series, as in `NovelSeriesResponse`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NovelSeries {
+    pub id: usize,
+    pub title: String,
+    #[serde(default)]
+    pub caption: String,
+    #[serde(default)]
+    pub is_original: bool,
+    #[serde(default)]
+    pub is_concluded: bool,
+    #[serde(default)]
+    pub content_count: usize,
+    #[serde(default)]
+    pub total_character_count: usize,
+    #[serde(default)]
+    pub user: Option<User>,
+}
+
+/// The response of `novel()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NovelDetail {
+    pub novel: Novel,
+}
+
+/// The response of `novel_text()`. Use `parse()` to read the markup in `novel_text`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NovelTextResponse {
+    pub novel_text: String,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub series_prev: Option<Novel>,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub series_next: Option<Novel>,
+}
+
+impl NovelTextResponse {
+    /// Parses the body text into pages, chapters, ruby and embedded images.
+    pub fn parse(&self) -> NovelText {
+        NovelText::parse(&self.novel_text)
+    }
+}
+
+/// The response of `novel_series()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NovelSeriesResponse {
+    pub novel_series_detail: NovelSeries,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub novel_series_first_novel: Option<Novel>,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub novel_series_latest_novel: Option<Novel>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub novels: Vec<Novel>,
+    #[serde(default)]
+    pub next_url: Option<String>,
+}
+
+impl NovelSeriesResponse {
+    /// Get a query param of `next_url`, e.g. `last_order` for the next request.
+    pub fn next_param(&self, name: &str) -> Option<String> {
+        next_param(self.next_url.as_deref(), name)
+    }
+}
+
+/// The response of `user_novels()`, `user_bookmarked_novels()`, `novel_ranking()` and `search_novels()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NovelList {
+    #[serde(default, deserialize_with = "nullable")]
+    pub novels: Vec<Novel>,
+    #[serde(default)]
+    pub next_url: Option<String>,
+}
+
+impl NovelList {
+    /// Get a query param of `next_url`, e.g. `offset` or `max_bookmark_id` for the next request.
+    pub fn next_param(&self, name: &str) -> Option<String> {
+        next_param(self.next_url.as_deref(), name)
+    }
+}
+
+/// The response of `illust_detail()`. The App API's illusts parse as a `Work`, including its `series`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IllustDetail {
+    pub illust: Work,
+}
+
+/// An illustration or manga series.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IllustSeries {
+    pub id: usize,
+    pub title: String,
+    #[serde(default)]
+    pub caption: String,
+    /// Cover image urls keyed by size, e.g. `medium`.
+    #[serde(default, deserialize_with = "nullable")]
+    pub cover_image_urls: BTreeMap<String, String>,
+    #[serde(default)]
+    pub series_work_count: usize,
+    #[serde(default, with = "timestamp")]
+    pub create_date: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub user: Option<User>,
+    /// Whether the series is on your manga watchlist.
+    #[serde(default)]
+    pub watchlist_added: bool,
+}
+
+/// The response of `illust_series()`. `illusts` is one page of the series' works.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IllustSeriesResponse {
+    pub illust_series_detail: IllustSeries,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub illust_series_first_illust: Option<Work>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub illusts: Vec<Work>,
+    #[serde(default)]
+    pub next_url: Option<String>,
+}
+
+impl IllustSeriesResponse {
+    /// Get a query param of `next_url`, e.g. `offset` for the next request.
+    pub fn next_param(&self, name: &str) -> Option<String> {
+        next_param(self.next_url.as_deref(), name)
+    }
+}
+
+/// A series on your manga watchlist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistSeries {
+    pub id: usize,
+    pub title: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub published_content_count: usize,
+    #[serde(default, with = "timestamp")]
+    pub last_published_content_datetime: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub latest_content_id: Option<usize>,
+    #[serde(default)]
+    pub user: Option<User>,
+}
+
+/// The response of `manga_watchlist()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MangaWatchlist {
+    #[serde(default, deserialize_with = "nullable")]
+    pub series: Vec<WatchlistSeries>,
+    #[serde(default)]
+    pub next_url: Option<String>,
+}
+
+impl MangaWatchlist {
+    /// Get a query param of `next_url`, e.g. `offset` for the next request.
+    pub fn next_param(&self, name: &str) -> Option<String> {
+        next_param(self.next_url.as_deref(), name)
+    }
+}
+
+/// A comment on a work, as sent by the App API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: usize,
+    /// The text of the comment, with emoji written as e.g. `(happy)`. Empty for stamps.
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default, with = "timestamp")]
+    pub date: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub user: Option<User>,
+    /// Whether the comment has replies, which `comment_replies()` returns.
+    #[serde(default)]
+    pub has_replies: bool,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub stamp: Option<Stamp>,
+    /// The comment this one replies to. Only sent by some endpoints.
+    #[serde(default, deserialize_with = "non_empty")]
+    pub parent_comment: Option<Box<Comment>>,
+}
+
+impl Comment {
+    /// Whether the comment is a stamp rather than text.
+    #[inline]
+    pub fn is_stamp(&self) -> bool {
+        self.stamp.is_some()
+    }
+    /// Get the names of the emoji in the comment, in order, e.g. `happy` for `(happy)`.
+    /// Emoji are lowercase ASCII names in parentheses, optionally ending in a digit.
+    pub fn emoji(&self) -> Vec<&str> {
+        self.comment
+            .split('(')
+            .skip(1)
+            .filter_map(|part| part.split_once(')').map(|(name, _)| name))
+            .filter(|name| {
+                let letters = name.trim_end_matches(|c: char| c.is_ascii_digit());
+                !letters.is_empty()
+                    && name.len() - letters.len() <= 1
+                    && letters.bytes().all(|b| b.is_ascii_lowercase())
+            })
+            .collect()
+    }
+}
+
+/// A stamp posted as a comment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stamp {
+    pub stamp_id: usize,
+    #[serde(default)]
+    pub stamp_url: String,
+}
+
+/// The response of `comments()` and `comment_replies()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentList {
+    #[serde(default, deserialize_with = "nullable")]
+    pub comments: Vec<Comment>,
+    /// The number of comments on the work. Not sent for replies.
+    #[serde(default)]
+    pub total_comments: Option<usize>,
+    /// Who may comment on the work: `0` for everyone, `1` for users the author follows.
+    #[serde(default)]
+    pub comment_access_control: Option<u32>,
+    #[serde(default)]
+    pub next_url: Option<String>,
+}
+
+impl CommentList {
+    /// Get a query param of `next_url`, e.g. `offset` for the next request.
+    pub fn next_param(&self, name: &str) -> Option<String> {
+        next_param(self.next_url.as_deref(), name)
+    }
+}
+
+/// The response of `comment_add()` and `comment_add_stamp()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentAdded {
+    pub comment: Comment,
+}
+
+/// A user with a few of their latest works, as returned by `search_user()` and `user_recommended()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserPreview {
+    pub user: User,
+    #[serde(default, deserialize_with = "nullable")]
+    pub illusts: Vec<Work>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub novels: Vec<Novel>,
+    /// Whether you've muted the user.
+    #[serde(default)]
+    pub is_muted: bool,
+}
+
+/// The response of `search_user()` and `user_recommended()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserPreviews {
+    #[serde(default, deserialize_with = "nullable")]
+    pub user_previews: Vec<UserPreview>,
+    #[serde(default)]
+    pub next_url: Option<String>,
+}
+
+impl UserPreviews {
+    /// Get a query param of `next_url`, e.g. `offset` for the next request.
+    pub fn next_param(&self, name: &str) -> Option<String> {
+        next_param(self.next_url.as_deref(), name)
+    }
+}
+
+/// How you bookmarked a work, as returned by `illust_bookmark_detail()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkDetail {
+    pub is_bookmarked: bool,
+    /// Every bookmark tag you've used, with whether it's on this bookmark.
+    #[serde(default, deserialize_with = "nullable")]
+    pub tags: Vec<BookmarkDetailTag>,
+    /// The publicity of the bookmark, or of new bookmarks if the work isn't bookmarked.
+    #[serde(rename = "restrict")]
+    pub publicity: Publicity,
+}
+
+impl BookmarkDetail {
+    /// Get the names of the tags on this bookmark.
+    pub fn tag_names(&self) -> Vec<&str> {
+        self.tags.iter().filter(|tag| tag.is_registered).map(|tag| tag.name.as_str()).collect()
+    }
+}
+
+/// A bookmark tag in a `BookmarkDetail`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkDetailTag {
+    pub name: String,
+    /// Whether the tag is on the bookmark.
+    #[serde(default)]
+    pub is_registered: bool,
+}
+
+/// The response of `illust_bookmark_detail()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkDetailResponse {
+    pub bookmark_detail: BookmarkDetail,
+}
+
+/// A bookmark tag and how many bookmarks it's on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkTag {
+    pub name: String,
+    #[serde(default)]
+    pub count: usize,
+}
+
+/// The response of `user_bookmark_tags()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkTags {
+    #[serde(default, deserialize_with = "nullable")]
+    pub bookmark_tags: Vec<BookmarkTag>,
+    #[serde(default)]
+    pub next_url: Option<String>,
+}
+
+impl BookmarkTags {
+    /// Get a query param of `next_url`, e.g. `offset` for the next request.
+    pub fn next_param(&self, name: &str) -> Option<String> {
+        next_param(self.next_url.as_deref(), name)
+    }
+}
+
+/// A page of works from the App API, e.g. the response of `user_bookmarks()`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IllustList {
+    #[serde(default, deserialize_with = "nullable")]
+    pub illusts: Vec<Work>,
+    #[serde(default)]
+    pub next_url: Option<String>,
+}
+
+impl IllustList {
+    /// Get a query param of `next_url`, e.g. `max_bookmark_id` for the next request.
+    pub fn next_param(&self, name: &str) -> Option<String> {
+        next_param(self.next_url.as_deref(), name)
+    }
+}
+
+fn next_param(next_url: Option<&str>, name: &str) -> Option<String> {
+    let query = next_url?.split_once('?')?.1;
+    serde_urlencoded::from_str::<Vec<(String, String)>>(query)
+        .ok()?
+        .into_iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value)
+}
+
+// Accepts a number where a string is expected, for fields the App API sends as numbers.
+fn string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::String(value) => Ok(Some(value)),
+        Value::Number(value) => Ok(Some(value.to_string())),
+        value => Err(de::Error::custom(format!("expected a string or number, found {}", value))),
+    }
+}
+
+// Treats `null` and `{}` as a missing object, which the App API sends in place of one.
+fn non_empty<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::Object(ref map) if map.is_empty() => Ok(None),
+        value => serde_json::from_value(value).map(Some).map_err(de::Error::custom),
+    }
+}
+
+// Treats values `T` can't be parsed from, e.g. a variant added after this crate, as missing.
+fn unknown_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    Ok(serde_json::from_value(Value::deserialize(deserializer)?).ok())
+}
+
+// Treats `null` the same as a missing field.
+fn nullable<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+// Pixiv sends timestamps in JST either as `%Y-%m-%d %H:%M:%S` or as RFC 3339.
+mod timestamp {
+    use super::*;
+
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<NaiveDateTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match *value {
+            Some(ref time) => serializer.collect_str(&time.format(FORMAT)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<NaiveDateTime>, D::Error> {
+        let value: Option<String> = Deserialize::deserialize(deserializer)?;
+        match value {
+            Some(ref value) if !value.is_empty() => parse(value).map(Some).map_err(de::Error::custom),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<NaiveDateTime, String> {
+        NaiveDateTime::parse_from_str(value, FORMAT)
+            .or_else(|_| DateTime::parse_from_rfc3339(value).map(|time| time.naive_local()))
+            .map_err(|e| format!("Invalid timestamp {:?}: {}", value, e))
+    }
+}
+
+impl fmt::Display for Work {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.title, self.id)
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.translated_name {
+            Some(ref translated) => write!(f, "{} ({})", self.name, translated),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl fmt::Display for Novel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.title, self.id)
+    }
+}
+
+impl fmt::Display for User {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+
+    use super::*;
+
+    const WORK_RESPONSE: &str = r#"{
+        "status": "success",
+        "response": [{
+            "id": 66024340,
+            "title": "title",
+            "caption": null,
+            "tags": ["tag"],
+            "tools": null,
+            "image_urls": {
+                "px_128x128": "https://i.pximg.net/128.jpg",
+                "px_480mw": "https://i.pximg.net/480mw.jpg",
+                "large": "https://i.pximg.net/large.jpg",
+                "px_9999x9999": "https://i.pximg.net/unknown.jpg"
+            },
+            "stats": {
+                "scored_count": 1,
+                "score": 10,
+                "views_count": 100,
+                "favorited_count": {"public": 2, "private": 3},
+                "commented_count": 0
+            },
+            "created_time": "2017-11-26 00:00:11",
+            "reuploaded_time": null,
+            "user": {
+                "id": 6996493,
+                "account": "account",
+                "name": "name",
+                "profile_image_urls": {"px_50x50": "https://i.pximg.net/50.jpg"}
+            },
+            "page_count": 1,
+            "type": "illustration",
+            "sanity_level": "white",
+            "metadata": null
+        }],
+        "count": 1
+    }"#;
+
+    #[test]
+    fn test_work_response() {
+        let response: PixivResponse<Work> = serde_json::from_str(WORK_RESPONSE).unwrap();
+        let work = &response.response[0];
+
+        assert_eq!(work.id, 66024340);
+        assert_eq!(work.work_type, Some(WorkType::Illustration));
+        assert!(work.tools.is_empty());
+        assert_eq!(work.image_urls.len(), 3);
+        assert_eq!(
+            work.image_url(ImageSize::Px480mw),
+            Some("https://i.pximg.net/480mw.jpg")
+        );
+        assert_eq!(work.image_url(ImageSize::Medium), None);
+        assert_eq!(
+            work.created_time.unwrap().to_string(),
+            "2017-11-26 00:00:11"
+        );
+        assert_eq!(work.stats.as_ref().unwrap().favorited_count.unwrap().private, 3);
+
+        let user = work.user.as_ref().unwrap();
+        assert_eq!(
+            user.profile_image_url(ProfileImageSize::Px50x50),
+            Some("https://i.pximg.net/50.jpg")
+        );
+        assert_eq!(user.profile_image_url(ProfileImageSize::Px170x170), None);
+    }
+
+    #[test]
+    fn test_unknown_work_type() {
+        let response: PixivResponse<Work> = serde_json::from_str(&WORK_RESPONSE.replace("\"illustration\"", "\"webtoon\"")).unwrap();
+        assert_eq!(response.response[0].work_type, None);
+        assert_eq!(response.response[0].id, 66024340);
+    }
+
+    #[test]
+    fn test_tags() {
+        let work: Work = serde_json::from_str(r#"{
+            "id": 1,
+            "title": "title",
+            "tags": ["R-18", {"name": "オリジナル", "translated_name": "original"}, {"name": "風景", "translated_name": ""}]
+        }"#).unwrap();
+        assert_eq!(work.tags[0], Tag::new("R-18"));
+        assert_eq!(work.tags[1].display_name(), "original");
+        assert_eq!(work.tags[1].to_string(), "オリジナル (original)");
+        assert!(work.tags[1].matches("ORIGINAL"));
+        assert!(work.tags[2].translated_name.is_none());
+        assert!(Tag::new("Ｆａｔｅ").matches("fate"));
+    }
+
+    #[test]
+    fn test_novel_responses() {
+        let detail: NovelDetail = serde_json::from_str(r#"{"novel": {
+            "id": 1,
+            "title": "novel",
+            "caption": "",
+            "restrict": 0,
+            "x_restrict": 0,
+            "is_original": true,
+            "image_urls": {"square_medium": "https://i.pximg.net/sq.jpg", "medium": "https://i.pximg.net/m.jpg"},
+            "create_date": "2018-02-22T12:00:00+09:00",
+            "tags": [{"name": "オリジナル", "translated_name": "original", "added_by_uploaded_user": true}],
+            "page_count": 2,
+            "text_length": 1000,
+            "user": {"id": 2, "name": "name", "account": "account", "profile_image_urls": {"medium": "https://i.pximg.net/u.jpg"}},
+            "series": {},
+            "total_bookmarks": 3,
+            "total_view": 4,
+            "novel_ai_type": 1
+        }}"#).unwrap();
+        let novel = &detail.novel;
+        assert_eq!(novel.image_urls["square_medium"], "https://i.pximg.net/sq.jpg");
+        assert_eq!(novel.create_date.unwrap().to_string(), "2018-02-22 12:00:00");
+        assert_eq!(novel.tags[0].display_name(), "original");
+        assert!(novel.series.is_none());
+        assert_eq!(novel.ai_type, Some(1));
+
+        let list: NovelList = serde_json::from_str(r#"{
+            "novels": [{"id": 1, "title": "a", "series": {"id": 5, "title": "series"}}],
+            "next_url": "https://app-api.pixiv.net/v1/user/bookmarks/novel?user_id=2&restrict=public&max_bookmark_id=99"
+        }"#).unwrap();
+        assert_eq!(list.novels[0].series.as_ref().unwrap().id, 5);
+        assert_eq!(list.next_param("max_bookmark_id").as_deref(), Some("99"));
+        assert_eq!(list.next_param("offset"), None);
+
+        let text: NovelTextResponse = serde_json::from_str(r#"{
+            "novel_text": "one[newpage]two",
+            "series_prev": {},
+            "series_next": {"id": 3, "title": "next"}
+        }"#).unwrap();
+        assert!(text.series_prev.is_none());
+        assert_eq!(text.series_next.as_ref().unwrap().id, 3);
+        assert_eq!(text.parse().pages.len(), 2);
+    }
+
+    #[test]
+    fn test_comments() {
+        let list: CommentList = serde_json::from_str(r#"{
+            "total_comments": 2,
+            "comments": [
+                {
+                    "id": 10,
+                    "comment": "(happy) nice (not emoji) (love2)",
+                    "date": "2018-02-22T12:00:00+09:00",
+                    "user": {"id": 2, "name": "name", "account": "account", "profile_image_urls": {"medium": "https://i.pximg.net/u.jpg"}},
+                    "has_replies": true,
+                    "stamp": null
+                },
+                {"id": 11, "comment": "", "stamp": {"stamp_id": 301, "stamp_url": "https://s.pximg.net/301.jpg"}}
+            ],
+            "next_url": "https://app-api.pixiv.net/v3/illust/comments?illust_id=1&offset=30",
+            "comment_access_control": 0
+        }"#).unwrap();
+        let text = &list.comments[0];
+        assert_eq!(text.date.unwrap().to_string(), "2018-02-22 12:00:00");
+        assert_eq!(text.user.as_ref().unwrap().id, 2);
+        assert!(text.has_replies && !text.is_stamp());
+        assert_eq!(text.emoji(), vec!["happy", "love2"]);
+        assert_eq!(list.comments[1].stamp.as_ref().unwrap().stamp_id, 301);
+        assert_eq!(list.next_param("offset").as_deref(), Some("30"));
+    }
+
+    #[test]
+    fn test_user_previews() {
+        let previews: UserPreviews = serde_json::from_str(r#"{
+            "user_previews": [{
+                "user": {"id": 2, "name": "name", "account": "account", "is_followed": true},
+                "illusts": [{"id": 10, "title": "latest", "type": "illust", "sanity_level": 2}],
+                "novels": null,
+                "is_muted": false
+            }],
+            "next_url": "https://app-api.pixiv.net/v1/user/recommended?offset=30"
+        }"#).unwrap();
+        let preview = &previews.user_previews[0];
+        assert_eq!(preview.user.account, "account");
+        assert_eq!(preview.user.is_following, Some(true));
+        assert_eq!(preview.illusts[0].id, 10);
+        assert!(preview.novels.is_empty());
+        assert_eq!(previews.next_param("offset").as_deref(), Some("30"));
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let detail: BookmarkDetailResponse = serde_json::from_str(r#"{"bookmark_detail": {
+            "is_bookmarked": true,
+            "tags": [{"name": "a", "is_registered": true}, {"name": "b", "is_registered": false}],
+            "restrict": "private"
+        }}"#).unwrap();
+        let detail = detail.bookmark_detail;
+        assert_eq!(detail.publicity, Publicity::Private);
+        assert_eq!(detail.tag_names(), vec!["a"]);
+
+        let tags: BookmarkTags = serde_json::from_str(r#"{
+            "bookmark_tags": [{"name": "a", "count": 3}],
+            "next_url": "https://app-api.pixiv.net/v1/user/bookmark-tags/illust?user_id=1&restrict=public&offset=30"
+        }"#).unwrap();
+        assert_eq!(tags.bookmark_tags[0], BookmarkTag { name: "a".to_owned(), count: 3 });
+        assert_eq!(tags.next_param("offset").as_deref(), Some("30"));
+
+        let list: IllustList = serde_json::from_str(r#"{"illusts": [{"id": 1, "title": "a"}], "next_url": null}"#).unwrap();
+        assert_eq!(list.illusts[0].id, 1);
+        assert_eq!(list.next_param("max_bookmark_id"), None);
+    }
+
+    #[test]
+    fn test_ranking_response() {
+        let json = r#"{
+            "status": "success",
+            "response": [{
+                "content": "all",
+                "mode": "daily",
+                "date": "2018-02-22",
+                "works": [
+                    {"rank": 1, "previous_rank": 0, "work": {"id": 1, "title": "first"}},
+                    {"rank": 2, "previous_rank": 1, "work": {"id": 2, "title": "second"}}
+                ]
+            }]
+        }"#;
+        let response: PixivResponse<Ranking> = serde_json::from_str(json).unwrap();
+        let ranking = &response.response[0];
+
+        assert_eq!(ranking.date, NaiveDate::from_ymd_opt(2018, 2, 22));
+        assert_eq!(ranking.works[0].previous_rank, 0);
+        assert_eq!(ranking.works[1].work.title, "second");
+    }
+
+    #[test]
+    fn test_work_roundtrip() {
+        let response: PixivResponse<Work> = serde_json::from_str(WORK_RESPONSE).unwrap();
+        let json = serde_json::to_string(&response.response[0]).unwrap();
+        let work: Work = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(work.image_urls, response.response[0].image_urls);
+        assert_eq!(work.created_time, response.response[0].created_time);
+    }
+}
@@ -0,0 +1,301 @@
+//! Pixiv's markup for novel body text, as returned by `novel_text()`.
+//!
+//! Novels are plain text with a few inline tags:
+//!
+//! * `[newpage]` starts a new page.
+//! * `[chapter:title]` starts a chapter.
+//! * `[[rb:base > reading]]` is ruby, i.e. a reading shown over the base text.
+//! * `[pixivimage:id]` and `[pixivimage:id-page]` embed an illustration, `[uploadedimage:id]` an uploaded image.
+//! * `[jump:page]` links to a page, `[[jumpuri:text > uri]]` to a url.
+//!
+//! Anything else in brackets is kept as text.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # use pixiv::novel::{Element, NovelText};
+//! # fn main() {
+//!     let text = NovelText::parse("[chapter:Prologue]\n[[rb:漢字 > かんじ]]です。[newpage]two");
+//!     assert_eq!(text.pages.len(), 2);
+//!     assert_eq!(text.pages[0].elements[0], Element::Chapter("Prologue".to_owned()));
+//!     assert_eq!(text.plain_text(), "Prologue\n漢字です。\n\ntwo");
+//! # }
+//! ```
+
+use std::fmt;
+
+/// A parsed novel body.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NovelText {
+    pub pages: Vec<Page>,
+}
+
+/// A page of a novel, split at `[newpage]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page {
+    pub elements: Vec<Element>,
+}
+
+/// A run of text or an inline tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Element {
+    /// Text, including its line breaks.
+    Text(String),
+    /// `[chapter:title]`.
+    Chapter(String),
+    /// `[[rb:base > reading]]`.
+    Ruby { base: String, reading: String },
+    /// `[pixivimage:illust_id]`, or `[pixivimage:illust_id-page]` for a page of a manga, counted from 1.
+    PixivImage { illust_id: usize, page: Option<usize> },
+    /// `[uploadedimage:id]`. The image url is only in the webview version of the text.
+    UploadedImage(usize),
+    /// `[jump:page]`, counted from 1.
+    JumpPage(usize),
+    /// `[[jumpuri:text > uri]]`.
+    JumpUri { text: String, uri: String },
+}
+
+impl NovelText {
+    /// Parses novel markup. This never fails; malformed tags are kept as text.
+    pub fn parse(source: &str) -> NovelText {
+        let mut pages = Vec::new();
+        let mut page = Page::default();
+        let mut text = String::new();
+        let mut rest = source;
+        while let Some(start) = rest.find('[') {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match parse_tag(rest) {
+                Some((tag, len)) => {
+                    if !text.is_empty() {
+                        page.elements.push(Element::Text(text.split_off(0)));
+                    }
+                    match tag {
+                        Tag::NewPage => pages.push(::std::mem::take(&mut page)),
+                        Tag::Element(element) => page.elements.push(element),
+                    }
+                    rest = &rest[len..];
+                }
+                None => {
+                    text.push('[');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            page.elements.push(Element::Text(text));
+        }
+        pages.push(page);
+        NovelText { pages }
+    }
+
+    /// Get the chapters with the index of the page each starts on.
+    pub fn chapters(&self) -> Vec<(usize, &str)> {
+        self.pages
+            .iter()
+            .enumerate()
+            .flat_map(|(i, page)| {
+                page.elements.iter().filter_map(move |element| match *element {
+                    Element::Chapter(ref title) => Some((i, title.as_str())),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the ids of the illustrations embedded with `[pixivimage:]`, in order.
+    pub fn pixiv_images(&self) -> Vec<usize> {
+        self.pages
+            .iter()
+            .flat_map(|page| &page.elements)
+            .filter_map(|element| match *element {
+                Element::PixivImage { illust_id, .. } => Some(illust_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Renders the text without markup: ruby as its base, chapters on their own line, images left out,
+    /// and non-empty pages separated by a blank line.
+    pub fn plain_text(&self) -> String {
+        let pages: Vec<String> = self.pages.iter().map(Page::plain_text).filter(|page| !page.is_empty()).collect();
+        pages.join("\n\n")
+    }
+}
+
+impl Page {
+    /// Renders the page without markup, trimmed of surrounding blank lines.
+    pub fn plain_text(&self) -> String {
+        let mut out = String::new();
+        for element in &self.elements {
+            match *element {
+                Element::Text(ref text) => out.push_str(text),
+                Element::Chapter(ref title) => {
+                    if !out.is_empty() && !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    out.push_str(&NovelText::parse(title).plain_text());
+                }
+                Element::Ruby { ref base, .. } => out.push_str(base),
+                Element::JumpUri { ref text, .. } => out.push_str(text),
+                Element::PixivImage { .. } | Element::UploadedImage(_) | Element::JumpPage(_) => {}
+            }
+        }
+        out.trim_matches('\n').to_owned()
+    }
+}
+
+// Renders back to Pixiv's markup.
+impl fmt::Display for NovelText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, page) in self.pages.iter().enumerate() {
+            if i > 0 {
+                f.write_str("[newpage]")?;
+            }
+            write!(f, "{}", page)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Page {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for element in &self.elements {
+            write!(f, "{}", element)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Element::Text(ref text) => f.write_str(text),
+            Element::Chapter(ref title) => write!(f, "[chapter:{}]", title),
+            Element::Ruby { ref base, ref reading } => write!(f, "[[rb:{} > {}]]", base, reading),
+            Element::PixivImage { illust_id, page: Some(page) } => write!(f, "[pixivimage:{}-{}]", illust_id, page),
+            Element::PixivImage { illust_id, page: None } => write!(f, "[pixivimage:{}]", illust_id),
+            Element::UploadedImage(id) => write!(f, "[uploadedimage:{}]", id),
+            Element::JumpPage(page) => write!(f, "[jump:{}]", page),
+            Element::JumpUri { ref text, ref uri } => write!(f, "[[jumpuri:{} > {}]]", text, uri),
+        }
+    }
+}
+
+enum Tag {
+    NewPage,
+    Element(Element),
+}
+
+// Parses the tag at the start of `source`, returning it and its length.
+fn parse_tag(source: &str) -> Option<(Tag, usize)> {
+    if source.starts_with("[newpage]") {
+        return Some((Tag::NewPage, "[newpage]".len()));
+    }
+    if let Some(inner) = source.strip_prefix("[[") {
+        let end = inner.find("]]")?;
+        let (name, value) = inner[..end].split_once(':')?;
+        let (left, right) = value.split_once('>')?;
+        let (left, right) = (left.trim().to_owned(), right.trim().to_owned());
+        let element = match name {
+            "rb" => Element::Ruby { base: left, reading: right },
+            "jumpuri" => Element::JumpUri { text: left, uri: right },
+            _ => return None,
+        };
+        return Some((Tag::Element(element), end + 4));
+    }
+    let (name, rest) = source[1..].split_once(':')?;
+    let offset = 1 + name.len() + 1;
+    if name == "chapter" {
+        // chapter titles may contain ruby, so they run to the bracket closing the tag on the same line
+        let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        let end = closing_bracket(line)?;
+        return Some((Tag::Element(Element::Chapter(line[..end].trim().to_owned())), offset + end + 1));
+    }
+    let end = rest.find(']')?;
+    let value = rest[..end].trim();
+    let element = match name {
+        "pixivimage" => match value.split_once('-') {
+            Some((id, page)) => Element::PixivImage {
+                illust_id: id.trim().parse().ok()?,
+                page: Some(page.trim().parse().ok()?),
+            },
+            None => Element::PixivImage { illust_id: value.parse().ok()?, page: None },
+        },
+        "uploadedimage" => Element::UploadedImage(value.parse().ok()?),
+        "jump" => Element::JumpPage(value.parse().ok()?),
+        _ => return None,
+    };
+    Some((Tag::Element(element), offset + end + 1))
+}
+
+// Finds the `]` closing a tag whose value is `text`, skipping `[[...]]` tags and nested brackets.
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        if let Some(tag) = rest.strip_prefix("[[") {
+            i += 2 + tag.find("]]")? + 2;
+            continue;
+        }
+        match rest.as_bytes()[0] {
+            b'[' => depth += 1,
+            b']' if depth == 0 => return Some(i),
+            b']' => depth -= 1,
+            _ => {}
+        }
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let source = "[chapter:第一章 [[rb:始 > はじ]]まり]\n\
+                      [[rb:吾輩 > わがはい]]は猫である。[pixivimage:12345-2]\n\
+                      [newpage]\n[jump:1] [uploadedimage:678] [[jumpuri:pixiv > https://www.pixiv.net]]\n\
+                      [unknown] [pixivimage:abc] [[rb:no reading]]";
+        let text = NovelText::parse(source);
+        assert_eq!(text.pages.len(), 2);
+        assert_eq!(text.chapters(), vec![(0, "第一章 [[rb:始 > はじ]]まり")]);
+        assert_eq!(
+            text.pages[0].elements[2],
+            Element::Ruby { base: "吾輩".to_owned(), reading: "わがはい".to_owned() }
+        );
+        assert_eq!(text.pages[0].elements[4], Element::PixivImage { illust_id: 12345, page: Some(2) });
+        assert_eq!(text.pixiv_images(), vec![12345]);
+        assert_eq!(text.pages[1].elements[1], Element::JumpPage(1));
+        assert_eq!(text.pages[1].elements[3], Element::UploadedImage(678));
+        assert_eq!(
+            text.pages[1].elements.last().unwrap(),
+            &Element::Text("\n[unknown] [pixivimage:abc] [[rb:no reading]]".to_owned())
+        );
+        assert_eq!(text.to_string(), source);
+
+        let text = NovelText::parse("[chapter:A [[rb:B > b]]] text [pixivimage:1]");
+        assert_eq!(
+            text.pages[0].elements,
+            vec![
+                Element::Chapter("A [[rb:B > b]]".to_owned()),
+                Element::Text(" text ".to_owned()),
+                Element::PixivImage { illust_id: 1, page: None },
+            ]
+        );
+        let text = NovelText::parse("[chapter:A] text [pixivimage:1]");
+        assert_eq!(text.chapters(), vec![(0, "A")]);
+        assert_eq!(text.pixiv_images(), vec![1]);
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let text = NovelText::parse("one[chapter:Two]\n[[rb:三 > さん]][pixivimage:1]\n[newpage]\n\nfour\n");
+        assert_eq!(text.plain_text(), "one\nTwo\n三\n\nfour");
+        assert_eq!(NovelText::parse("").pages, vec![Page::default()]);
+    }
+}
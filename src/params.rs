@@ -0,0 +1,783 @@
+//! Typed params for each endpoint.
+//!
+//! Every constructor of `PixivRequestBuilder` returns a builder over one of these structs, so only
+//! the params an endpoint actually accepts can be set on it. Params are serialized in field order.
+//!
+//! Setting a param an endpoint doesn't accept fails to compile:
+//!
+//! ```rust,compile_fail
+//! # extern crate pixiv;
+//! # use pixiv::{PixivRequestBuilder, RankingMode};
+//! # fn main() {
+//!     let request = PixivRequestBuilder::user(6996493)
+//!         .ranking_mode(RankingMode::Weekly)
+//!         .build();
+//! # }
+//! ```
+
+use std::fmt::Display;
+
+use chrono::naive::NaiveDate;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::{Serialize, Serializer};
+
+use utils::comma_delimited;
+
+use super::{
+    ImageSize, NovelRankingMode, NovelSearchSort, ProfileImageSize, Publicity, RankingMode, RankingType, RequestError,
+    SearchMode, SearchOrder, SearchPeriod, SearchSort, SearchTarget, WorkType,
+};
+
+/// Params of a request, which are validated before the request is built.
+pub trait Params: Serialize {
+    /// Returns an error describing the first invalid param, if any.
+    fn validate(&self) -> Result<(), RequestError> {
+        Ok(())
+    }
+}
+
+/// Params accepting `page`.
+pub trait HasPage {
+    /// Get a mutable reference to the `page` param.
+    fn page_mut(&mut self) -> &mut usize;
+}
+
+/// Params accepting `per_page`.
+pub trait HasPerPage {
+    /// Get a mutable reference to the `per_page` param.
+    fn per_page_mut(&mut self) -> &mut usize;
+}
+
+/// Params accepting `max_id`.
+pub trait HasMaxId {
+    /// Get a mutable reference to the `max_id` param.
+    fn max_id_mut(&mut self) -> &mut Option<usize>;
+}
+
+/// Params accepting `offset`.
+pub trait HasOffset {
+    /// Get a mutable reference to the `offset` param.
+    fn offset_mut(&mut self) -> &mut usize;
+}
+
+/// Params accepting `max_bookmark_id`.
+pub trait HasMaxBookmarkId {
+    /// Get a mutable reference to the `max_bookmark_id` param.
+    fn max_bookmark_id_mut(&mut self) -> &mut Option<usize>;
+}
+
+/// Params accepting a bookmark `tag`.
+pub trait HasBookmarkTag {
+    /// Get a mutable reference to the `tag` param.
+    fn bookmark_tag_mut(&mut self) -> &mut Option<String>;
+}
+
+/// Params accepting `image_sizes`.
+pub trait HasImageSizes {
+    /// Get a mutable reference to the `image_sizes` param.
+    fn image_sizes_mut(&mut self) -> &mut Vec<ImageSize>;
+}
+
+/// Params accepting `profile_image_sizes`.
+pub trait HasProfileImageSizes {
+    /// Get a mutable reference to the `profile_image_sizes` param.
+    fn profile_image_sizes_mut(&mut self) -> &mut Vec<ProfileImageSize>;
+}
+
+/// Params accepting `publicity`.
+pub trait HasPublicity {
+    /// Get a mutable reference to the `publicity` param.
+    fn publicity_mut(&mut self) -> &mut Publicity;
+}
+
+/// Params accepting `show_r18`.
+pub trait HasShowR18 {
+    /// Get a mutable reference to the `show_r18` param.
+    fn show_r18_mut(&mut self) -> &mut bool;
+}
+
+/// Params accepting `include_stats`.
+pub trait HasIncludeStats {
+    /// Get a mutable reference to the `include_stats` param.
+    fn include_stats_mut(&mut self) -> &mut bool;
+}
+
+/// Params accepting `include_sanity_level`.
+pub trait HasIncludeSanityLevel {
+    /// Get a mutable reference to the `include_sanity_level` param.
+    fn include_sanity_level_mut(&mut self) -> &mut bool;
+}
+
+/// Params accepting `date`.
+pub trait HasDate {
+    /// Get a mutable reference to the `date` param.
+    fn date_mut(&mut self) -> &mut Option<NaiveDate>;
+}
+
+macro_rules! impl_has {
+    ($trait_:ident, $method:ident, $field:ident: $ty:ty, [$($params:ident),*]) => {
+        $(
+            impl $trait_ for $params {
+                #[inline]
+                fn $method(&mut self) -> &mut $ty {
+                    &mut self.$field
+                }
+            }
+        )*
+    };
+}
+
+/// Params of `bad_words()`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BadWordsParams {}
+
+impl Params for BadWordsParams {}
+
+/// Params of `work()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkParams {
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+    pub include_stats: bool,
+}
+
+impl Params for WorkParams {}
+
+/// Params of `user()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserParams {
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub profile_image_sizes: Vec<ProfileImageSize>,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+    #[serde(serialize_with = "numeric_flag")]
+    pub include_stats: bool,
+    #[serde(serialize_with = "numeric_flag")]
+    pub include_profile: bool,
+    #[serde(serialize_with = "numeric_flag")]
+    pub include_workspace: bool,
+    #[serde(serialize_with = "numeric_flag")]
+    pub include_contacts: bool,
+}
+
+impl Params for UserParams {}
+
+/// Params of `feed()` and `user_feed()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedParams {
+    pub relation: &'static str,
+    #[serde(rename = "type")]
+    pub feed_type: &'static str,
+    #[serde(serialize_with = "numeric_flag")]
+    pub show_r18: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_id: Option<usize>,
+}
+
+impl Params for FeedParams {}
+
+impl Default for FeedParams {
+    fn default() -> Self {
+        FeedParams {
+            relation: "all",
+            feed_type: "touch_nottext",
+            show_r18: true,
+            max_id: None,
+        }
+    }
+}
+
+/// Params of `favorite_works()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FavoriteWorksParams {
+    pub page: usize,
+    pub per_page: usize,
+    pub publicity: Publicity,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+}
+
+impl Params for FavoriteWorksParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `favorite_work_add()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FavoriteWorkAddParams {
+    pub work_id: usize,
+    pub publicity: Publicity,
+}
+
+impl Params for FavoriteWorkAddParams {}
+
+/// Params of `favorite_works_remove()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FavoriteWorksRemoveParams {
+    #[serde(serialize_with = "comma")]
+    pub ids: Vec<usize>,
+    pub publicity: Publicity,
+}
+
+impl Params for FavoriteWorksRemoveParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        not_empty("ids", &self.ids)
+    }
+}
+
+/// Params of `following_works()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FollowingWorksParams {
+    pub page: usize,
+    pub per_page: usize,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+    pub include_stats: bool,
+    pub include_sanity_level: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_id: Option<usize>,
+}
+
+impl Params for FollowingWorksParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `following()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FollowingParams {
+    pub page: usize,
+    pub per_page: usize,
+    pub publicity: Publicity,
+}
+
+impl Params for FollowingParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `following_add()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FollowingAddParams {
+    pub target_user_id: usize,
+    pub publicity: Publicity,
+}
+
+impl Params for FollowingAddParams {}
+
+/// Params of `following_remove()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FollowingRemoveParams {
+    #[serde(serialize_with = "comma")]
+    pub delete_ids: Vec<usize>,
+    pub publicity: Publicity,
+}
+
+impl Params for FollowingRemoveParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        not_empty("delete_ids", &self.delete_ids)
+    }
+}
+
+/// Params of `user_works()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserWorksParams {
+    pub page: usize,
+    pub per_page: usize,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+    pub include_stats: bool,
+    pub include_sanity_level: bool,
+}
+
+impl Params for UserWorksParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `user_favorite_works()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserFavoriteWorksParams {
+    pub page: usize,
+    pub per_page: usize,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+    pub include_sanity_level: bool,
+}
+
+impl Params for UserFavoriteWorksParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `user_following()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserFollowingParams {
+    pub page: usize,
+    pub per_page: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_id: Option<usize>,
+}
+
+impl Params for UserFollowingParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `ranking()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RankingParams {
+    pub mode: RankingMode,
+    pub page: usize,
+    pub per_page: usize,
+    #[serde(serialize_with = "date", skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    #[serde(serialize_with = "capitalized_flag")]
+    pub include_stats: bool,
+    #[serde(serialize_with = "capitalized_flag")]
+    pub include_sanity_level: bool,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub profile_image_sizes: Vec<ProfileImageSize>,
+}
+
+impl Params for RankingParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `search_works()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchWorksParams {
+    pub q: String,
+    pub page: usize,
+    pub per_page: usize,
+    #[serde(serialize_with = "date", skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    #[serde(serialize_with = "date", skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(serialize_with = "date", skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<NaiveDate>,
+    pub mode: SearchMode,
+    pub period: SearchPeriod,
+    pub order: SearchOrder,
+    pub sort: SearchSort,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<WorkType>,
+    /// Serialized as `search_ai_type=0`, which hides AI-generated works.
+    #[serde(rename = "search_ai_type", serialize_with = "exclude_flag", skip_serializing_if = "is_false")]
+    pub exclude_ai: bool,
+    pub include_stats: bool,
+    pub include_sanity_level: bool,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+}
+
+impl Params for SearchWorksParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        if self.q.trim().is_empty() {
+            return Err(RequestError::new("`q` must not be empty"));
+        }
+        if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
+            if start > end {
+                return Err(RequestError::new("`start_date` must not be after `end_date`"));
+            }
+        }
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `latest_works()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatestWorksParams {
+    pub page: usize,
+    pub per_page: usize,
+    #[serde(serialize_with = "date", skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    pub include_stats: bool,
+    pub include_sanity_level: bool,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub image_sizes: Vec<ImageSize>,
+    #[serde(serialize_with = "comma", skip_serializing_if = "Vec::is_empty")]
+    pub profile_image_sizes: Vec<ProfileImageSize>,
+}
+
+impl Params for LatestWorksParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        positive("page", self.page)?;
+        positive("per_page", self.per_page)
+    }
+}
+
+/// Params of `novel()` and `novel_text()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NovelParams {
+    pub novel_id: usize,
+}
+
+impl Params for NovelParams {}
+
+/// Params of `novel_series()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NovelSeriesParams {
+    pub series_id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_order: Option<usize>,
+}
+
+impl Params for NovelSeriesParams {}
+
+/// Params of `user_novels()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserNovelsParams {
+    pub user_id: usize,
+    pub offset: usize,
+}
+
+impl Params for UserNovelsParams {}
+
+/// Params of `user_bookmarked_novels()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserBookmarkedNovelsParams {
+    pub user_id: usize,
+    #[serde(rename = "restrict")]
+    pub publicity: Publicity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bookmark_id: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+impl Params for UserBookmarkedNovelsParams {}
+
+/// Params of `novel_ranking()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NovelRankingParams {
+    pub mode: NovelRankingMode,
+    #[serde(serialize_with = "date", skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    pub offset: usize,
+}
+
+impl Params for NovelRankingParams {}
+
+/// Params of `search_novels()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchNovelsParams {
+    pub word: String,
+    pub search_target: SearchTarget,
+    pub sort: NovelSearchSort,
+    #[serde(serialize_with = "date", skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(serialize_with = "date", skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<NaiveDate>,
+    pub offset: usize,
+}
+
+impl Params for SearchNovelsParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        if self.word.trim().is_empty() {
+            return Err(RequestError::new("`word` must not be empty"));
+        }
+        if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
+            if start > end {
+                return Err(RequestError::new("`start_date` must not be after `end_date`"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Params of `illust_detail()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IllustParams {
+    pub illust_id: usize,
+}
+
+impl Params for IllustParams {}
+
+/// Params of `illust_series()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IllustSeriesParams {
+    pub illust_series_id: usize,
+    pub offset: usize,
+}
+
+impl Params for IllustSeriesParams {}
+
+/// Params of `manga_watchlist()`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MangaWatchlistParams {
+    pub offset: usize,
+}
+
+impl Params for MangaWatchlistParams {}
+
+/// Params of `manga_watchlist_add()` and `manga_watchlist_remove()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MangaWatchlistEditParams {
+    pub series_id: usize,
+}
+
+impl Params for MangaWatchlistEditParams {}
+
+/// Params of `comments()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentsParams {
+    pub illust_id: usize,
+    pub offset: usize,
+}
+
+impl Params for CommentsParams {}
+
+/// Params of `comment_replies()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentRepliesParams {
+    pub comment_id: usize,
+    pub offset: usize,
+}
+
+impl Params for CommentRepliesParams {}
+
+/// Params of `comment_add()` and `comment_add_stamp()`. Exactly one of `comment` and `stamp_id` is sent.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentAddParams {
+    pub illust_id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stamp_id: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_comment_id: Option<usize>,
+}
+
+impl Params for CommentAddParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        match (&self.comment, self.stamp_id) {
+            (Some(comment), None) if comment.trim().is_empty() => Err(RequestError::new("`comment` must not be empty")),
+            (Some(_), None) | (None, Some(_)) => Ok(()),
+            _ => Err(RequestError::new("exactly one of `comment` and `stamp_id` must be given")),
+        }
+    }
+}
+
+/// Params of `comment_delete()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentDeleteParams {
+    pub comment_id: usize,
+}
+
+impl Params for CommentDeleteParams {}
+
+/// Params of `search_user()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchUserParams {
+    pub word: String,
+    pub offset: usize,
+}
+
+impl Params for SearchUserParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        if self.word.trim().is_empty() {
+            return Err(RequestError::new("`word` must not be empty"));
+        }
+        Ok(())
+    }
+}
+
+/// Params of `user_recommended()`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UserRecommendedParams {
+    pub offset: usize,
+}
+
+impl Params for UserRecommendedParams {}
+
+/// Params of `illust_bookmark_add()` and `illust_bookmark_edit()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IllustBookmarkAddParams {
+    pub illust_id: usize,
+    #[serde(rename = "restrict")]
+    pub publicity: Publicity,
+    #[serde(rename = "tags[]", serialize_with = "space", skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Params for IllustBookmarkAddParams {
+    fn validate(&self) -> Result<(), RequestError> {
+        if self.tags.iter().any(|tag| tag.is_empty() || tag.contains(char::is_whitespace)) {
+            return Err(RequestError::new("`tags` must not be empty or contain spaces"));
+        }
+        Ok(())
+    }
+}
+
+/// Params of `user_bookmark_tags()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserBookmarkTagsParams {
+    pub user_id: usize,
+    #[serde(rename = "restrict")]
+    pub publicity: Publicity,
+    pub offset: usize,
+}
+
+impl Params for UserBookmarkTagsParams {}
+
+/// Params of `user_bookmarks()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserBookmarksParams {
+    pub user_id: usize,
+    #[serde(rename = "restrict")]
+    pub publicity: Publicity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bookmark_id: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+impl Params for UserBookmarksParams {}
+
+impl_has!(HasPage, page_mut, page: usize, [
+    FavoriteWorksParams, FollowingWorksParams, FollowingParams, UserWorksParams,
+    UserFavoriteWorksParams, UserFollowingParams, RankingParams, SearchWorksParams,
+    LatestWorksParams
+]);
+impl_has!(HasPerPage, per_page_mut, per_page: usize, [
+    FavoriteWorksParams, FollowingWorksParams, FollowingParams, UserWorksParams,
+    UserFavoriteWorksParams, UserFollowingParams, RankingParams, SearchWorksParams,
+    LatestWorksParams
+]);
+impl_has!(HasMaxId, max_id_mut, max_id: Option<usize>, [
+    FeedParams, FollowingWorksParams, UserFollowingParams
+]);
+impl_has!(HasOffset, offset_mut, offset: usize, [
+    UserNovelsParams, NovelRankingParams, SearchNovelsParams, IllustSeriesParams, MangaWatchlistParams,
+    CommentsParams, CommentRepliesParams, SearchUserParams, UserRecommendedParams, UserBookmarkTagsParams
+]);
+impl_has!(HasMaxBookmarkId, max_bookmark_id_mut, max_bookmark_id: Option<usize>, [
+    UserBookmarkedNovelsParams, UserBookmarksParams
+]);
+impl_has!(HasBookmarkTag, bookmark_tag_mut, tag: Option<String>, [
+    UserBookmarkedNovelsParams, UserBookmarksParams
+]);
+impl_has!(HasImageSizes, image_sizes_mut, image_sizes: Vec<ImageSize>, [
+    WorkParams, UserParams, FavoriteWorksParams, FollowingWorksParams, UserWorksParams,
+    UserFavoriteWorksParams, RankingParams, SearchWorksParams, LatestWorksParams
+]);
+impl_has!(HasProfileImageSizes, profile_image_sizes_mut, profile_image_sizes: Vec<ProfileImageSize>, [
+    UserParams, RankingParams, LatestWorksParams
+]);
+impl_has!(HasPublicity, publicity_mut, publicity: Publicity, [
+    FavoriteWorksParams, FavoriteWorkAddParams, FavoriteWorksRemoveParams, FollowingParams,
+    FollowingAddParams, FollowingRemoveParams, UserBookmarkedNovelsParams, IllustBookmarkAddParams,
+    UserBookmarkTagsParams, UserBookmarksParams
+]);
+impl_has!(HasShowR18, show_r18_mut, show_r18: bool, [FeedParams]);
+impl_has!(HasIncludeStats, include_stats_mut, include_stats: bool, [
+    WorkParams, UserParams, FollowingWorksParams, UserWorksParams, RankingParams,
+    SearchWorksParams, LatestWorksParams
+]);
+impl_has!(HasIncludeSanityLevel, include_sanity_level_mut, include_sanity_level: bool, [
+    FollowingWorksParams, UserWorksParams, UserFavoriteWorksParams, RankingParams,
+    SearchWorksParams, LatestWorksParams
+]);
+impl_has!(HasDate, date_mut, date: Option<NaiveDate>, [
+    RankingParams, SearchWorksParams, LatestWorksParams, NovelRankingParams
+]);
+
+macro_rules! impl_serialize_as_str {
+    ($($ty:ident),*) => {
+        $(
+            impl Serialize for $ty {
+                #[inline]
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(self.as_str())
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize_as_str!(
+    Publicity, RankingType, RankingMode, SearchPeriod, SearchMode, SearchOrder, SearchSort, NovelRankingMode,
+    SearchTarget, NovelSearchSort
+);
+
+macro_rules! impl_deserialize_from_str {
+    ($($ty:ident),*) => {
+        $(
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = String::deserialize(deserializer)?;
+                    $ty::from_str(&value)
+                        .ok_or_else(|| de::Error::custom(format!("unknown {} `{}`", stringify!($ty), value)))
+                }
+            }
+        )*
+    };
+}
+
+impl_deserialize_from_str!(Publicity, RankingType, RankingMode);
+
+pub(crate) fn positive(name: &str, value: usize) -> Result<(), RequestError> {
+    if value == 0 {
+        return Err(RequestError::new(format!("`{}` must be a positive integer", name)));
+    }
+    Ok(())
+}
+
+pub(crate) fn not_empty<T>(name: &str, values: &[T]) -> Result<(), RequestError> {
+    if values.is_empty() {
+        return Err(RequestError::new(format!("`{}` must not be empty", name)));
+    }
+    Ok(())
+}
+
+pub(crate) fn comma<T: Display, S: Serializer>(values: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&comma_delimited::<T, _, _>(values))
+}
+
+pub(crate) fn space<S: Serializer>(values: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&values.join(" "))
+}
+
+pub(crate) fn numeric_flag<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(if *value { "1" } else { "0" })
+}
+
+// The ranking endpoint has always been sent `True`, as in Python.
+pub(crate) fn capitalized_flag<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(if *value { "True" } else { "False" })
+}
+
+pub(crate) fn exclude_flag<S: Serializer>(_: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str("0")
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+pub(crate) fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+pub(crate) fn date<S: Serializer>(value: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error> {
+    match *value {
+        Some(ref date) => serializer.collect_str(&date.format("%Y-%m-%d")),
+        None => serializer.serialize_none(),
+    }
+}
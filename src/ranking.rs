@@ -0,0 +1,563 @@
+//! Ranking history across a range of dates.
+//!
+//! `RankingHistory` walks a date range for a `RankingType` and `RankingMode`, fetching every page of each
+//! day's ranking, and `RankingArchive` keeps the results, e.g. in a JSON file, so they can be extended
+//! later and compared day by day with `RankingDiff`.
+//!
+//! ```rust,no_run
+//! # extern crate pixiv;
+//! # extern crate reqwest;
+//! # use std::time::Duration;
+//! # use pixiv::client::Pixiv;
+//! # use pixiv::ranking::RankingArchive;
+//! # use pixiv::{RankingMode, RankingType};
+//! # use pixiv::chrono::NaiveDate;
+//! # use reqwest::Client;
+//! # fn main() {
+//! #   let client = Client::new();
+//! #   let mut pixiv: Pixiv = Pixiv::new(&client);
+//! #   pixiv.login("username", "password");
+//!     let mut archive = RankingArchive::load_or_new("daily.json", RankingType::Illust, RankingMode::Daily)
+//!         .expect("Failed to read archive.");
+//!     let from = NaiveDate::from_ymd_opt(2018, 1, 1).unwrap();
+//!     let to = NaiveDate::from_ymd_opt(2018, 3, 31).unwrap();
+//!     // Days already in the archive aren't fetched again.
+//!     archive.update(&pixiv, from, to, Duration::from_secs(1)).expect("Failed to fetch rankings.");
+//!     archive.save("daily.json").expect("Failed to write archive.");
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::path::Path;
+#[cfg(feature = "reqwest-client")]
+use std::collections::BTreeSet;
+#[cfg(feature = "reqwest-client")]
+use std::thread;
+#[cfg(feature = "reqwest-client")]
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use serde_json;
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+#[cfg(feature = "reqwest-client")]
+use models::PixivResponse;
+use models::Ranking;
+use utils;
+#[cfg(feature = "reqwest-client")]
+use PixivRequestBuilder;
+use {RankingMode, RankingType};
+
+/// One work's position in a day's ranking.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankingRecord {
+    pub date: NaiveDate,
+    pub rank: usize,
+    /// The rank on the day before, if the work was ranked then.
+    pub previous_rank: Option<usize>,
+    pub work_id: usize,
+}
+
+impl RankingRecord {
+    /// Creates the records of a ranking for `date`.
+    pub fn from_ranking(date: NaiveDate, ranking: &Ranking) -> Vec<RankingRecord> {
+        ranking
+            .works
+            .iter()
+            .map(|ranked| RankingRecord {
+                date,
+                rank: ranked.rank,
+                previous_rank: if ranked.previous_rank == 0 { None } else { Some(ranked.previous_rank) },
+                work_id: ranked.work.id,
+            })
+            .collect()
+    }
+}
+
+/// Error returned when reading or writing a `RankingArchive`.
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The archive holds a different ranking than the one asked for.
+    Mismatch {
+        expected: (RankingType, RankingMode),
+        found: (RankingType, RankingMode),
+    },
+}
+
+impl Error for ArchiveError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to read or write a ranking archive."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            ArchiveError::Io(ref e) => Some(e),
+            ArchiveError::Json(ref e) => Some(e),
+            ArchiveError::Mismatch { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArchiveError::Io(ref e) => write!(f, "An error occurred while trying to access a ranking archive. Reason: {}", e),
+            ArchiveError::Json(ref e) => write!(f, "Ranking archive is invalid: {}", e),
+            ArchiveError::Mismatch { expected, found } => write!(
+                f,
+                "Ranking archive holds the {} {} ranking, not the {} {} one.",
+                found.0, found.1, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        ArchiveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ArchiveError {
+    fn from(e: serde_json::Error) -> Self {
+        ArchiveError::Json(e)
+    }
+}
+
+/// Ranking records of one `RankingType` and `RankingMode`, grouped by date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingArchive {
+    ranking_type: RankingType,
+    mode: RankingMode,
+    days: BTreeMap<NaiveDate, Vec<RankingRecord>>,
+}
+
+impl RankingArchive {
+    /// Creates an empty archive.
+    pub fn new(ranking_type: RankingType, mode: RankingMode) -> RankingArchive {
+        RankingArchive {
+            ranking_type,
+            mode,
+            days: BTreeMap::new(),
+        }
+    }
+    /// Reads an archive written by `save()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<RankingArchive, ArchiveError> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+    /// Reads an archive written by `save()`, or creates an empty one if `path` doesn't exist. Fails with
+    /// `ArchiveError::Mismatch` if the archive is of another ranking type or mode.
+    pub fn load_or_new<P: AsRef<Path>>(
+        path: P,
+        ranking_type: RankingType,
+        mode: RankingMode,
+    ) -> Result<RankingArchive, ArchiveError> {
+        match File::open(path) {
+            Ok(file) => {
+                let archive: RankingArchive = serde_json::from_reader(BufReader::new(file))?;
+                if (archive.ranking_type, archive.mode) != (ranking_type, mode) {
+                    return Err(ArchiveError::Mismatch {
+                        expected: (ranking_type, mode),
+                        found: (archive.ranking_type, archive.mode),
+                    });
+                }
+                Ok(archive)
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(RankingArchive::new(ranking_type, mode)),
+            Err(e) => Err(e.into()),
+        }
+    }
+    /// Writes the archive as JSON. The file is replaced only once the archive is fully written, so an
+    /// interrupted save keeps the previous one.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ArchiveError> {
+        utils::replace_file(path, |out| Ok(serde_json::to_writer(out, self)?))
+    }
+    /// Get the ranking type.
+    #[inline]
+    pub fn ranking_type(&self) -> RankingType {
+        self.ranking_type
+    }
+    /// Get the ranking mode.
+    #[inline]
+    pub fn mode(&self) -> RankingMode {
+        self.mode
+    }
+    /// Get the records of a day, ordered by rank.
+    pub fn day(&self, date: NaiveDate) -> Option<&[RankingRecord]> {
+        self.days.get(&date).map(|records| records.as_slice())
+    }
+    /// Get the archived dates in order.
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.days.keys().cloned()
+    }
+    /// Get every record, ordered by date and rank.
+    pub fn records(&self) -> impl Iterator<Item = &RankingRecord> {
+        self.days.values().flat_map(|records| records.iter())
+    }
+    /// Stores the records of a day, replacing any already stored for it.
+    pub fn insert_day(&mut self, date: NaiveDate, mut records: Vec<RankingRecord>) {
+        records.sort_by_key(|record| record.rank);
+        self.days.insert(date, records);
+    }
+    /// Compares the archived rankings of two days, if both are archived.
+    pub fn diff(&self, from: NaiveDate, to: NaiveDate) -> Option<RankingDiff> {
+        Some(RankingDiff::new(from, self.day(from)?, to, self.day(to)?))
+    }
+    /// Fetches the rankings of every day in `from..=to` that isn't archived yet, waiting `delay` between requests.
+    /// Returns how many days were fetched. Days fetched before an error are kept.
+    #[cfg(feature = "reqwest-client")]
+    pub fn update(&mut self, pixiv: &Pixiv, from: NaiveDate, to: NaiveDate, delay: Duration) -> Result<usize, FetchError> {
+        let history = RankingHistory::new(pixiv, self.ranking_type, self.mode, from, to)
+            .delay(delay)
+            .skip_dates(self.dates());
+        let mut fetched = 0;
+        for day in history {
+            let (date, records) = day?;
+            self.insert_day(date, records);
+            fetched += 1;
+        }
+        Ok(fetched)
+    }
+}
+
+/// How a work that's in both rankings of a `RankingDiff` moved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Movement {
+    pub work_id: usize,
+    pub from_rank: usize,
+    pub to_rank: usize,
+}
+
+impl Movement {
+    /// How many places the work climbed. Negative if it fell.
+    pub fn change(&self) -> isize {
+        self.from_rank as isize - self.to_rank as isize
+    }
+}
+
+/// Formats a `RankingDiff` can be rendered as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+/// Differences between the rankings of two dates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankingDiff {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Works only ranked on `to`, ordered by rank.
+    pub entrants: Vec<RankingRecord>,
+    /// Works only ranked on `from`, ordered by their rank then.
+    pub drop_outs: Vec<RankingRecord>,
+    /// Works ranked on both dates, including ones that kept their rank, ordered by their rank on `to`.
+    pub movements: Vec<Movement>,
+}
+
+impl RankingDiff {
+    /// Compares the records of two rankings, e.g. from `RankingRecord::from_ranking`.
+    pub fn new(from: NaiveDate, before: &[RankingRecord], to: NaiveDate, after: &[RankingRecord]) -> RankingDiff {
+        let before_ranks: BTreeMap<usize, usize> = before.iter().map(|r| (r.work_id, r.rank)).collect();
+        let after_ranks: BTreeMap<usize, usize> = after.iter().map(|r| (r.work_id, r.rank)).collect();
+
+        let mut entrants: Vec<RankingRecord> = after
+            .iter()
+            .filter(|r| !before_ranks.contains_key(&r.work_id))
+            .cloned()
+            .collect();
+        let mut drop_outs: Vec<RankingRecord> = before
+            .iter()
+            .filter(|r| !after_ranks.contains_key(&r.work_id))
+            .cloned()
+            .collect();
+        let mut movements: Vec<Movement> = after
+            .iter()
+            .filter_map(|r| {
+                before_ranks.get(&r.work_id).map(|&from_rank| Movement {
+                    work_id: r.work_id,
+                    from_rank,
+                    to_rank: r.rank,
+                })
+            })
+            .collect();
+        entrants.sort_by_key(|r| r.rank);
+        drop_outs.sort_by_key(|r| r.rank);
+        movements.sort_by_key(|m| m.to_rank);
+
+        RankingDiff {
+            from,
+            to,
+            entrants,
+            drop_outs,
+            movements,
+        }
+    }
+    /// Renders the diff in the given format.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self).expect("Failed to serialize ranking diff."),
+            ReportFormat::Csv => {
+                let mut buffer = Vec::new();
+                self.write_csv(&mut buffer).expect("Failed to write to buffer.");
+                String::from_utf8(buffer).expect("CSV is always UTF-8.")
+            }
+            ReportFormat::Markdown => self.to_markdown(),
+        }
+    }
+    /// Writes the diff as CSV with the columns `change,work_id,from_rank,to_rank`,
+    /// where `change` is one of `entered`, `dropped`, `up`, `down` or `same`.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "change,work_id,from_rank,to_rank")?;
+        for r in &self.entrants {
+            writeln!(writer, "entered,{},,{}", r.work_id, r.rank)?;
+        }
+        for m in &self.movements {
+            let change = match m.change() {
+                0 => "same",
+                c if c > 0 => "up",
+                _ => "down",
+            };
+            writeln!(writer, "{},{},{},{}", change, m.work_id, m.from_rank, m.to_rank)?;
+        }
+        for r in &self.drop_outs {
+            writeln!(writer, "dropped,{},{},", r.work_id, r.rank)?;
+        }
+        Ok(())
+    }
+    /// Renders the diff as Markdown tables linking to each work.
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "## Ranking changes from {} to {}", self.from, self.to);
+
+        let _ = write!(md, "\n### Entrants ({})\n\n", self.entrants.len());
+        if !self.entrants.is_empty() {
+            md.push_str("| Rank | Work |\n| ---: | --- |\n");
+            for r in &self.entrants {
+                let _ = writeln!(md, "| {} | {} |", r.rank, work_link(r.work_id));
+            }
+        }
+
+        let _ = write!(md, "\n### Drop-outs ({})\n\n", self.drop_outs.len());
+        if !self.drop_outs.is_empty() {
+            md.push_str("| Previous rank | Work |\n| ---: | --- |\n");
+            for r in &self.drop_outs {
+                let _ = writeln!(md, "| {} | {} |", r.rank, work_link(r.work_id));
+            }
+        }
+
+        let _ = write!(md, "\n### Movement ({})\n\n", self.movements.len());
+        if !self.movements.is_empty() {
+            md.push_str("| Rank | Previous rank | Change | Work |\n| ---: | ---: | ---: | --- |\n");
+            for m in &self.movements {
+                let change = match m.change() {
+                    0 => "=".to_owned(),
+                    c => format!("{:+}", c),
+                };
+                let _ = writeln!(md, "| {} | {} | {} | {} |", m.to_rank, m.from_rank, change, work_link(m.work_id));
+            }
+        }
+        md
+    }
+}
+
+fn work_link(id: usize) -> String {
+    format!("[{0}](https://www.pixiv.net/artworks/{0})", id)
+}
+
+/// Iterator fetching the complete ranking of each day in a date range, oldest first.
+#[cfg(feature = "reqwest-client")]
+#[derive(Debug)]
+pub struct RankingHistory<'a> {
+    pixiv: &'a Pixiv,
+    ranking_type: RankingType,
+    mode: RankingMode,
+    next: Option<NaiveDate>,
+    to: NaiveDate,
+    delay: Duration,
+    skip: BTreeSet<NaiveDate>,
+    fetched: bool,
+}
+
+#[cfg(feature = "reqwest-client")]
+impl<'a> RankingHistory<'a> {
+    /// Walks the days in `from..=to`.
+    pub fn new(
+        pixiv: &'a Pixiv,
+        ranking_type: RankingType,
+        mode: RankingMode,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> RankingHistory<'a> {
+        RankingHistory {
+            pixiv,
+            ranking_type,
+            mode,
+            next: Some(from),
+            to,
+            delay: Duration::from_secs(0),
+            skip: BTreeSet::new(),
+            fetched: false,
+        }
+    }
+    /// Sets how long to wait between requests (default: no wait).
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+    /// Skips the given dates, e.g. ones already archived.
+    pub fn skip_dates<I: IntoIterator<Item = NaiveDate>>(mut self, dates: I) -> Self {
+        self.skip.extend(dates);
+        self
+    }
+
+    fn fetch_day(&mut self, date: NaiveDate) -> Result<Vec<RankingRecord>, FetchError> {
+        let mut records = Vec::new();
+        let mut page = 1;
+        loop {
+            if self.fetched && self.delay > Duration::from_secs(0) {
+                thread::sleep(self.delay);
+            }
+            self.fetched = true;
+            let builder = PixivRequestBuilder::ranking(self.ranking_type)
+                .ranking_mode(self.mode)
+                .date(date)
+                .page(page);
+            let response: PixivResponse<Ranking> = self.pixiv.fetch(builder)?;
+            for ranking in &response.response {
+                records.extend(RankingRecord::from_ranking(date, ranking));
+            }
+            match response.pagination.and_then(|pagination| pagination.next) {
+                Some(next) if next > page => page = next,
+                _ => return Ok(records),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl<'a> Iterator for RankingHistory<'a> {
+    type Item = Result<(NaiveDate, Vec<RankingRecord>), FetchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let date = self.next.filter(|date| *date <= self.to)?;
+            self.next = date.succ_opt();
+            if !self.skip.contains(&date) {
+                return Some(self.fetch_day(date).map(|records| (date, records)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2018, 2, d).unwrap()
+    }
+
+    #[test]
+    fn test_records() {
+        let ranking: Ranking = serde_json::from_value(json!({
+            "content": "illust",
+            "mode": "daily",
+            "date": "2018-02-22",
+            "works": [
+                {"rank": 2, "previous_rank": 0, "work": {"id": 20, "title": "b"}},
+                {"rank": 1, "previous_rank": 5, "work": {"id": 10, "title": "a"}},
+            ],
+        }))
+        .unwrap();
+        let mut archive = RankingArchive::new(RankingType::Illust, RankingMode::Daily);
+        archive.insert_day(day(22), RankingRecord::from_ranking(day(22), &ranking));
+
+        assert_eq!(
+            archive.day(day(22)).unwrap(),
+            &[
+                RankingRecord { date: day(22), rank: 1, previous_rank: Some(5), work_id: 10 },
+                RankingRecord { date: day(22), rank: 2, previous_rank: None, work_id: 20 },
+            ][..]
+        );
+        assert!(archive.day(day(21)).is_none());
+    }
+
+    fn record(d: u32, rank: usize, work_id: usize) -> RankingRecord {
+        RankingRecord { date: day(d), rank, previous_rank: None, work_id }
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut archive = RankingArchive::new(RankingType::Illust, RankingMode::Weekly);
+        archive.insert_day(day(1), vec![record(1, 1, 10), record(1, 2, 20), record(1, 3, 30)]);
+        archive.insert_day(day(8), vec![record(8, 1, 30), record(8, 2, 20), record(8, 3, 40)]);
+        assert!(archive.diff(day(1), day(2)).is_none());
+
+        let diff = archive.diff(day(1), day(8)).unwrap();
+        assert_eq!(diff.entrants, vec![record(8, 3, 40)]);
+        assert_eq!(diff.drop_outs, vec![record(1, 1, 10)]);
+        assert_eq!(
+            diff.movements,
+            vec![
+                Movement { work_id: 30, from_rank: 3, to_rank: 1 },
+                Movement { work_id: 20, from_rank: 2, to_rank: 2 },
+            ]
+        );
+        assert_eq!(diff.movements[0].change(), 2);
+
+        assert_eq!(
+            diff.render(ReportFormat::Csv),
+            "change,work_id,from_rank,to_rank\n\
+             entered,40,,3\n\
+             up,30,3,1\n\
+             same,20,2,2\n\
+             dropped,10,1,\n"
+        );
+        let md = diff.render(ReportFormat::Markdown);
+        assert!(md.starts_with("## Ranking changes from 2018-02-01 to 2018-02-08\n"));
+        assert!(md.contains("| 1 | 3 | +2 | [30](https://www.pixiv.net/artworks/30) |"));
+        let json: RankingDiff = serde_json::from_str(&diff.render(ReportFormat::Json)).unwrap();
+        assert_eq!(json, diff);
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = env::temp_dir().join(format!("pixiv-ranking-test-{}.json", ::std::process::id()));
+        let mut archive = RankingArchive::new(RankingType::Manga, RankingMode::WeeklyR18);
+        archive.insert_day(day(2), vec![RankingRecord { date: day(2), rank: 1, previous_rank: None, work_id: 1 }]);
+        archive.insert_day(day(1), vec![RankingRecord { date: day(1), rank: 1, previous_rank: None, work_id: 2 }]);
+        archive.save(&path).unwrap();
+
+        let loaded = RankingArchive::load(&path).unwrap();
+        assert_eq!(loaded.ranking_type(), RankingType::Manga);
+        assert_eq!(loaded.mode(), RankingMode::WeeklyR18);
+        assert_eq!(loaded.dates().collect::<Vec<_>>(), vec![day(1), day(2)]);
+        assert_eq!(loaded.records().map(|r| r.work_id).collect::<Vec<_>>(), vec![2, 1]);
+
+        let loaded = RankingArchive::load_or_new(&path, RankingType::Manga, RankingMode::WeeklyR18).unwrap();
+        assert_eq!(loaded.dates().count(), 2);
+        match RankingArchive::load_or_new(&path, RankingType::Manga, RankingMode::Weekly) {
+            Err(ArchiveError::Mismatch { expected, found }) => {
+                assert_eq!(expected, (RankingType::Manga, RankingMode::Weekly));
+                assert_eq!(found, (RankingType::Manga, RankingMode::WeeklyR18));
+            }
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
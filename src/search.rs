@@ -0,0 +1,352 @@
+//! Building `search_works` queries out of tag expressions.
+//!
+//! Pixiv's search syntax separates tags with spaces (all must match), joins alternatives with ` OR `,
+//! groups with parentheses and excludes tags prefixed with `-`. `SearchQuery` renders to that syntax and
+//! sets the matching params, so it can be turned straight into a request.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # use pixiv::search::{SearchQuery, TagQuery};
+//! # use pixiv::PixivRequestBuilder;
+//! # fn main() {
+//!     let query = SearchQuery::new()
+//!         .tag("オリジナル")
+//!         .matching(TagQuery::any(vec!["風景", "空"]))
+//!         .exclude("R-18")
+//!         .min_bookmarks(10000)
+//!         .date_range("2018-01-01", "2018-03-31")
+//!         .exclude_ai(true);
+//!     assert_eq!(
+//!         query.to_string(),
+//!         "オリジナル (風景 OR 空) -R-18 (10000users入り OR 20000users入り OR 30000users入り OR 50000users入り OR 100000users入り)"
+//!     );
+//!     let request = PixivRequestBuilder::search_query(query).try_build();
+//!     assert!(request.is_ok());
+//! # }
+//! ```
+
+use std::fmt;
+
+use chrono::NaiveDate;
+
+use {DateParam, PixivRequestBuilder, RequestError, SearchMode};
+use params::SearchWorksParams;
+
+/// Bookmark counts Pixiv tags popular works with, as in `1000users入り`.
+pub const BOOKMARK_THRESHOLDS: [usize; 11] = [50, 100, 300, 500, 1000, 5000, 10000, 20000, 30000, 50000, 100000];
+
+/// An expression over tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagQuery {
+    /// Works with the tag.
+    Tag(String),
+    /// Works matching every expression.
+    All(Vec<TagQuery>),
+    /// Works matching any of the expressions.
+    Any(Vec<TagQuery>),
+    /// Works not matching the expression.
+    Not(Box<TagQuery>),
+}
+
+impl TagQuery {
+    /// Works with the tag.
+    pub fn tag<S: Into<String>>(tag: S) -> TagQuery {
+        TagQuery::Tag(tag.into())
+    }
+    /// Works matching every expression.
+    pub fn all<T: Into<TagQuery>, I: IntoIterator<Item = T>>(queries: I) -> TagQuery {
+        TagQuery::All(queries.into_iter().map(Into::into).collect())
+    }
+    /// Works matching any of the expressions.
+    pub fn any<T: Into<TagQuery>, I: IntoIterator<Item = T>>(queries: I) -> TagQuery {
+        TagQuery::Any(queries.into_iter().map(Into::into).collect())
+    }
+    /// Works not matching this expression.
+    pub fn negate(self) -> TagQuery {
+        TagQuery::Not(Box::new(self))
+    }
+    /// Works tagged as bookmarked at least `count` times, using the `users入り` tags.
+    /// `count` is rounded up to the next of `BOOKMARK_THRESHOLDS`, or down to the highest if above it.
+    pub fn min_bookmarks(count: usize) -> TagQuery {
+        let highest = BOOKMARK_THRESHOLDS[BOOKMARK_THRESHOLDS.len() - 1];
+        TagQuery::Any(
+            BOOKMARK_THRESHOLDS
+                .iter()
+                .filter(|&&threshold| threshold >= count.min(highest))
+                .map(|threshold| TagQuery::Tag(format!("{}users入り", threshold)))
+                .collect(),
+        )
+    }
+
+    // Pushes negations down to single tags, since Pixiv can only exclude tags.
+    fn normalize(self, negated: bool) -> TagQuery {
+        match self {
+            TagQuery::Tag(tag) => {
+                let tag = TagQuery::Tag(tag);
+                if negated {
+                    TagQuery::Not(Box::new(tag))
+                } else {
+                    tag
+                }
+            }
+            TagQuery::Not(query) => query.normalize(!negated),
+            TagQuery::All(queries) => {
+                let queries = queries.into_iter().map(|query| query.normalize(negated)).collect();
+                if negated {
+                    TagQuery::Any(queries)
+                } else {
+                    TagQuery::All(queries)
+                }
+            }
+            TagQuery::Any(queries) => {
+                let queries = queries.into_iter().map(|query| query.normalize(negated)).collect();
+                if negated {
+                    TagQuery::All(queries)
+                } else {
+                    TagQuery::Any(queries)
+                }
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), RequestError> {
+        match *self {
+            TagQuery::Tag(ref tag) => {
+                if tag.is_empty() || tag.chars().any(|c| c.is_whitespace() || c == '(' || c == ')') {
+                    return Err(RequestError::new(format!(
+                        "Tag `{}` can't be searched for, as it's empty or has whitespace or parentheses",
+                        tag
+                    )));
+                }
+                Ok(())
+            }
+            TagQuery::Not(ref query) => query.validate(),
+            TagQuery::All(ref queries) | TagQuery::Any(ref queries) => {
+                queries.iter().try_for_each(TagQuery::validate)
+            }
+        }
+    }
+
+    // Expects a normalized query.
+    fn render(&self, out: &mut String, nested: bool) {
+        match *self {
+            TagQuery::Tag(ref tag) => out.push_str(tag),
+            TagQuery::Not(ref query) => {
+                out.push('-');
+                query.render(out, true);
+            }
+            TagQuery::All(ref queries) | TagQuery::Any(ref queries) => {
+                let separator = if let TagQuery::All(_) = *self { " " } else { " OR " };
+                let queries: Vec<&TagQuery> = queries.iter().filter(|query| !query.is_empty()).collect();
+                let parenthesize = nested && queries.len() > 1;
+                if parenthesize {
+                    out.push('(');
+                }
+                for (i, query) in queries.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(separator);
+                    }
+                    query.render(out, true);
+                }
+                if parenthesize {
+                    out.push(')');
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match *self {
+            TagQuery::Tag(_) => false,
+            TagQuery::Not(ref query) => query.is_empty(),
+            TagQuery::All(ref queries) | TagQuery::Any(ref queries) => queries.iter().all(TagQuery::is_empty),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for TagQuery {
+    fn from(tag: &'a str) -> TagQuery {
+        TagQuery::tag(tag)
+    }
+}
+
+impl From<String> for TagQuery {
+    fn from(tag: String) -> TagQuery {
+        TagQuery::Tag(tag)
+    }
+}
+
+impl fmt::Display for TagQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = String::new();
+        self.clone().normalize(false).render(&mut out, false);
+        f.write_str(&out)
+    }
+}
+
+/// Builder of a tag search, turned into a request with `PixivRequestBuilder::search_query`.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    terms: Vec<TagQuery>,
+    exact: bool,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+    exclude_ai: bool,
+    error: Option<RequestError>,
+}
+
+impl Default for SearchQuery {
+    fn default() -> SearchQuery {
+        SearchQuery::new()
+    }
+}
+
+impl SearchQuery {
+    /// Creates an empty query, matching tags partially.
+    pub fn new() -> SearchQuery {
+        SearchQuery {
+            terms: Vec::new(),
+            exact: false,
+            start_date: None,
+            end_date: None,
+            exclude_ai: false,
+            error: None,
+        }
+    }
+    /// Requires works to have a tag.
+    pub fn tag<S: Into<String>>(self, tag: S) -> Self {
+        self.matching(TagQuery::tag(tag))
+    }
+    /// Requires works not to have a tag.
+    pub fn exclude<S: Into<String>>(self, tag: S) -> Self {
+        self.matching(TagQuery::tag(tag).negate())
+    }
+    /// Requires works to match an expression.
+    pub fn matching(mut self, query: TagQuery) -> Self {
+        self.terms.push(query);
+        self
+    }
+    /// Requires works to have been bookmarked at least `count` times. See `TagQuery::min_bookmarks`.
+    pub fn min_bookmarks(self, count: usize) -> Self {
+        self.matching(TagQuery::min_bookmarks(count))
+    }
+    /// Sets whether tags must match exactly (`SearchMode::ExactTag`) rather than partially (`SearchMode::Tag`).
+    #[inline]
+    pub fn exact(mut self, value: bool) -> Self {
+        self.exact = value;
+        self
+    }
+    /// Limits results to works posted within the dates (inclusive). Takes the same values as
+    /// `PixivRequestBuilder::date`; an invalid date is reported when the request is built.
+    pub fn date_range<S: DateParam, E: DateParam>(mut self, start: S, end: E) -> Self {
+        match (start.to_naive_date(), end.to_naive_date()) {
+            (Ok(start), Ok(end)) => {
+                self.start_date = Some(start);
+                self.end_date = Some(end);
+            }
+            (Err(e), _) | (_, Err(e)) => self.error = Some(e),
+        }
+        self
+    }
+    /// Sets whether AI-generated works are left out.
+    #[inline]
+    pub fn exclude_ai(mut self, value: bool) -> Self {
+        self.exclude_ai = value;
+        self
+    }
+    /// Get the search mode the query uses.
+    #[inline]
+    pub fn mode(&self) -> SearchMode {
+        if self.exact {
+            SearchMode::ExactTag
+        } else {
+            SearchMode::Tag
+        }
+    }
+
+    fn validate(&self) -> Result<(), RequestError> {
+        if let Some(ref e) = self.error {
+            return Err(e.clone());
+        }
+        self.terms.iter().try_for_each(TagQuery::validate)
+    }
+}
+
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        TagQuery::All(self.terms.clone()).fmt(f)
+    }
+}
+
+impl PixivRequestBuilder<SearchWorksParams> {
+    /// Used to build a tag search from a `SearchQuery`. The query sets `q`, `mode`, `start_date`, `end_date`
+    /// and `exclude_ai`; everything else is as in `search_works`.
+    pub fn search_query(query: SearchQuery) -> Self {
+        let mut builder = PixivRequestBuilder::search_works(query.to_string()).search_mode(query.mode());
+        builder.params.start_date = query.start_date;
+        builder.params.end_date = query.end_date;
+        builder.params.exclude_ai = query.exclude_ai;
+        if let Err(e) = query.validate() {
+            builder.error = Some(e);
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(TagQuery::all(vec!["a", "b"]).to_string(), "a b");
+        assert_eq!(TagQuery::any(vec!["a", "b"]).to_string(), "a OR b");
+        assert_eq!(
+            TagQuery::all(vec![TagQuery::tag("a"), TagQuery::any(vec!["b", "c"]), TagQuery::tag("d").negate()])
+                .to_string(),
+            "a (b OR c) -d"
+        );
+        // negations are pushed down to tags
+        assert_eq!(TagQuery::any(vec!["a", "b"]).negate().to_string(), "-a -b");
+        assert_eq!(TagQuery::all(vec!["a", "b"]).negate().to_string(), "-a OR -b");
+        assert_eq!(TagQuery::tag("a").negate().negate().to_string(), "a");
+        // empty and single-item groups aren't parenthesized
+        assert_eq!(
+            TagQuery::all(vec![TagQuery::any(Vec::<&str>::new()), TagQuery::any(vec!["a"]), TagQuery::tag("b")])
+                .to_string(),
+            "a b"
+        );
+        assert_eq!(TagQuery::min_bookmarks(40000).to_string(), "50000users入り OR 100000users入り");
+        assert_eq!(TagQuery::min_bookmarks(500000).to_string(), "100000users入り");
+    }
+
+    #[test]
+    fn test_search_query() {
+        let request = PixivRequestBuilder::search_query(
+            SearchQuery::new()
+                .tag("original")
+                .exclude("R-18")
+                .exact(true)
+                .date_range("2018-01-01", "2018-01-31")
+                .exclude_ai(true),
+        )
+        .build();
+        let query = request.url().query().unwrap();
+        assert!(query.starts_with("q=original+-R-18&page=1&per_page=30&start_date=2018-01-01&end_date=2018-01-31&mode=exact_tag&"));
+        assert!(query.contains("&search_ai_type=0&"));
+
+        let request = PixivRequestBuilder::search_query(SearchQuery::new().tag("original")).build();
+        let query = request.url().query().unwrap();
+        assert!(query.contains("&mode=tag&"));
+        assert!(!query.contains("start_date") && !query.contains("search_ai_type"));
+
+        assert!(PixivRequestBuilder::search_query(SearchQuery::new()).try_build().is_err());
+        assert!(PixivRequestBuilder::search_query(SearchQuery::new().tag("two words")).try_build().is_err());
+        assert!(PixivRequestBuilder::search_query(SearchQuery::new().tag("a").date_range("2018-02-30", "2018-03-01"))
+            .try_build()
+            .is_err());
+        assert!(PixivRequestBuilder::search_query(SearchQuery::new().tag("a").date_range("2018-03-01", "2018-02-01"))
+            .try_build()
+            .is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Walking an illustration or manga series in reading order.
+//!
+//! `illust_series()` returns a series a page at a time. A `Series` holds all of its works, oldest first,
+//! so a reader can step to the previous or next work from any of them.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # extern crate serde_json;
+//! # use pixiv::models::IllustSeriesResponse;
+//! # use pixiv::series::Series;
+//! # fn main() {
+//! #   let response: IllustSeriesResponse = serde_json::from_str(r#"{
+//! #       "illust_series_detail": {"id": 1, "title": "series"},
+//! #       "illusts": [
+//! #           {"id": 12, "title": "two", "create_date": "2018-02-02T00:00:00+09:00"},
+//! #           {"id": 11, "title": "one", "create_date": "2018-02-01T00:00:00+09:00"}
+//! #       ]
+//! #   }"#).unwrap();
+//!     let series = Series::from_responses(vec![response]).unwrap();
+//!     assert_eq!(series.next(11).map(|work| work.id), Some(12));
+//!     assert!(series.previous(11).is_none());
+//! # }
+//! ```
+
+use std::collections::HashSet;
+#[cfg(feature = "reqwest-client")]
+use std::thread;
+#[cfg(feature = "reqwest-client")]
+use std::time::Duration;
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+#[cfg(feature = "reqwest-client")]
+use models::IllustDetail;
+use models::{IllustSeries, IllustSeriesResponse, Work};
+#[cfg(feature = "reqwest-client")]
+use PixivRequestBuilder;
+
+/// A series with all of its works, in reading order.
+#[derive(Debug, Clone)]
+pub struct Series {
+    series: IllustSeries,
+    works: Vec<Work>,
+}
+
+impl Series {
+    /// Creates a series from its works in any order. They're sorted by when they were posted, then by id,
+    /// and duplicates are dropped.
+    pub fn new<I: IntoIterator<Item = Work>>(series: IllustSeries, works: I) -> Series {
+        let mut seen = HashSet::new();
+        let mut works: Vec<Work> = works.into_iter().filter(|work| seen.insert(work.id)).collect();
+        works.sort_by_key(|work| (work.created_time, work.id));
+        Series { series, works }
+    }
+    /// Creates a series from every page of `illust_series()`. Returns `None` if there are no responses.
+    pub fn from_responses<I: IntoIterator<Item = IllustSeriesResponse>>(responses: I) -> Option<Series> {
+        let mut detail = None;
+        let mut works = Vec::new();
+        for response in responses {
+            works.extend(response.illust_series_first_illust);
+            works.extend(response.illusts);
+            detail.get_or_insert(response.illust_series_detail);
+        }
+        detail.map(|detail| Series::new(detail, works))
+    }
+
+    /// Get the series.
+    #[inline]
+    pub fn series(&self) -> &IllustSeries {
+        &self.series
+    }
+    /// Get the works, oldest first.
+    #[inline]
+    pub fn works(&self) -> &[Work] {
+        &self.works
+    }
+    /// Get the number of works.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.works.len()
+    }
+    /// Whether the series has no works.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.works.is_empty()
+    }
+
+    /// Get the index of a work in reading order.
+    pub fn position(&self, work_id: usize) -> Option<usize> {
+        self.works.iter().position(|work| work.id == work_id)
+    }
+    /// Get a work of the series.
+    pub fn get(&self, work_id: usize) -> Option<&Work> {
+        self.position(work_id).map(|i| &self.works[i])
+    }
+    /// Get the work before this one, if it's in the series and not the first.
+    pub fn previous(&self, work_id: usize) -> Option<&Work> {
+        let i = self.position(work_id)?;
+        self.works.get(i.checked_sub(1)?)
+    }
+    /// Get the work after this one, if it's in the series and not the last.
+    pub fn next(&self, work_id: usize) -> Option<&Work> {
+        let i = self.position(work_id)?;
+        self.works.get(i + 1)
+    }
+
+    /// Fetches every page of a series, waiting `delay` between requests.
+    #[cfg(feature = "reqwest-client")]
+    pub fn fetch(pixiv: &Pixiv, series_id: usize, delay: Duration) -> Result<Series, FetchError> {
+        let mut responses: Vec<IllustSeriesResponse> = vec![pixiv.fetch(PixivRequestBuilder::illust_series(series_id))?];
+        let next_offset = |responses: &[IllustSeriesResponse]| {
+            responses.last()?.next_param("offset")?.parse::<usize>().ok()
+        };
+        while let Some(offset) = next_offset(&responses) {
+            thread::sleep(delay);
+            let response: IllustSeriesResponse = pixiv.fetch(PixivRequestBuilder::illust_series(series_id).offset(offset))?;
+            let done = response.illusts.is_empty();
+            responses.push(response);
+            if done {
+                break;
+            }
+        }
+        Ok(Series::from_responses(responses).expect("There is always a first response."))
+    }
+
+    /// Fetches the series a work belongs to, or `None` if it isn't part of one.
+    #[cfg(feature = "reqwest-client")]
+    pub fn fetch_for_work(pixiv: &Pixiv, work_id: usize, delay: Duration) -> Result<Option<Series>, FetchError> {
+        let detail: IllustDetail = pixiv.fetch(PixivRequestBuilder::illust_detail(work_id))?;
+        match detail.illust.series {
+            Some(series) => {
+                thread::sleep(delay);
+                Series::fetch(pixiv, series.id, delay).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, json};
+
+    use super::*;
+
+    #[test]
+    fn test_navigation() {
+        let page = |illusts: serde_json::Value| -> IllustSeriesResponse {
+            serde_json::from_value(json!({
+                "illust_series_detail": {"id": 1, "title": "series", "series_work_count": 3},
+                "illust_series_first_illust": {"id": 10, "title": "first", "create_date": "2018-02-01T00:00:00+09:00"},
+                "illusts": illusts,
+                "next_url": null
+            }))
+            .unwrap()
+        };
+        let series = Series::from_responses(vec![
+            page(json!([
+                {"id": 30, "title": "third", "type": "manga", "create_date": "2018-02-03T00:00:00+09:00",
+                 "sanity_level": 2, "series": {"id": 1, "title": "series"}},
+                {"id": 20, "title": "second", "create_date": "2018-02-02T00:00:00+09:00"}
+            ])),
+            page(json!([{"id": 10, "title": "first", "create_date": "2018-02-01T00:00:00+09:00"}])),
+        ])
+        .unwrap();
+
+        assert_eq!(series.series().series_work_count, 3);
+        assert_eq!(series.works().iter().map(|work| work.id).collect::<Vec<_>>(), vec![10, 20, 30]);
+        assert_eq!(series.previous(20).unwrap().id, 10);
+        assert_eq!(series.next(20).unwrap().id, 30);
+        assert!(series.next(30).is_none());
+        assert!(series.previous(10).is_none());
+        assert!(series.next(99).is_none());
+
+        let third = series.get(30).unwrap();
+        assert_eq!(third.sanity_level.as_deref(), Some("2"));
+        assert_eq!(third.series.as_ref().unwrap().id, 1);
+        assert!(Series::from_responses(Vec::new()).is_none());
+    }
+}
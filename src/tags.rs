@@ -0,0 +1,263 @@
+//! A local dictionary of tag translations, and grouping of works by normalised tag.
+//!
+//! Only some responses carry a `translated_name`, so a `TagDictionary` collects them as works come in
+//! and fills them into works which lack them. Tags are keyed by `text::normalize`, so `ｆａｔｅ` and
+//! `Fate` are the same entry.
+//!
+//! ```rust
+//! # extern crate pixiv;
+//! # extern crate serde_json;
+//! # use pixiv::models::Work;
+//! # use pixiv::tags::TagDictionary;
+//! # fn main() {
+//! #   let works: Vec<Work> = serde_json::from_str(r#"[
+//! #       {"id": 1, "title": "a", "tags": [{"name": "オリジナル", "translated_name": "original"}]},
+//! #       {"id": 2, "title": "b", "tags": ["オリジナル"]}
+//! #   ]"#).unwrap();
+//!     let mut dictionary = TagDictionary::new();
+//!     dictionary.merge_works(&works);
+//!     assert_eq!(dictionary.translation("オリジナル"), Some("original"));
+//!
+//!     let mut work = works[1].clone();
+//!     assert_eq!(dictionary.apply(&mut work), 1);
+//!     assert_eq!(work.tags[0].display_name(), "original");
+//! # }
+//! ```
+
+use std::collections::btree_map::{self, BTreeMap};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use serde_json;
+
+use models::{Tag, Work};
+use text;
+use utils;
+
+/// Error returned when loading or saving a `TagDictionary`.
+#[derive(Debug)]
+pub enum DictionaryError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl Error for DictionaryError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to read or write a tag dictionary."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            DictionaryError::Io(ref e) => Some(e),
+            DictionaryError::Json(ref e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DictionaryError::Io(ref e) => write!(f, "An error occurred while trying to access a tag dictionary. Reason: {}", e),
+            DictionaryError::Json(ref e) => write!(f, "Tag dictionary is invalid: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for DictionaryError {
+    fn from(e: io::Error) -> Self {
+        DictionaryError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for DictionaryError {
+    fn from(e: serde_json::Error) -> Self {
+        DictionaryError::Json(e)
+    }
+}
+
+/// Tags keyed by their normalised name. Saved as a JSON list of tags.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<Tag>", into = "Vec<Tag>")]
+pub struct TagDictionary {
+    tags: BTreeMap<String, Tag>,
+}
+
+impl TagDictionary {
+    /// Creates an empty dictionary.
+    pub fn new() -> TagDictionary {
+        TagDictionary::default()
+    }
+    /// Reads a dictionary written by `save()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<TagDictionary, DictionaryError> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+    /// Reads a dictionary written by `save()`, or creates an empty one if `path` doesn't exist.
+    pub fn load_or_new<P: AsRef<Path>>(path: P) -> Result<TagDictionary, DictionaryError> {
+        match File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(BufReader::new(file))?),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(TagDictionary::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+    /// Writes the dictionary as JSON. The file is replaced only once the dictionary is fully written, so an
+    /// interrupted save keeps the previous one.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), DictionaryError> {
+        utils::replace_file(path, |out| Ok(serde_json::to_writer(out, self)?))
+    }
+    /// Get the number of tags.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+    /// Whether there are no tags.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+    /// Iterates over the tags, ordered by normalised name.
+    pub fn iter(&self) -> btree_map::Values<'_, String, Tag> {
+        self.tags.values()
+    }
+
+    /// Adds a tag. A translation replaces the known one, but a tag without one never removes it.
+    /// Returns whether the dictionary changed.
+    pub fn insert(&mut self, tag: &Tag) -> bool {
+        match self.tags.entry(tag.normalized()) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(tag.clone());
+                true
+            }
+            btree_map::Entry::Occupied(mut entry) => match tag.translated_name {
+                Some(ref translated) if entry.get().translated_name.as_ref() != Some(translated) => {
+                    entry.get_mut().translated_name = Some(translated.clone());
+                    true
+                }
+                _ => false,
+            },
+        }
+    }
+    /// Sets the translation of a tag by hand, adding the tag if it's unknown.
+    pub fn set_translation<N: Into<String>, T: Into<String>>(&mut self, name: N, translated_name: T) {
+        self.insert(&Tag { name: name.into(), translated_name: Some(translated_name.into()) });
+    }
+    /// Adds the tags of a work.
+    pub fn merge_work(&mut self, work: &Work) {
+        for tag in &work.tags {
+            self.insert(tag);
+        }
+    }
+    /// Adds the tags of all the works.
+    pub fn merge_works<'a, I: IntoIterator<Item = &'a Work>>(&mut self, works: I) {
+        for work in works {
+            self.merge_work(work);
+        }
+    }
+    /// Adds the tags of another dictionary.
+    pub fn merge(&mut self, other: &TagDictionary) {
+        for tag in other.iter() {
+            self.insert(tag);
+        }
+    }
+
+    /// Get a tag by its name, compared after normalisation.
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.tags.get(&text::normalize(name))
+    }
+    /// Get the translation of a tag, if one is known.
+    pub fn translation(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(|tag| tag.translated_name.as_deref())
+    }
+    /// Get the tags with this translated name, compared after normalisation.
+    pub fn originals(&self, translated_name: &str) -> Vec<&Tag> {
+        let translated_name = text::normalize(translated_name);
+        self.tags
+            .values()
+            .filter(|tag| tag.translated_name.as_ref().is_some_and(|translated| text::normalize(translated) == translated_name))
+            .collect()
+    }
+    /// Fills in known translations for the tags of a work which lack one. Returns how many were filled in.
+    pub fn apply(&self, work: &mut Work) -> usize {
+        let mut filled = 0;
+        for tag in work.tags.iter_mut().filter(|tag| tag.translated_name.is_none()) {
+            if let Some(translated) = self.translation(&tag.name) {
+                tag.translated_name = Some(translated.to_owned());
+                filled += 1;
+            }
+        }
+        filled
+    }
+}
+
+impl From<Vec<Tag>> for TagDictionary {
+    fn from(tags: Vec<Tag>) -> Self {
+        let mut dictionary = TagDictionary::new();
+        for tag in &tags {
+            dictionary.insert(tag);
+        }
+        dictionary
+    }
+}
+
+impl From<TagDictionary> for Vec<Tag> {
+    fn from(dictionary: TagDictionary) -> Self {
+        dictionary.tags.into_values().collect()
+    }
+}
+
+/// Groups works by their tags' normalised names, so `ｆａｔｅ` and `Fate` end up in the same group.
+/// A work appears once in each group of its tags.
+pub fn group_by_tag<'a, I: IntoIterator<Item = &'a Work>>(works: I) -> BTreeMap<String, Vec<&'a Work>> {
+    let mut groups: BTreeMap<String, Vec<&'a Work>> = BTreeMap::new();
+    for work in works {
+        for tag in &work.tags {
+            let group = groups.entry(tag.normalized()).or_default();
+            if group.last().is_none_or(|last| last.id != work.id) {
+                group.push(work);
+            }
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn work(id: usize, tags: serde_json::Value) -> Work {
+        serde_json::from_value(json!({"id": id, "title": "title", "tags": tags})).unwrap()
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let mut dictionary = TagDictionary::new();
+        dictionary.merge_work(&work(1, json!([{"name": "オリジナル", "translated_name": "original"}, "Ｆａｔｅ"])));
+        dictionary.merge_work(&work(2, json!([{"name": "オリジナル", "translated_name": null}, "fate"])));
+        assert_eq!(dictionary.len(), 2);
+        assert_eq!(dictionary.translation("オリジナル"), Some("original"));
+        assert_eq!(dictionary.get("FATE").unwrap().name, "Ｆａｔｅ");
+        assert_eq!(dictionary.originals("Original")[0].name, "オリジナル");
+
+        dictionary.set_translation("fate", "Fate series");
+        let mut fate = work(3, json!(["ｆａｔｅ", "unknown"]));
+        assert_eq!(dictionary.apply(&mut fate), 1);
+        assert_eq!(fate.tags[0].translated_name.as_ref().unwrap(), "Fate series");
+        assert!(fate.tags[1].translated_name.is_none());
+
+        let saved = serde_json::to_string(&dictionary).unwrap();
+        let loaded: TagDictionary = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded.iter().collect::<Vec<_>>(), dictionary.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_group_by_tag() {
+        let works = vec![work(1, json!(["Fate", "ｆａｔｅ"])), work(2, json!(["ｆａｔｅ"])), work(3, json!(["other"]))];
+        let groups = group_by_tag(&works);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["fate", "other"]);
+        assert_eq!(groups["fate"].iter().map(|work| work.id).collect::<Vec<_>>(), vec![1, 2]);
+    }
+}
@@ -0,0 +1,118 @@
+//! Unicode normalisation for comparing user-written text such as tags and bad words.
+//!
+//! Text is normalised with NFKC, which folds full-width and half-width forms together
+//! (`ＡＢＣ` → `ABC`, `ｶﾞ` → `ガ`), and then lowercased.
+
+#[cfg(feature = "bad-words")]
+use std::ops::Range;
+
+#[cfg(feature = "bad-words")]
+use unicode_normalization::char::{canonical_combining_class, decompose_compatible};
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalises text for comparison: NFKC, then lowercase.
+pub fn normalize(text: &str) -> String {
+    text.nfkc().collect::<String>().to_lowercase()
+}
+
+/// Whether two strings are equal once normalised.
+pub fn eq_normalized(a: &str, b: &str) -> bool {
+    a == b || normalize(a) == normalize(b)
+}
+
+// Normalised text which remembers where each part of it came from in the original.
+#[cfg(feature = "bad-words")]
+#[derive(Debug, Clone)]
+pub(crate) struct Normalized {
+    pub text: String,
+    // (range in `text`, range in the original), in order.
+    segments: Vec<(Range<usize>, Range<usize>)>,
+}
+
+#[cfg(feature = "bad-words")]
+impl Normalized {
+    // Normalises a segment at a time, where a segment is a character plus any following ones that
+    // combine with it (e.g. a half-width voiced sound mark), so segments normalise independently.
+    pub fn new(original: &str) -> Normalized {
+        let mut text = String::with_capacity(original.len());
+        let mut segments = Vec::new();
+        let mut start = 0;
+        for (i, c) in original.char_indices().skip(1) {
+            if is_starter(c) {
+                push_segment(&mut text, &mut segments, original, start..i);
+                start = i;
+            }
+        }
+        if !original.is_empty() {
+            push_segment(&mut text, &mut segments, original, start..original.len());
+        }
+        Normalized { text, segments }
+    }
+
+    // Maps a range of the normalised text to the smallest range of the original covering it.
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        let mut covering = self
+            .segments
+            .iter()
+            .filter(|&(normalized, _)| normalized.start < range.end && range.start < normalized.end);
+        match covering.next() {
+            Some((_, first)) => {
+                let end = covering.next_back().map_or(first.end, |(_, last)| last.end);
+                first.start..end
+            }
+            None => 0..0,
+        }
+    }
+}
+
+#[cfg(feature = "bad-words")]
+fn push_segment(
+    text: &mut String,
+    segments: &mut Vec<(Range<usize>, Range<usize>)>,
+    original: &str,
+    range: Range<usize>,
+) {
+    let start = text.len();
+    text.push_str(&normalize(&original[range.clone()]));
+    if text.len() > start {
+        segments.push((start..text.len(), range));
+    }
+}
+
+// Whether the character doesn't combine with the one before it, even after compatibility decomposition.
+#[cfg(feature = "bad-words")]
+fn is_starter(c: char) -> bool {
+    let mut first = None;
+    decompose_compatible(c, |d| {
+        if first.is_none() {
+            first = Some(d);
+        }
+    });
+    first.is_none_or(|d| canonical_combining_class(d) == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("ＡＢＣ１２３"), "abc123");
+        assert_eq!(normalize("ｶﾞｷﾞ"), "ガギ");
+        assert!(eq_normalized("Ｆａｔｅ", "fate"));
+        assert!(!eq_normalized("fate", "fete"));
+    }
+
+    #[cfg(feature = "bad-words")]
+    #[test]
+    fn test_original_range() {
+        let original = "aｶﾞＢc";
+        let normalized = Normalized::new(original);
+        assert_eq!(normalized.text, "aガbc");
+        let start = normalized.text.find('ガ').unwrap();
+        let range = normalized.original_range(start..start + 'ガ'.len_utf8());
+        assert_eq!(&original[range], "ｶﾞ");
+        let start = normalized.text.find('b').unwrap();
+        assert_eq!(&original[normalized.original_range(start..start + 2)], "Ｂc");
+    }
+}
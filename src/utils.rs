@@ -2,7 +2,9 @@ use ::bytes;
 
 use ::std::fmt::{Write, Display};
 use ::std::borrow::Borrow;
-use ::std::io;
+use ::std::fs::{self, File};
+use ::std::io::{self, BufWriter, Write as IoWrite};
+use ::std::path::Path;
 
 pub fn comma_delimited<T: Display + ?Sized, B: Borrow<T>, I: IntoIterator<Item=B>>(iter: I) -> String {
     let mut iter = iter.into_iter();
@@ -16,6 +18,46 @@ pub fn comma_delimited<T: Display + ?Sized, B: Borrow<T>, I: IntoIterator<Item=B
     ret
 }
 
+// Escapes text for XML content and attribute values.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// Writes a file beside `path` and renames it over `path`, so an interrupted write leaves the old file intact.
+pub(crate) fn replace_file<P, E, F>(path: P, write: F) -> Result<(), E>
+where
+    P: AsRef<Path>,
+    E: From<io::Error>,
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), E>,
+{
+    let path = path.as_ref();
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let mut out = BufWriter::new(File::create(&temp)?);
+    write(&mut out)?;
+    out.flush()?;
+    out.get_ref().sync_all()?;
+    drop(out);
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+// 64-bit FNV-1a, used where a hash has to stay the same across builds.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3))
+}
+
 //const DEFAULT_CAPACITY: usize = 4096;
 const SMOL_CAPCITY: usize = 64;
 
@@ -0,0 +1,303 @@
+//! Watching `following_works()` for new works.
+//!
+//! A `WatchState` remembers the highest work id seen, and can be kept in a small JSON file so restarts
+//! don't deliver a work twice. Each poll of a `FollowingWatcher` fetches the newest works, paging back
+//! with `max_id` until it reaches ones already seen, and delivers the new ones oldest first.
+//!
+//! ```rust,no_run
+//! # extern crate pixiv;
+//! # extern crate reqwest;
+//! # use std::time::Duration;
+//! # use pixiv::client::Pixiv;
+//! # use pixiv::watcher::FollowingWatcher;
+//! # use reqwest::Client;
+//! # fn main() {
+//! #   let client = Client::new();
+//! #   let mut pixiv: Pixiv = Pixiv::new(&client);
+//! #   pixiv.login("username", "password");
+//!     let mut watcher = FollowingWatcher::with_state_file(&pixiv, "watch.json").expect("Failed to read state.");
+//!     let error = watcher.run(
+//!         Duration::from_secs(300),
+//!         |work| println!("New: {}", work),
+//!         |error| eprintln!("Poll failed: {}", error),
+//!     );
+//!     eprintln!("Stopped watching: {}", error);
+//! # }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+#[cfg(feature = "reqwest-client")]
+use std::path::PathBuf;
+#[cfg(feature = "reqwest-client")]
+use std::sync::mpsc::Sender;
+#[cfg(feature = "reqwest-client")]
+use std::thread;
+#[cfg(feature = "reqwest-client")]
+use std::time::Duration;
+
+use serde_json;
+
+#[cfg(feature = "reqwest-client")]
+use client::{FetchError, Pixiv};
+#[cfg(feature = "reqwest-client")]
+use models::PixivResponse;
+use models::Work;
+use utils;
+#[cfg(feature = "reqwest-client")]
+use PixivRequestBuilder;
+
+/// Error returned when loading or saving a `WatchState`, or polling for new works.
+#[derive(Debug)]
+pub enum WatchError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    #[cfg(feature = "reqwest-client")]
+    Fetch(FetchError),
+}
+
+impl Error for WatchError {
+    fn description(&self) -> &str {
+        "An error occurred while trying to watch for new works."
+    }
+
+    fn cause(&self) -> Option<&dyn Error> {
+        match *self {
+            WatchError::Io(ref e) => Some(e),
+            WatchError::Json(ref e) => Some(e),
+            #[cfg(feature = "reqwest-client")]
+            WatchError::Fetch(ref e) => Some(e),
+        }
+    }
+}
+
+impl fmt::Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WatchError::Io(ref e) => write!(f, "An error occurred while trying to access the watch state. Reason: {}", e),
+            WatchError::Json(ref e) => write!(f, "Watch state is invalid: {}", e),
+            #[cfg(feature = "reqwest-client")]
+            WatchError::Fetch(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for WatchError {
+    fn from(e: io::Error) -> Self {
+        WatchError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for WatchError {
+    fn from(e: serde_json::Error) -> Self {
+        WatchError::Json(e)
+    }
+}
+
+#[cfg(feature = "reqwest-client")]
+impl From<FetchError> for WatchError {
+    fn from(e: FetchError) -> Self {
+        WatchError::Fetch(e)
+    }
+}
+
+/// What a watcher has seen so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchState {
+    /// The highest work id seen, or `None` before the first poll.
+    #[serde(default)]
+    pub last_seen: Option<usize>,
+}
+
+impl WatchState {
+    /// Creates a state which hasn't seen anything.
+    pub fn new() -> WatchState {
+        WatchState::default()
+    }
+    /// Reads a state written by `save()`, or creates a new one if `path` doesn't exist.
+    pub fn load_or_new<P: AsRef<Path>>(path: P) -> Result<WatchState, WatchError> {
+        match File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(BufReader::new(file))?),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(WatchState::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+    /// Writes the state as JSON. The file is replaced only once the state is fully written, so an
+    /// interrupted save keeps the previous state.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), WatchError> {
+        utils::replace_file(path, |out| Ok(serde_json::to_writer(out, self)?))
+    }
+
+    /// Whether a work is newer than anything seen. Everything is old before the first poll.
+    pub fn is_new(&self, work_id: usize) -> bool {
+        self.last_seen.is_some_and(|last_seen| work_id > last_seen)
+    }
+
+    /// Takes the works not seen yet out of a poll's works, oldest first and without duplicates, and marks
+    /// them seen. The first poll only marks the newest work, so a new watcher doesn't deliver the whole feed.
+    pub fn take_new<I: IntoIterator<Item = Work>>(&mut self, works: I) -> Vec<Work> {
+        let first = self.last_seen.is_none();
+        let mut new: Vec<Work> = Vec::new();
+        for work in works {
+            if first {
+                self.last_seen = self.last_seen.max(Some(work.id));
+            } else if self.is_new(work.id) && new.iter().all(|seen| seen.id != work.id) {
+                new.push(work);
+            }
+        }
+        new.sort_by_key(|work| work.id);
+        if let Some(newest) = new.last() {
+            self.last_seen = Some(newest.id);
+        }
+        new
+    }
+}
+
+/// Polls `following_works()` for works newer than its `WatchState`.
+#[cfg(feature = "reqwest-client")]
+#[derive(Debug)]
+pub struct FollowingWatcher<'a> {
+    pixiv: &'a Pixiv,
+    state: WatchState,
+    path: Option<PathBuf>,
+    max_pages: usize,
+    delay: Duration,
+}
+
+#[cfg(feature = "reqwest-client")]
+impl<'a> FollowingWatcher<'a> {
+    /// Creates a watcher which keeps its state in memory only.
+    pub fn new(pixiv: &'a Pixiv, state: WatchState) -> FollowingWatcher<'a> {
+        FollowingWatcher {
+            pixiv,
+            state,
+            path: None,
+            max_pages: 5,
+            delay: Duration::from_secs(1),
+        }
+    }
+    /// Creates a watcher which loads its state from `path` if it exists, and saves it there after each poll.
+    pub fn with_state_file<P: AsRef<Path>>(pixiv: &'a Pixiv, path: P) -> Result<FollowingWatcher<'a>, WatchError> {
+        let state = WatchState::load_or_new(&path)?;
+        Ok(FollowingWatcher {
+            path: Some(path.as_ref().to_owned()),
+            ..FollowingWatcher::new(pixiv, state)
+        })
+    }
+    /// Sets how many pages a poll fetches at most before giving up on reaching seen works (default: 5).
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+    /// Sets how long to wait between the requests of a poll (default: 1 second).
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+    /// Get the state.
+    #[inline]
+    pub fn state(&self) -> &WatchState {
+        &self.state
+    }
+
+    /// Fetches the works posted since the last poll, oldest first, and saves the state.
+    ///
+    /// The state is saved before the works are returned, so delivery is at most once: works lost to a
+    /// crash after the save aren't fetched again.
+    pub fn poll(&mut self) -> Result<Vec<Work>, WatchError> {
+        let mut works = Vec::new();
+        let mut max_id = None;
+        for page in 0..self.max_pages {
+            if page > 0 {
+                thread::sleep(self.delay);
+            }
+            let mut builder = PixivRequestBuilder::following_works();
+            if let Some(max_id) = max_id {
+                builder = builder.max_id(max_id);
+            }
+            let response: PixivResponse<Work> = self.pixiv.fetch(builder)?;
+            let oldest = response.response.iter().map(|work| work.id).min();
+            let reached_seen = response.response.iter().any(|work| !self.state.is_new(work.id));
+            works.extend(response.response);
+            match oldest {
+                Some(oldest) if oldest > 0 && !reached_seen => max_id = Some(oldest - 1),
+                _ => break,
+            }
+        }
+        let new = self.state.take_new(works);
+        if let Some(ref path) = self.path {
+            self.state.save(path)?;
+        }
+        Ok(new)
+    }
+    /// Polls once, passing each new work to `callback`. Returns how many there were.
+    pub fn poll_with<F: FnMut(Work)>(&mut self, mut callback: F) -> Result<usize, WatchError> {
+        let new = self.poll()?;
+        let count = new.len();
+        for work in new {
+            callback(work);
+        }
+        Ok(count)
+    }
+    /// Polls once, sending each new work down a channel. Works the receiver hung up before getting are dropped.
+    pub fn poll_to(&mut self, sender: &Sender<Work>) -> Result<usize, WatchError> {
+        self.poll_with(|work| {
+            let _ = sender.send(work);
+        })
+    }
+    /// Polls every `interval`, passing each new work to `callback`. A poll whose fetch fails is passed to
+    /// `on_error` and tried again after the next interval. Stops only if the state can't be saved, and
+    /// returns that error.
+    pub fn run<F, E>(&mut self, interval: Duration, mut callback: F, mut on_error: E) -> WatchError
+    where
+        F: FnMut(Work),
+        E: FnMut(&WatchError),
+    {
+        loop {
+            match self.poll_with(&mut callback) {
+                Err(e @ WatchError::Fetch(_)) => on_error(&e),
+                Err(e) => return e,
+                Ok(_) => {}
+            }
+            thread::sleep(interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use serde_json::{self, json};
+
+    use super::*;
+
+    fn works(ids: &[usize]) -> Vec<Work> {
+        ids.iter().map(|&id| serde_json::from_value(json!({"id": id, "title": id.to_string()})).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_take_new() {
+        let mut state = WatchState::new();
+        assert!(state.take_new(works(&[30, 20, 10])).is_empty());
+        assert_eq!(state.last_seen, Some(30));
+
+        let new = state.take_new(works(&[50, 40, 40, 30, 5]));
+        assert_eq!(new.iter().map(|work| work.id).collect::<Vec<_>>(), vec![40, 50]);
+        assert_eq!(state.last_seen, Some(50));
+        assert!(state.take_new(works(&[50, 40])).is_empty());
+        assert_eq!(state.last_seen, Some(50));
+
+        let path = env::temp_dir().join(format!("pixiv-watch-test-{}.json", ::std::process::id()));
+        assert_eq!(WatchState::load_or_new(&path).unwrap(), WatchState::new());
+        WatchState::new().save(&path).unwrap();
+        state.save(&path).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+        assert_eq!(WatchState::load_or_new(&path).unwrap(), state);
+        fs::remove_file(path).unwrap();
+    }
+}